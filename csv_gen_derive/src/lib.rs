@@ -0,0 +1,261 @@
+//! `#[derive(CsvGen)]`, re-exported by `csv_gen`.
+//!
+//! The derive implements `csv_gen::record::CsvRecord`: one column per field, typed by the
+//! field's `#[csvgen(...)]` attributes or, failing those, by the field's type.
+
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use syn::{
+    Data, DeriveInput, Expr, ExprLit, ExprRange, ExprUnary, Field, Fields, GenericArgument, Lit, LitStr,
+    PathArguments, RangeLimits, Type, UnOp, parse_macro_input,
+};
+
+#[proc_macro_derive(CsvGen, attributes(csvgen))]
+pub fn derive_csv_gen(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand(input).unwrap_or_else(syn::Error::into_compile_error).into()
+}
+
+fn expand(input: DeriveInput) -> syn::Result<TokenStream2> {
+    let fields = match &input.data {
+        Data::Struct(data) => match &data.fields {
+            Fields::Named(fields) => &fields.named,
+            _ => return Err(syn::Error::new_spanned(&input.ident, "CsvGen needs a struct with named fields")),
+        },
+        _ => return Err(syn::Error::new_spanned(&input.ident, "CsvGen can only be derived for structs")),
+    };
+
+    let mut columns = Vec::new();
+    let mut parsers = Vec::new();
+    for (index, field) in fields.iter().enumerate() {
+        let ident = field.ident.as_ref().expect("named field");
+        let attrs = FieldAttrs::parse(field)?;
+        let inner = option_inner(&field.ty);
+        if attrs.null_rate.is_some() && inner.is_none() {
+            return Err(syn::Error::new_spanned(&field.ty, "null_rate needs an Option field"));
+        }
+
+        let name = attrs.rename.clone().unwrap_or_else(|| ident.to_string());
+        let data_type = attrs.data_type(inner.unwrap_or(&field.ty))?;
+        let mut column = quote! { ::csv_gen::data::types::ColumnSpec::new(#name, #data_type) };
+        if let Some(width) = attrs.width {
+            column = quote! { #column.with_width(#width) };
+        }
+        if let Some(null_rate) = attrs.null_rate {
+            column = quote! { #column.with_null_rate(#null_rate) };
+        }
+        columns.push(column);
+
+        parsers.push(if inner.is_some() {
+            quote! { #ident: ::csv_gen::record::parse_optional_field(row, #index, #name)? }
+        } else {
+            quote! { #ident: ::csv_gen::record::parse_field(row, #index, #name)? }
+        });
+    }
+
+    let ident = &input.ident;
+    let (impl_generics, type_generics, where_clause) = input.generics.split_for_impl();
+    Ok(quote! {
+        impl #impl_generics ::csv_gen::record::CsvRecord for #ident #type_generics #where_clause {
+            fn schema_spec() -> ::csv_gen::Result<::csv_gen::data::types::SchemaSpec> {
+                Ok(::csv_gen::data::types::SchemaSpec {
+                    columns: vec![#(#columns),*],
+                    sample_rows: 0,
+                })
+            }
+
+            fn from_row(row: &[String]) -> ::csv_gen::Result<Self> {
+                Ok(Self { #(#parsers),* })
+            }
+        }
+    })
+}
+
+/// What a `#[csvgen(...)]` attribute asks a column to hold.
+enum Kind {
+    Unique,
+    Email,
+    Domain,
+    Name,
+    Date,
+    Pattern(LitStr),
+    OneOf(Vec<String>),
+    Range(i128, i128),
+}
+
+#[derive(Default)]
+struct FieldAttrs {
+    kind: Option<Kind>,
+    width: Option<usize>,
+    null_rate: Option<f64>,
+    rename: Option<String>,
+}
+
+impl FieldAttrs {
+    fn parse(field: &Field) -> syn::Result<Self> {
+        let mut attrs = FieldAttrs::default();
+        for attr in field.attrs.iter().filter(|a| a.path().is_ident("csvgen")) {
+            attr.parse_nested_meta(|meta| {
+                let path = &meta.path;
+                let kind = if path.is_ident("unique") {
+                    Kind::Unique
+                } else if path.is_ident("email") {
+                    Kind::Email
+                } else if path.is_ident("domain") {
+                    Kind::Domain
+                } else if path.is_ident("name") {
+                    Kind::Name
+                } else if path.is_ident("date") {
+                    Kind::Date
+                } else if path.is_ident("pattern") {
+                    Kind::Pattern(meta.value()?.parse()?)
+                } else if path.is_ident("one_of") {
+                    let values: LitStr = meta.value()?.parse()?;
+                    let values: Vec<String> = values.value().split(',').map(|v| v.trim().to_string()).collect();
+                    if values.iter().any(String::is_empty) {
+                        return Err(meta.error("one_of values cannot be empty"));
+                    }
+                    Kind::OneOf(values)
+                } else if path.is_ident("range") {
+                    let range: ExprRange = meta.value()?.parse()?;
+                    range_bounds(&range)?
+                } else if path.is_ident("width") {
+                    let width: syn::LitInt = meta.value()?.parse()?;
+                    attrs.width = Some(width.base10_parse()?);
+                    return Ok(());
+                } else if path.is_ident("null_rate") {
+                    let rate = match meta.value()?.parse::<Lit>()? {
+                        Lit::Float(rate) => rate.base10_parse()?,
+                        Lit::Int(rate) => rate.base10_parse::<u8>()? as f64,
+                        other => return Err(syn::Error::new_spanned(other, "expected a number")),
+                    };
+                    if !(0.0..=1.0).contains(&rate) {
+                        return Err(meta.error("null_rate must be between 0 and 1"));
+                    }
+                    attrs.null_rate = Some(rate);
+                    return Ok(());
+                } else if path.is_ident("rename") {
+                    let name: LitStr = meta.value()?.parse()?;
+                    attrs.rename = Some(name.value());
+                    return Ok(());
+                } else {
+                    return Err(meta.error("unknown csvgen attribute"));
+                };
+                if attrs.kind.replace(kind).is_some() {
+                    return Err(meta.error("a field can only have one data type"));
+                }
+                Ok(())
+            })?;
+        }
+        Ok(attrs)
+    }
+
+    /// The column's `DataType`, from the attribute or else from the field's type.
+    fn data_type(&self, ty: &Type) -> syn::Result<TokenStream2> {
+        let int_bounds = type_name(ty).as_deref().and_then(int_bounds);
+        let range = |min: i128, max: i128| {
+            let (min, max) = (min as i64, max as i64);
+            quote! { ::csv_gen::data::types::DataType::Range { min: #min, max: #max } }
+        };
+        Ok(match &self.kind {
+            Some(Kind::Unique) => quote! { ::csv_gen::data::types::DataType::UniqueId },
+            Some(Kind::Email) => quote! { ::csv_gen::data::types::DataType::Email },
+            Some(Kind::Domain) => quote! { ::csv_gen::data::types::DataType::Domain },
+            Some(Kind::Name) => quote! { ::csv_gen::data::types::DataType::Name },
+            Some(Kind::Date) => quote! { ::csv_gen::data::types::DataType::Date },
+            Some(Kind::Pattern(source)) => quote! {
+                ::csv_gen::data::types::DataType::Pattern(
+                    ::csv_gen::data::pattern::Pattern::new(#source)
+                        .map_err(|e| ::csv_gen::Error::InvalidSchema(e.to_string()))?,
+                )
+            },
+            Some(Kind::OneOf(values)) => {
+                quote! { ::csv_gen::data::types::DataType::Enum(vec![#(#values.to_string()),*]) }
+            }
+            Some(Kind::Range(min, max)) => {
+                if let Some((lo, hi)) = int_bounds
+                    && (*min < lo || *max > hi)
+                {
+                    return Err(syn::Error::new_spanned(ty, "range does not fit in the field's type"));
+                }
+                range(*min, *max)
+            }
+            None => match int_bounds {
+                Some((lo, hi)) => range(lo.max(i64::MIN as i128), hi.min(i64::MAX as i128)),
+                None => match type_name(ty).as_deref() {
+                    Some("f32" | "f64") => quote! { ::csv_gen::data::types::DataType::Decimal { scale: 2 } },
+                    Some("bool") => quote! {
+                        ::csv_gen::data::types::DataType::Enum(vec!["false".to_string(), "true".to_string()])
+                    },
+                    _ => quote! { ::csv_gen::data::types::DataType::String },
+                },
+            },
+        })
+    }
+}
+
+/// Inclusive bounds of a `range = a..b` or `a..=b` attribute.
+fn range_bounds(range: &ExprRange) -> syn::Result<Kind> {
+    let (Some(start), Some(end)) = (&range.start, &range.end) else {
+        return Err(syn::Error::new_spanned(range, "range needs both bounds"));
+    };
+    let min = int_literal(start)?;
+    let max = match range.limits {
+        RangeLimits::HalfOpen(_) => int_literal(end)? - 1,
+        RangeLimits::Closed(_) => int_literal(end)?,
+    };
+    if min > max {
+        return Err(syn::Error::new_spanned(range, "range is empty"));
+    }
+    if min < i64::MIN as i128 || max > i64::MAX as i128 {
+        return Err(syn::Error::new_spanned(range, "range bounds must fit in an i64"));
+    }
+    Ok(Kind::Range(min, max))
+}
+
+fn int_literal(expr: &Expr) -> syn::Result<i128> {
+    match expr {
+        Expr::Lit(ExprLit { lit: Lit::Int(value), .. }) => value.base10_parse(),
+        Expr::Unary(ExprUnary { op: UnOp::Neg(_), expr, .. }) => Ok(-int_literal(expr)?),
+        Expr::Paren(paren) => int_literal(&paren.expr),
+        _ => Err(syn::Error::new_spanned(expr, "expected an integer literal")),
+    }
+}
+
+/// `T` for an `Option<T>` field.
+fn option_inner(ty: &Type) -> Option<&Type> {
+    let Type::Path(path) = ty else { return None };
+    let segment = path.path.segments.last()?;
+    if segment.ident != "Option" {
+        return None;
+    }
+    match &segment.arguments {
+        PathArguments::AngleBracketed(args) => match args.args.first()? {
+            GenericArgument::Type(inner) => Some(inner),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+fn type_name(ty: &Type) -> Option<String> {
+    match ty {
+        Type::Path(path) => path.path.segments.last().map(|s| s.ident.to_string()),
+        _ => None,
+    }
+}
+
+fn int_bounds(name: &str) -> Option<(i128, i128)> {
+    Some(match name {
+        "i8" => (i8::MIN as i128, i8::MAX as i128),
+        "i16" => (i16::MIN as i128, i16::MAX as i128),
+        "i32" => (i32::MIN as i128, i32::MAX as i128),
+        "i64" | "isize" | "i128" => (i64::MIN as i128, i64::MAX as i128),
+        "u8" => (0, u8::MAX as i128),
+        "u16" => (0, u16::MAX as i128),
+        "u32" => (0, u32::MAX as i128),
+        "u64" | "usize" | "u128" => (0, i64::MAX as i128),
+        _ => return None,
+    })
+}
@@ -0,0 +1,323 @@
+use crate::data::DataGenerator;
+use crate::data::headers::{self, HeaderStrategy};
+use crate::data::planner::{self, ColumnOptions, Plan, Solved, SolverObjective};
+use crate::data::types::{ColumnSpec, CsvSchema, DataType, FixedColumn, SchemaSpec};
+use crate::error::{Error, Result};
+use crate::generator::rows::RowGenerator;
+use crate::generator::size_based::CsvGenerator;
+use crate::utils::progress::{Progress, ProgressEvent};
+#[cfg(feature = "async")]
+use crate::generator::stream::CsvStream;
+use std::collections::HashSet;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::iter::Take;
+use std::ops::Range;
+use std::path::Path;
+
+/// A planned CSV, ready to be written any number of times.
+///
+/// ```
+/// use csv_gen::CsvGen;
+/// use csv_gen::data::types::DataType;
+///
+/// let csv = CsvGen::builder()
+///     .size(64 * 1024)
+///     .rows(500)
+///     .column("id", DataType::UniqueId)
+///     .column("email", DataType::Email)
+///     .column("notes", DataType::String)
+///     .seed(42)
+///     .build()?;
+///
+/// let mut output = Vec::new();
+/// assert_eq!(csv.write_to(&mut output)?, 500);
+/// # Ok::<(), csv_gen::Error>(())
+/// ```
+#[derive(Debug, Clone)]
+pub struct CsvGen {
+    plan: Plan,
+    seed: Option<u64>,
+    progress: Progress,
+    threads: usize,
+}
+
+impl CsvGen {
+    pub fn builder() -> CsvGenBuilder {
+        CsvGenBuilder::default()
+    }
+
+    pub fn schema(&self) -> &CsvSchema {
+        &self.plan.schema
+    }
+
+    pub fn rows(&self) -> usize {
+        self.plan.num_rows
+    }
+
+    /// Expected file size in bytes: the requested size, or the derived one.
+    pub fn planned_size(&self) -> usize {
+        self.plan.target_size
+    }
+
+    /// Which of size and rows the planner derived.
+    pub fn solved(&self) -> Solved {
+        self.plan.solved
+    }
+
+    /// The planned rows, generated in memory. With a seed, these are the rows
+    /// [`CsvGen::write_to`] writes.
+    pub fn iter(&self) -> Take<RowGenerator> {
+        self.row_generator().take(self.plan.num_rows)
+    }
+
+    /// The planned rows `rows.start` up to `rows.end`, counting from 0, without generating
+    /// the rows before them. With a seed, these are the same rows [`CsvGen::iter`] yields.
+    /// The range is clipped to the planned rows, so rows past them are left out.
+    pub fn iter_range(&self, rows: Range<usize>) -> Take<RowGenerator> {
+        let mut generator = self.row_generator();
+        generator.seek(rows.start as u64);
+        generator.take(rows.end.min(self.plan.num_rows).saturating_sub(rows.start))
+    }
+
+    /// The CSV as a stream of encoded chunks, generated as the consumer polls.
+    #[cfg(feature = "async")]
+    pub fn stream(&self) -> CsvStream {
+        CsvStream::new(self.row_generator(), self.plan.num_rows)
+    }
+
+    /// Like [`CsvGen::write_to`], without blocking the async runtime.
+    #[cfg(feature = "async")]
+    pub async fn write_to_async<W: tokio::io::AsyncWrite + Unpin>(&self, output: W) -> Result<usize> {
+        Ok(self.stream().write_to(output).await?)
+    }
+
+    /// Writes the CSV to `output`, returning the number of data rows. With a seed, every call
+    /// writes the same bytes.
+    pub fn write_to<W: Write>(&self, output: W) -> Result<usize> {
+        self.generator().write(output, self.plan.num_rows).map_err(Error::from_generation)
+    }
+
+    /// Writes rows `rows.start` up to `rows.end` as they appear in the full CSV, with the
+    /// header only if the range starts at row 0, so that with a seed the pieces of a file
+    /// written this way concatenate into the file [`CsvGen::write_to`] writes. A range that is
+    /// reversed or goes past the planned rows is an [`Error::ImpossibleConstraints`].
+    pub fn write_range_to<W: Write>(&self, output: W, rows: Range<usize>) -> Result<usize> {
+        if rows.start > rows.end || rows.end > self.plan.num_rows {
+            return Err(Error::ImpossibleConstraints(format!(
+                "rows {}..{} are not within the {} planned rows",
+                rows.start, rows.end, self.plan.num_rows
+            )));
+        }
+        self.generator().write_range(output, rows).map_err(Error::from_generation)
+    }
+
+    pub fn write_to_file(&self, path: impl AsRef<Path>) -> Result<usize> {
+        let mut output = BufWriter::new(File::create(path)?);
+        let rows = self.write_to(&mut output)?;
+        output.flush()?;
+        Ok(rows)
+    }
+
+    fn row_generator(&self) -> RowGenerator {
+        let data_generator = match self.seed {
+            Some(seed) => DataGenerator::with_seed(seed),
+            None => DataGenerator::new(),
+        };
+        RowGenerator::new(self.plan.schema.clone(), data_generator)
+    }
+
+    fn generator(&self) -> CsvGenerator {
+        let generator = CsvGenerator::new(self.plan.schema.clone())
+            .with_progress(self.progress.clone())
+            .with_threads(self.threads);
+        match self.seed {
+            Some(seed) => generator.with_seed(seed),
+            None => generator,
+        }
+    }
+}
+
+/// Collects constraints for a [`CsvGen`]; see [`CsvGen::builder`].
+///
+/// Set a size, a row count or both; the planner derives whichever is missing. Without
+/// explicit columns, columns are generated like the command line does: an ID column, any
+/// email, domain and fixed columns, then free-text columns up to the column bounds.
+#[derive(Debug, Clone)]
+pub struct CsvGenBuilder {
+    size: Option<usize>,
+    rows: Option<usize>,
+    options: ColumnOptions,
+    columns: Vec<ColumnSpec>,
+    seed: Option<u64>,
+    progress: Progress,
+    threads: usize,
+}
+
+impl Default for CsvGenBuilder {
+    fn default() -> Self {
+        Self {
+            size: None,
+            rows: None,
+            options: ColumnOptions {
+                min_columns: 2,
+                max_columns: 100,
+                email_columns: 0,
+                domain_columns: 0,
+                fixed_columns: Vec::new(),
+                header_strategy: HeaderStrategy::default(),
+                objective: SolverObjective::default(),
+            },
+            columns: Vec::new(),
+            seed: None,
+            progress: Progress::default(),
+            threads: 1,
+        }
+    }
+}
+
+impl CsvGenBuilder {
+    /// Target file size in bytes.
+    pub fn size(mut self, bytes: usize) -> Self {
+        self.size = Some(bytes);
+        self
+    }
+
+    pub fn rows(mut self, rows: usize) -> Self {
+        self.rows = Some(rows);
+        self
+    }
+
+    /// Exact number of generated columns.
+    pub fn columns(mut self, columns: usize) -> Self {
+        self.options.min_columns = columns;
+        self.options.max_columns = columns;
+        self
+    }
+
+    pub fn min_columns(mut self, columns: usize) -> Self {
+        self.options.min_columns = columns;
+        self
+    }
+
+    pub fn max_columns(mut self, columns: usize) -> Self {
+        self.options.max_columns = columns;
+        self
+    }
+
+    pub fn email_columns(mut self, columns: usize) -> Self {
+        self.options.email_columns = columns;
+        self
+    }
+
+    pub fn domain_columns(mut self, columns: usize) -> Self {
+        self.options.domain_columns = columns;
+        self
+    }
+
+    /// Adds a generated column of a known width, such as a pattern or timestamp column.
+    pub fn fixed_column(mut self, column: FixedColumn) -> Self {
+        self.options.fixed_columns.push(column);
+        self
+    }
+
+    pub fn header_strategy(mut self, strategy: HeaderStrategy) -> Self {
+        self.options.header_strategy = strategy;
+        self
+    }
+
+    pub fn objective(mut self, objective: SolverObjective) -> Self {
+        self.options.objective = objective;
+        self
+    }
+
+    /// Adds an explicit column at its type's natural width. Once any explicit column is
+    /// added, only explicit columns are written; free-text ones stretch to hit the size.
+    pub fn column(self, name: impl Into<String>, data_type: DataType) -> Self {
+        self.column_spec(ColumnSpec::new(name, data_type))
+    }
+
+    /// Adds an explicit column with full control over width and null rate.
+    pub fn column_spec(mut self, column: ColumnSpec) -> Self {
+        self.columns.push(column);
+        self
+    }
+
+    /// Uses every column of a schema file as an explicit column.
+    pub fn schema(mut self, spec: SchemaSpec) -> Self {
+        self.columns.extend(spec.columns);
+        self
+    }
+
+    /// Seeds the value generator so output is reproducible.
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.seed = Some(seed);
+        self
+    }
+
+    /// How [`CsvGen::write_to`] reports progress; a bar on the terminal by default.
+    pub fn progress(mut self, progress: Progress) -> Self {
+        self.progress = progress;
+        self
+    }
+
+    /// Generates rows on `threads` threads when writing; the output is the same for any number.
+    pub fn threads(mut self, threads: usize) -> Self {
+        self.threads = threads.max(1);
+        self
+    }
+
+    /// Calls `callback` with the rows and bytes written about once a second, and once when
+    /// a write is done, instead of showing a bar.
+    pub fn on_progress(self, callback: impl Fn(&ProgressEvent) + Send + Sync + 'static) -> Self {
+        self.progress(Progress::callback(callback))
+    }
+
+    pub fn build(self) -> Result<CsvGen> {
+        if self.size.is_none() && self.rows.is_none() {
+            return Err(Error::ImpossibleConstraints("set a size, a row count, or both".to_string()));
+        }
+        if self.rows == Some(0) {
+            return Err(Error::ImpossibleConstraints("the row count must be positive".to_string()));
+        }
+
+        let plan = if self.columns.is_empty() {
+            planner::plan_generated(self.size, self.rows, &self.options)
+        } else {
+            self.validate_columns()?;
+            let spec = SchemaSpec {
+                columns: self.columns,
+                sample_rows: 0,
+            };
+            planner::plan_from_spec(&spec, self.size, self.rows)
+        }?;
+
+        Ok(CsvGen { plan, seed: self.seed, progress: self.progress, threads: self.threads })
+    }
+
+    fn validate_columns(&self) -> Result<()> {
+        let invalid = |message: String| Err(Error::InvalidSchema(message));
+
+        if self.options.email_columns + self.options.domain_columns + self.options.fixed_columns.len() > 0 {
+            return invalid("explicit columns cannot be mixed with generated email, domain or fixed columns".to_string());
+        }
+        let mut names = HashSet::new();
+        for column in &self.columns {
+            if let Err(e) = headers::validate_name(&column.name) {
+                return invalid(e.to_string());
+            }
+            if !names.insert(column.name.as_str()) {
+                return invalid(format!("duplicate column name '{}'", column.name));
+            }
+            if !(0.0..=1.0).contains(&column.null_rate) {
+                return invalid(format!("null rate of '{}' must be between 0 and 1", column.name));
+            }
+            if let DataType::Enum(values) = &column.data_type
+                && values.is_empty()
+            {
+                return invalid(format!("enum column '{}' has no values", column.name));
+            }
+        }
+        Ok(())
+    }
+}
@@ -1,28 +1,146 @@
-use clap::Parser;
+use clap::error::ErrorKind;
+use clap::parser::ValueSource;
+use clap::{ArgGroup, CommandFactory, FromArgMatches, Parser, Subcommand};
+use crate::config::parser::GeneratorConfig;
+use crate::data::headers::{self, HeaderStrategy};
+use crate::data::inference::DEFAULT_MAX_ENUM_VALUES;
+use crate::data::pattern::Pattern;
+use crate::data::planner::{ColumnOptions, SolverObjective};
+use crate::data::timeseries::{SeriesKind, SeriesSpec, TimestampFormat, TimestampSpec};
+use crate::data::types::FixedColumn;
+use crate::data::verify::{DEFAULT_MAX_VIOLATIONS, DEFAULT_SIZE_TOLERANCE};
+use crate::report::ReportFormat;
+use crate::utils::progress::Progress;
+use crate::utils::{dates, units};
+use crate::generator::duplicates::DuplicateConfig;
+use crate::generator::mask::MaskRule;
+use crate::generator::sql::{SqlDialect, SqlStatements};
+use crate::generator::throttle::{Burst, Rate};
+use std::ffi::OsString;
+use std::ops::Range;
+use std::path::Path;
+use std::str::FromStr;
 
 #[derive(Parser, Debug)]
 #[command(name = "csvgen")]
 #[command(about = "A scalable CSV generator with size and row control")]
+#[command(subcommand_negates_reqs = true)]
 pub struct Args {
-    /// Target file size (e.g., 1MB, 500KB, 2GB)
-    #[arg(short, long)]
-    pub size: String,
+    #[command(subcommand)]
+    pub command: Option<Command>,
+
+    #[command(flatten)]
+    pub global: GlobalArgs,
+
+    /// Generate options given without a command, as `csvgen --size 1MB` did before commands
+    #[command(flatten)]
+    pub generate: GenerateArgs,
+}
+
+impl Args {
+    /// Parses a command line, program name first. Global options may come before or after
+    /// a command name, but generate options before one are an error rather than ignored.
+    pub fn try_parse_args_from<I, T>(args: I) -> Result<Self, clap::Error>
+    where
+        I: IntoIterator<Item = T>,
+        T: Into<OsString> + Clone,
+    {
+        let mut command = Self::command();
+        let matches = command.try_get_matches_from_mut(args)?;
+        if let Some((name, _)) = matches.subcommand() {
+            let before = command
+                .get_arguments()
+                .find(|a| !a.is_global_set() && matches.value_source(a.get_id().as_str()) == Some(ValueSource::CommandLine))
+                .map(|a| a.to_string());
+            if let Some(arg) = before {
+                let message = format!("the subcommand '{}' cannot be used with '{}'", name, arg);
+                return Err(command.error(ErrorKind::ArgumentConflict, message));
+            }
+        }
+        Self::from_arg_matches(&matches)
+    }
+
+    /// The generate options and global options, if this is a generate command with or
+    /// without the `generate` keyword.
+    pub fn into_generate(self) -> Option<(GenerateArgs, GlobalArgs)> {
+        match self.command {
+            None => Some((self.generate, self.global)),
+            Some(Command::Generate(generate)) => Some((*generate, self.global)),
+            Some(_) => None,
+        }
+    }
+}
+
+/// Options every command accepts, before or after the command name.
+#[derive(clap::Args, Debug)]
+pub struct GlobalArgs {
+    /// Seed for reproducible output; without one a random seed is used and recorded
+    #[arg(long, global = true)]
+    pub seed: Option<u64>,
+
+    /// Generator config file supplying defaults, e.g. for --min-columns and --max-columns
+    #[arg(long, global = true)]
+    pub config: Option<String>,
+
+    /// Only print errors and results: no progress bars or status messages
+    #[arg(short, long, global = true)]
+    pub quiet: bool,
+
+    /// Output format: csv, sql, fixed, sqlite or xlsx (the last two need the matching features)
+    #[arg(long, global = true, default_value = "csv")]
+    pub format: OutputFormat,
+
+    /// How to report progress: bar, json (one event per line on stderr, every second) or none
+    #[arg(long, global = true, default_value = "bar", value_name = "MODE")]
+    pub progress: Progress,
+}
+
+impl GlobalArgs {
+    /// The `--config` file, or the defaults without one.
+    pub fn generator_config(&self) -> Result<GeneratorConfig, anyhow::Error> {
+        match &self.config {
+            Some(path) => GeneratorConfig::load_from_file(path)
+                .map_err(|e| anyhow::anyhow!("Failed to load config {}: {}", path, e)),
+            None => Ok(GeneratorConfig::default()),
+        }
+    }
+
+    /// The `--progress` mode; `--quiet` hides the bar but not JSON events.
+    pub fn progress(&self) -> Progress {
+        match self.progress {
+            Progress::Bar if self.quiet => Progress::Hidden,
+            ref progress => progress.clone(),
+        }
+    }
+}
 
-    /// Number of rows to generate
+/// What to generate: the size, row count and columns a plan is made from.
+#[derive(clap::Args, Debug)]
+#[command(group(ArgGroup::new("dimensions").args(["size", "rows"]).multiple(true).required(true)))]
+pub struct PlanArgs {
+    /// Target file size (e.g., 1MB = 1000000 bytes, 1MiB = 1048576, 2.5GB); derived from the rows if omitted
     #[arg(short, long)]
-    pub rows: usize,
+    pub size: Option<String>,
 
-    /// Output file path
-    #[arg(short, long, default_value = "output.csv")]
-    pub output: String,
+    /// Number of rows to generate (e.g., 5000, 2.5k, 10M); derived from the size if omitted
+    #[arg(short, long, value_parser = units::parse_count)]
+    pub rows: Option<usize>,
 
-    /// Minimum number of columns
-    #[arg(long, default_value = "2")]
-    pub min_columns: usize,
+    /// Minimum number of columns [default: 2, or the config's default_min_columns]
+    #[arg(long)]
+    pub min_columns: Option<usize>,
 
-    /// Maximum number of columns
-    #[arg(long, default_value = "100")]
-    pub max_columns: usize,
+    /// Maximum number of columns [default: 100, or the config's default_max_columns]
+    #[arg(long)]
+    pub max_columns: Option<usize>,
+
+    /// Exact number of columns, overriding --min-columns and --max-columns
+    #[arg(long, conflicts_with = "schema")]
+    pub columns: Option<usize>,
+
+    /// Column count objective: fewest, most or width:N (regular columns about N bytes wide)
+    #[arg(long, default_value = "fewest", value_name = "OBJECTIVE")]
+    pub objective: SolverObjective,
 
     /// Number of email columns to generate
     #[arg(long, default_value = "0")]
@@ -31,25 +149,544 @@ pub struct Args {
     /// Number of domain columns to generate
     #[arg(long, default_value = "0")]
     pub domain_columns: usize,
+
+    /// Regex for a pattern column (e.g., '[A-Z]{3}-\d{6}'); repeat for more columns
+    #[arg(long = "pattern-column", value_name = "REGEX")]
+    pub pattern_columns: Vec<String>,
+
+    /// Length cap for pattern columns, required for unbounded patterns like 'SKU_\w+'
+    #[arg(long)]
+    pub pattern_max_len: Option<usize>,
+
+    /// Header names: padded, sql, spreadsheet, prefix[:PREFIX], words or list:NAME,... [default: padded]
+    #[arg(long, value_name = "STRATEGY")]
+    pub header_strategy: Option<HeaderStrategy>,
+
+    /// Schema file (e.g., from `csvgen infer`) to scale instead of generating columns
+    #[arg(long)]
+    pub schema: Option<String>,
+
+    #[command(flatten)]
+    pub time_series: TimeSeriesArgs,
 }
 
-impl Args {
-    pub fn parse_size(&self) -> Result<usize, anyhow::Error> {
-        let size_str = self.size.to_uppercase();
-        if size_str.ends_with("KB") {
-            let num: f64 = size_str.trim_end_matches("KB").parse()?;
-            Ok((num * 1024.0) as usize)
-        } else if size_str.ends_with("MB") {
-            let num: f64 = size_str.trim_end_matches("MB").parse()?;
-            Ok((num * 1024.0 * 1024.0) as usize)
-        } else if size_str.ends_with("GB") {
-            let num: f64 = size_str.trim_end_matches("GB").parse()?;
-            Ok((num * 1024.0 * 1024.0 * 1024.0) as usize)
-        } else if size_str.ends_with("B") {
-            let num: f64 = size_str.trim_end_matches("B").parse()?;
-            Ok(num as usize)
+/// Where and how generated rows are written.
+#[derive(clap::Args, Debug)]
+pub struct GenerateArgs {
+    #[command(flatten)]
+    pub plan: PlanArgs,
+
+    /// Output file path
+    #[arg(short, long, default_value = "output.csv")]
+    pub output: String,
+
+    /// Copybook layout file for --format fixed [default: <OUTPUT>.cpy]
+    #[arg(long)]
+    pub layout: Option<String>,
+
+    /// Manifest recording how the file was made [default: <OUTPUT>.manifest.json]
+    #[arg(long)]
+    pub manifest: Option<String>,
+
+    /// Don't write a manifest
+    #[arg(long, conflicts_with = "manifest")]
+    pub no_manifest: bool,
+
+    #[command(flatten)]
+    pub sql: SqlArgs,
+
+    #[command(flatten)]
+    pub feed: FeedArgs,
+
+    /// Probability that a row repeats an earlier row exactly (with a new ID)
+    #[arg(long, default_value = "0")]
+    pub duplicate_rate: f64,
+
+    /// Probability that a row repeats an earlier row with typos, case or format changes
+    #[arg(long, default_value = "0")]
+    pub near_duplicate_rate: f64,
+
+    /// Ground-truth CSV mapping duplicate IDs to original IDs [default: <OUTPUT>.duplicates.csv]
+    #[arg(long)]
+    pub ground_truth: Option<String>,
+
+    /// After a CSV is written, show statistics of what was generated: table or json
+    #[arg(long, value_name = "FORMAT")]
+    pub report: Option<ReportFormat>,
+
+    /// Write the --report to this file instead of standard output
+    #[arg(long, requires = "report")]
+    pub report_output: Option<String>,
+
+    /// Write only rows START..END of the planned CSV (e.g. 1M..2M, or 1M.. for the rest), with the header only if START is 0
+    #[arg(long, value_name = "START..END", conflicts_with = "follow")]
+    pub row_range: Option<RowRange>,
+
+    /// Threads generating CSV rows; the output is the same for any number
+    #[arg(long, default_value = "1")]
+    pub threads: usize,
+}
+
+/// What the generated rows are written as.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OutputFormat {
+    Csv,
+    Sql,
+    /// Fixed-width text records with a copybook layout
+    FixedWidth,
+    /// A SQLite database file
+    Sqlite,
+    /// An Excel workbook
+    Xlsx,
+}
+
+impl OutputFormat {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            OutputFormat::Csv => "csv",
+            OutputFormat::Sql => "sql",
+            OutputFormat::FixedWidth => "fixed",
+            OutputFormat::Sqlite => "sqlite",
+            OutputFormat::Xlsx => "xlsx",
+        }
+    }
+}
+
+impl FromStr for OutputFormat {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "csv" => Ok(OutputFormat::Csv),
+            "sql" => Ok(OutputFormat::Sql),
+            "fixed" | "fixed-width" => Ok(OutputFormat::FixedWidth),
+            "sqlite" if cfg!(feature = "sqlite") => Ok(OutputFormat::Sqlite),
+            "sqlite" => Err(anyhow::anyhow!("SQLite output needs csvgen built with the sqlite feature")),
+            "xlsx" if cfg!(feature = "xlsx") => Ok(OutputFormat::Xlsx),
+            "xlsx" => Err(anyhow::anyhow!("XLSX output needs csvgen built with the xlsx feature")),
+            _ => Err(anyhow::anyhow!("Unknown output format '{}'. Use csv, sql, fixed, sqlite or xlsx", s)),
+        }
+    }
+}
+
+/// Rows `start` up to `end`, counting from 0, or to the last row if `end` is omitted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RowRange {
+    pub start: usize,
+    pub end: Option<usize>,
+}
+
+impl RowRange {
+    /// The rows of a file of `num_rows` rows.
+    pub fn resolve(&self, num_rows: usize) -> Result<Range<usize>, anyhow::Error> {
+        let end = self.end.unwrap_or(num_rows);
+        if self.start >= end {
+            anyhow::bail!("Row range {}..{} is empty", self.start, end);
+        }
+        if end > num_rows {
+            anyhow::bail!("Row range {}..{} goes past the {} planned rows", self.start, end, num_rows);
+        }
+        Ok(self.start..end)
+    }
+}
+
+impl FromStr for RowRange {
+    type Err = anyhow::Error;
+
+    /// Parses `START..END` or `START..`, with counts such as `1M` or `2.5k`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (start, end) = s
+            .split_once("..")
+            .ok_or_else(|| anyhow::anyhow!("Invalid row range '{}'. Use START..END or START..", s))?;
+        Ok(RowRange {
+            start: units::parse_count(start)?,
+            end: if end.trim().is_empty() { None } else { Some(units::parse_count(end)?) },
+        })
+    }
+}
+
+#[derive(clap::Args, Debug)]
+#[command(next_help_heading = "SQL output")]
+pub struct SqlArgs {
+    /// SQL dialect for --format sql: postgres, mysql or sqlite
+    #[arg(long, default_value = "postgres")]
+    pub dialect: SqlDialect,
+
+    /// Table name, or worksheet name for --format xlsx [default: the output file name]
+    #[arg(long)]
+    pub table: Option<String>,
+
+    /// Rows per INSERT statement
+    #[arg(long, default_value = "1000")]
+    pub batch_size: usize,
+
+    /// Load rows with COPY ... FROM STDIN instead of INSERTs (PostgreSQL only)
+    #[arg(long)]
+    pub copy: bool,
+
+    /// Column to index in --format sqlite; repeatable
+    #[arg(long = "index", value_name = "COLUMN")]
+    pub indexes: Vec<String>,
+
+    /// Data rows per worksheet in --format xlsx before continuing on a new one [default: Excel's limit]
+    #[arg(long, value_parser = units::parse_count)]
+    pub sheet_rows: Option<usize>,
+}
+
+impl SqlArgs {
+    pub fn statements(&self) -> SqlStatements {
+        if self.copy {
+            SqlStatements::Copy
         } else {
-            Err(anyhow::anyhow!("Invalid size format. Use KB, MB, GB, or B"))
+            SqlStatements::Insert { batch_size: self.batch_size }
         }
     }
-}
\ No newline at end of file
+
+    /// The `--table` name, or the output file's name.
+    pub fn sheet_name(&self, output: &str) -> String {
+        match &self.table {
+            Some(table) => table.clone(),
+            None => Path::new(output).file_stem().and_then(|s| s.to_str()).unwrap_or("data").to_string(),
+        }
+    }
+
+    /// The `--table` name, or a SQL-safe version of the output file's name.
+    pub fn table_name(&self, output: &str) -> String {
+        match &self.table {
+            Some(table) => table.clone(),
+            None => headers::sql_identifier(&self.sheet_name(output)),
+        }
+    }
+}
+
+#[derive(clap::Args, Debug)]
+#[command(next_help_heading = "Live feed")]
+pub struct FeedArgs {
+    /// Write rows at this rate, e.g. 500rows/s or 2MB/s, flushing each batch for readers tailing the file
+    #[arg(long)]
+    pub rate: Option<Rate>,
+
+    /// How the rate varies: steady, sine[:period=S,amplitude=A] or spikes[:every=S,length=S,factor=F]
+    #[arg(long, default_value = "steady", requires = "rate", value_name = "PATTERN")]
+    pub burst: Burst,
+
+    /// Keep writing rows past --rows or --size until interrupted or the reader of a pipe goes away
+    #[arg(long)]
+    pub follow: bool,
+}
+
+#[derive(clap::Args, Debug)]
+#[command(next_help_heading = "Time series")]
+pub struct TimeSeriesArgs {
+    /// Add an ordered timestamp column after the ID column
+    #[arg(long)]
+    pub time_series: bool,
+
+    /// First timestamp (YYYY-MM-DD, YYYY-MM-DDTHH:MM:SSZ or Unix seconds)
+    #[arg(long, default_value = "2024-01-01T00:00:00Z")]
+    pub start: String,
+
+    /// Time between rows (e.g., 500ms, 1s, 5m, 1h, 1d)
+    #[arg(long, default_value = "1s")]
+    pub interval: String,
+
+    /// Random shift applied to each timestamp, either way
+    #[arg(long, default_value = "0ms")]
+    pub jitter: String,
+
+    /// Probability of a gap after each row
+    #[arg(long, default_value = "0")]
+    pub gap_rate: f64,
+
+    /// Length of each gap
+    #[arg(long, default_value = "1h")]
+    pub gap: String,
+
+    /// Probability of a row being emitted out of order
+    #[arg(long, default_value = "0")]
+    pub out_of_order_rate: f64,
+
+    /// Timestamp format: iso, iso-millis, datetime, unix or unix-millis
+    #[arg(long, default_value = "iso")]
+    pub timestamp_format: TimestampFormat,
+
+    /// Value column as KIND[:param=value,...] with KIND random-walk, seasonal or trend; repeatable
+    #[arg(long, value_name = "KIND")]
+    pub series: Vec<SeriesKind>,
+
+    /// Width in bytes of each value column
+    #[arg(long, default_value = "10")]
+    pub series_width: usize,
+
+    /// Decimal places of each value column
+    #[arg(long, default_value = "2")]
+    pub series_precision: usize,
+}
+
+impl TimeSeriesArgs {
+    /// Timestamp and value columns for time-series mode; empty unless `--time-series` is set.
+    pub fn fixed_columns(&self) -> Result<Vec<FixedColumn>, anyhow::Error> {
+        if !self.time_series {
+            return Ok(Vec::new());
+        }
+
+        let parse_duration = |value: &str| {
+            dates::parse_duration_ms(value)
+                .ok_or_else(|| anyhow::anyhow!("Invalid duration '{}'. Use e.g. 500ms, 1s, 5m, 1h or 1d", value))
+        };
+        let timestamp = TimestampSpec {
+            start_ms: dates::parse_timestamp_ms(&self.start)
+                .ok_or_else(|| anyhow::anyhow!("Invalid start time '{}'", self.start))?,
+            interval_ms: parse_duration(&self.interval)?,
+            jitter_ms: parse_duration(&self.jitter)?,
+            gap_rate: self.gap_rate,
+            gap_ms: parse_duration(&self.gap)?,
+            out_of_order_rate: self.out_of_order_rate,
+            format: self.timestamp_format,
+        };
+
+        let mut columns = vec![FixedColumn::timestamp(timestamp)];
+        for (i, kind) in self.series.iter().enumerate() {
+            let spec = SeriesSpec {
+                kind: kind.clone(),
+                precision: self.series_precision,
+            };
+            columns.push(FixedColumn::series(i + 1, spec, self.series_width));
+        }
+        Ok(columns)
+    }
+}
+
+#[derive(Subcommand, Debug)]
+pub enum Command {
+    /// Generate a file (the default: `csvgen --size 1MB` is `csvgen generate --size 1MB`)
+    Generate(Box<GenerateArgs>),
+    /// Print the planned rows, size and columns without writing anything
+    Plan(Box<PlanArgs>),
+    /// Infer a schema file from a sample CSV
+    Infer(InferArgs),
+    /// Rewrite selected columns of an existing CSV with generated values
+    Mask(MaskArgs),
+    /// Serve generated CSV over HTTP, e.g. /generate?rows=1000&size=1MB&seed=42&schema=users
+    Serve(ServeArgs),
+    /// Check a CSV against a schema file, reporting violations by line
+    Verify(VerifyArgs),
+    /// Regenerate the file a manifest describes and confirm its checksum
+    Reproduce(ReproduceArgs),
+    /// Check or write schema files
+    Schema(SchemaArgs),
+}
+
+#[derive(clap::Args, Debug)]
+pub struct InferArgs {
+    /// Sample CSV file with a header row
+    pub input: String,
+
+    /// Schema file to write
+    #[arg(short, long, default_value = "schema.json")]
+    pub output: String,
+
+    /// Columns with at most this many distinct values become enums
+    #[arg(long, default_value_t = DEFAULT_MAX_ENUM_VALUES)]
+    pub max_enum_values: usize,
+}
+
+#[derive(clap::Args, Debug)]
+pub struct MaskArgs {
+    /// CSV file with a header row
+    pub input: String,
+
+    /// Masked output file
+    #[arg(short, long, default_value = "masked.csv")]
+    pub output: String,
+
+    /// Column to mask as COLUMN or COLUMN=KIND (email, name, domain, string, format); repeatable
+    #[arg(short, long = "column", value_name = "COLUMN[=KIND]", required = true)]
+    pub columns: Vec<MaskRule>,
+
+    /// Secret key; equal inputs then map to equal outputs across files masked with the same key
+    #[arg(long)]
+    pub key: Option<String>,
+}
+
+#[derive(clap::Args, Debug)]
+pub struct VerifyArgs {
+    /// CSV file with a header row
+    pub input: String,
+
+    /// Schema file the CSV should match (e.g., from `csvgen infer`)
+    #[arg(long)]
+    pub schema: String,
+
+    /// Expected number of data rows (e.g., 5000, 10M)
+    #[arg(short, long, value_parser = units::parse_count)]
+    pub rows: Option<usize>,
+
+    /// Expected file size (e.g., 1MB, 500KiB)
+    #[arg(short, long)]
+    pub size: Option<String>,
+
+    /// Allowed relative difference from --size
+    #[arg(long, default_value_t = DEFAULT_SIZE_TOLERANCE)]
+    pub size_tolerance: f64,
+
+    /// Violations to list; the rest are only counted
+    #[arg(long, default_value_t = DEFAULT_MAX_VIOLATIONS)]
+    pub max_violations: usize,
+
+    /// Also check that unique columns other than IDs don't repeat, remembering up to this
+    /// many values per column (e.g., 1M); a column with more fails
+    #[arg(long, value_parser = units::parse_count)]
+    pub unique_limit: Option<usize>,
+}
+
+#[derive(clap::Args, Debug)]
+pub struct ReproduceArgs {
+    /// Manifest written alongside the original file
+    pub manifest: String,
+
+    /// Where to write the file [default: the manifest's output path]
+    #[arg(short, long)]
+    pub output: Option<String>,
+}
+
+#[derive(clap::Args, Debug)]
+pub struct SchemaArgs {
+    #[command(subcommand)]
+    pub command: SchemaCommand,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum SchemaCommand {
+    /// Check a schema file for mistakes the planner would trip over
+    Validate(SchemaValidateArgs),
+    /// Write the planned columns as a schema file that --schema can scale
+    Export(Box<SchemaExportArgs>),
+}
+
+#[derive(clap::Args, Debug)]
+pub struct SchemaValidateArgs {
+    /// Schema file to check
+    pub input: String,
+}
+
+#[derive(clap::Args, Debug)]
+pub struct SchemaExportArgs {
+    /// Schema file to write [default: standard output]
+    pub output: Option<String>,
+
+    #[command(flatten)]
+    pub plan: PlanArgs,
+}
+
+#[derive(clap::Args, Debug)]
+pub struct ServeArgs {
+    /// Port to listen on
+    #[arg(short, long, default_value = "8080")]
+    pub port: u16,
+
+    /// Address to listen on
+    #[arg(long, default_value = "127.0.0.1")]
+    pub host: String,
+
+    /// Directory of schema files; `schema=NAME` loads NAME.json from it
+    #[arg(long, default_value = ".")]
+    pub schema_dir: String,
+
+    /// Delay before each response (e.g., 250ms, 2s); a `latency` parameter overrides it
+    #[arg(long, default_value = "0ms")]
+    pub latency: String,
+
+    /// Body rate limit (e.g., 64KB/s); a `throttle` parameter overrides it
+    #[arg(long)]
+    pub throttle: Option<String>,
+
+    /// Most rows a request may generate (e.g., 1M)
+    #[arg(long, default_value = "10M", value_parser = units::parse_count)]
+    pub max_rows: usize,
+
+    /// Most bytes a request may generate (e.g., 500MB)
+    #[arg(long, default_value = "1GB", value_parser = units::parse_size)]
+    pub max_size: usize,
+}
+
+impl GenerateArgs {
+    /// Duplicate injection settings, if either duplicate rate is set.
+    pub fn duplicate_config(&self) -> Option<DuplicateConfig> {
+        if self.duplicate_rate <= 0.0 && self.near_duplicate_rate <= 0.0 {
+            return None;
+        }
+        Some(DuplicateConfig {
+            duplicate_rate: self.duplicate_rate,
+            near_duplicate_rate: self.near_duplicate_rate,
+            ground_truth_path: self
+                .ground_truth
+                .clone()
+                .unwrap_or_else(|| format!("{}.duplicates.csv", self.output)),
+        })
+    }
+
+    /// Where the manifest goes, unless `--no-manifest` is given.
+    pub fn manifest_path(&self) -> Option<String> {
+        if self.no_manifest {
+            return None;
+        }
+        Some(self.manifest.clone().unwrap_or_else(|| format!("{}.manifest.json", self.output)))
+    }
+
+    /// Where the copybook for fixed-width output goes.
+    pub fn layout_path(&self) -> String {
+        self.layout.clone().unwrap_or_else(|| format!("{}.cpy", self.output))
+    }
+
+}
+
+impl PlanArgs {
+    /// Column options for the planner; `--columns` pins both bounds, and bounds not given
+    /// come from `config`.
+    pub fn column_options(&self, config: &GeneratorConfig) -> Result<ColumnOptions, anyhow::Error> {
+        let (min_columns, max_columns) = match self.columns {
+            Some(columns) => (columns, columns),
+            None => (
+                self.min_columns.unwrap_or(config.default_min_columns),
+                self.max_columns.unwrap_or(config.default_max_columns),
+            ),
+        };
+        Ok(ColumnOptions {
+            min_columns,
+            max_columns,
+            email_columns: self.email_columns,
+            domain_columns: self.domain_columns,
+            fixed_columns: self.fixed_columns()?,
+            header_strategy: self.header_strategy.clone().unwrap_or_default(),
+            objective: self.objective,
+        })
+    }
+
+    /// Pattern columns followed by any time-series columns.
+    pub fn fixed_columns(&self) -> Result<Vec<FixedColumn>, anyhow::Error> {
+        let mut columns: Vec<FixedColumn> = self
+            .parse_patterns()?
+            .iter()
+            .enumerate()
+            .map(|(i, pattern)| FixedColumn::pattern(i + 1, pattern))
+            .collect();
+        columns.extend(self.time_series.fixed_columns()?);
+        Ok(columns)
+    }
+
+    pub fn parse_patterns(&self) -> Result<Vec<Pattern>, anyhow::Error> {
+        self.pattern_columns
+            .iter()
+            .map(|regex| match self.pattern_max_len {
+                Some(max_len) => Pattern::with_max_len(regex, max_len),
+                None => Pattern::new(regex),
+            })
+            .collect()
+    }
+
+    /// Target size in bytes, or `None` if `--size` was not given.
+    pub fn parse_size(&self) -> Result<Option<usize>, anyhow::Error> {
+        self.size.as_deref().map(units::parse_size).transpose()
+    }
+}
@@ -1,27 +1,86 @@
-use rand::Rng;
-use rand::thread_rng;
+use rand::{Rng, RngCore, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+use std::collections::HashMap;
+use crate::data::timeseries::{SeriesKind, TimestampSpec};
 use crate::data::types::{ColumnConfig, DataType};
+use crate::utils::dates;
 
+// Range used for generated dates: 1970-01-01 up to 2037-12-31
+const MAX_DATE_DAYS: i64 = 24_836;
+// Keystream words between the starts of consecutive rows' cells: 64 MiB, far more than any
+// value draws, leaving room for 2^44 rows
+const CELL_BITS: u32 = 24;
+// Streams from here on hold the steps and gaps of time-series columns, one per column
+const CARRY_STREAM: u64 = 1 << 32;
+
+/// Generates column values, each a function of the seed, its row and its column alone.
+///
+/// Every cell draws from its own position of the seed's ChaCha8 keystream: the stream number
+/// is the column and the position is the row, 2^24 words apart, so any row can be generated
+/// without the rows before it and in any order. IDs are the row number plus one. Random-walk
+/// series and timestamps with gaps carry state from row to row; their steps and gaps come
+/// from separate streams, so reaching row N takes one draw per earlier row rather than
+/// generating those rows, and the state is kept so that consecutive rows cost nothing extra.
+#[derive(Clone)]
 pub struct DataGenerator {
-    rng: rand::rngs::ThreadRng,
-    id_counter: usize,
+    rng: Keystream,
+    // Row of the next value from `generate_value`
+    samples: u64,
+    // Carried state of time-series columns, keyed by column index: the row it is for, and
+    // the nominal time or the walked value there
+    timestamps: HashMap<usize, (u64, i64)>,
+    series: HashMap<usize, (u64, f64)>,
+}
+
+impl Default for DataGenerator {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl DataGenerator {
+    /// A generator with a random seed.
     pub fn new() -> Self {
+        Self::with_seed(rand::random())
+    }
+
+    /// Creates a generator whose output is fully determined by `seed`.
+    pub fn with_seed(seed: u64) -> Self {
         Self {
-            rng: thread_rng(),
-            id_counter: 1,
+            rng: Keystream::new(seed),
+            samples: 0,
+            timestamps: HashMap::new(),
+            series: HashMap::new(),
         }
     }
 
+    /// The next of a sequence of values for `column`, as if each were in its own row.
     pub fn generate_value(&mut self, column: &ColumnConfig) -> String {
+        let row = self.samples;
+        self.samples += 1;
+        self.generate_cell(row, 0, column)
+    }
+
+    /// The value of the cell at `row` of `column`, which is column `col` of its schema. The
+    /// same seed always gives the same value, whichever cells were generated before.
+    pub fn generate_cell(&mut self, row: u64, col: usize, column: &ColumnConfig) -> String {
+        // Carried state first, as walking it moves the keystream
+        let nominal_ms = match &column.data_type {
+            DataType::Timestamp(spec) => self.nominal_ms(row, col, spec),
+            _ => 0,
+        };
+        let walked = match &column.data_type {
+            DataType::Series(spec) => self.walked(row, col, &spec.kind),
+            _ => 0.0,
+        };
+
+        self.rng.seek(col, false, row);
+        if column.null_rate > 0.0 && self.rng.gen_bool(column.null_rate.min(1.0)) {
+            return String::new();
+        }
+
         match &column.data_type {
-            DataType::UniqueId => {
-                let id = self.id_counter;
-                self.id_counter += 1;
-                format!("{:0width$}", id, width = column.size_bytes)
-            }
+            DataType::UniqueId => format!("{:0width$}", row + 1, width = column.size_bytes),
             DataType::String => {
                 let chars: Vec<char> = "abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ0123456789"
                     .chars()
@@ -31,7 +90,8 @@ impl DataGenerator {
                     .collect()
             }
             DataType::Number => {
-                let max = 10u64.pow(column.size_bytes as u32);
+                // u64 holds 19 digits; wider columns get 19-digit numbers
+                let max = 10u64.pow(column.size_bytes.min(19) as u32);
                 self.rng.gen_range(0..max).to_string()
             }
             DataType::Email => {
@@ -63,8 +123,8 @@ impl DataGenerator {
                 }
             }
             DataType::Name => {
-                let first_names = vec!["John", "Jane", "Bob", "Alice", "Tom", "Sue"];
-                let last_names = vec!["Smith", "Doe", "Johnson", "Brown", "Davis"];
+                let first_names = ["John", "Jane", "Bob", "Alice", "Tom", "Sue"];
+                let last_names = ["Smith", "Doe", "Johnson", "Brown", "Davis"];
                 let first = first_names[self.rng.gen_range(0..first_names.len())];
                 let last = last_names[self.rng.gen_range(0..last_names.len())];
                 let name = format!("{} {}", first, last);
@@ -94,10 +154,70 @@ impl DataGenerator {
                     }
                 }
             }
+            DataType::Pattern(pattern) => pattern.generate(&mut self.rng),
+            DataType::Decimal { scale } => {
+                let int_digits = column.size_bytes.saturating_sub(scale + 1).max(1);
+                let int_part = self.rng.gen_range(0..10u64.pow(int_digits.min(19) as u32));
+                let frac_part = self.rng.gen_range(0..10u64.pow((*scale).min(19) as u32));
+                if *scale == 0 {
+                    int_part.to_string()
+                } else {
+                    format!("{}.{:0scale$}", int_part, frac_part, scale = *scale)
+                }
+            }
+            DataType::Date => dates::format_iso_date(self.rng.gen_range(0..=MAX_DATE_DAYS)),
+            DataType::Timestamp(spec) => spec.emit(nominal_ms, &mut self.rng),
+            DataType::Series(spec) => spec.format(spec.kind.value(row, walked, &mut self.rng), column.size_bytes),
+            DataType::Enum(values) => {
+                if values.is_empty() {
+                    String::new()
+                } else {
+                    values[self.rng.gen_range(0..values.len())].clone()
+                }
+            }
+            DataType::Range { min, max } => self.rng.gen_range(*min.min(max)..=*max.max(min)).to_string(),
+        }
+    }
+
+    /// Nominal time of `row` of a timestamp column, walking the gaps from the nearest
+    /// known row before it.
+    fn nominal_ms(&mut self, row: u64, col: usize, spec: &TimestampSpec) -> i64 {
+        if let Some(nominal_ms) = spec.nominal_without_gaps(row) {
+            return nominal_ms;
+        }
+        let (mut at, mut nominal_ms) = match self.timestamps.get(&col) {
+            Some(&(at, nominal_ms)) if at <= row => (at, nominal_ms),
+            _ => (0, spec.start_ms),
+        };
+        while at < row {
+            // The gap after a row is drawn at that row
+            self.rng.seek(col, true, at);
+            nominal_ms = spec.advance(nominal_ms, &mut self.rng);
+            at += 1;
+        }
+        self.timestamps.insert(col, (row, nominal_ms));
+        nominal_ms
+    }
+
+    /// Value of a random walk at `row`, walking the steps from the nearest known row before it.
+    fn walked(&mut self, row: u64, col: usize, kind: &SeriesKind) -> f64 {
+        if !matches!(kind, SeriesKind::RandomWalk { .. }) {
+            return 0.0;
         }
+        let (mut at, mut value) = match self.series.get(&col) {
+            Some(&(at, value)) if at <= row => (at, value),
+            _ => (0, kind.start()),
+        };
+        while at < row {
+            at += 1;
+            self.rng.seek(col, true, at);
+            value += kind.step(&mut self.rng);
+        }
+        self.series.insert(col, (row, value));
+        value
     }
 
-    fn select_domain_and_local_size(rng: &mut rand::rngs::ThreadRng, size_bytes: usize) -> (&'static str, usize) {
+    fn select_domain_and_local_size(rng: &mut impl Rng, size_bytes: usize) -> (&'static str, usize) {
         match size_bytes {
             9..=25 => {
                 // Medium: use medium domains, 60% for local part
@@ -130,7 +250,7 @@ impl DataGenerator {
         }
     }
 
-    fn generate_local_part(rng: &mut rand::rngs::ThreadRng, size: usize) -> String {
+    fn generate_local_part(rng: &mut impl Rng, size: usize) -> String {
         if size <= 3 {
             // Very small: just letters
             (0..size)
@@ -205,7 +325,7 @@ impl DataGenerator {
         }
     }
 
-    fn generate_smart_domain(rng: &mut rand::rngs::ThreadRng, size_bytes: usize) -> String {
+    fn generate_smart_domain(rng: &mut impl Rng, size_bytes: usize) -> String {
         match size_bytes {
             9..=20 => {
                 // Medium: simple domains
@@ -246,4 +366,70 @@ impl DataGenerator {
             }
         }
     }
-}
\ No newline at end of file
+}
+/// The ChaCha8 streams of one seed, positioned at a cell.
+///
+/// Each stream keeps its own generator, and a seek only takes effect at the first draw, so
+/// moving between columns or past cells that draw nothing costs nothing.
+#[derive(Clone)]
+struct Keystream {
+    seed: ChaCha8Rng,
+    // Value and carry streams of each column, in turn, created as they are first used
+    streams: Vec<Option<ChaCha8Rng>>,
+    active: usize,
+    pending: Option<u128>,
+}
+
+impl Keystream {
+    fn new(seed: u64) -> Self {
+        Self {
+            seed: ChaCha8Rng::seed_from_u64(seed),
+            streams: Vec::new(),
+            active: 0,
+            pending: None,
+        }
+    }
+
+    /// Moves to the cell at `row` of column `col`'s value stream, or its carry stream.
+    fn seek(&mut self, col: usize, carry: bool, row: u64) {
+        self.active = col * 2 + usize::from(carry);
+        if self.streams.len() <= self.active {
+            self.streams.resize(self.active + 1, None);
+        }
+        if self.streams[self.active].is_none() {
+            let mut rng = self.seed.clone();
+            rng.set_stream(if carry { CARRY_STREAM + col as u64 } else { col as u64 });
+            self.streams[self.active] = Some(rng);
+        }
+        self.pending = Some(u128::from(row) << CELL_BITS);
+    }
+
+    fn rng(&mut self) -> &mut ChaCha8Rng {
+        if self.streams.is_empty() {
+            self.seek(0, false, 0);
+        }
+        let rng = self.streams[self.active].as_mut().expect("Streams are created when sought");
+        if let Some(position) = self.pending.take() {
+            rng.set_word_pos(position);
+        }
+        rng
+    }
+}
+
+impl RngCore for Keystream {
+    fn next_u32(&mut self) -> u32 {
+        self.rng().next_u32()
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.rng().next_u64()
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        self.rng().fill_bytes(dest)
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand::Error> {
+        self.rng().try_fill_bytes(dest)
+    }
+}
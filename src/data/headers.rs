@@ -0,0 +1,316 @@
+use crate::data::planner::{PlanError, PlanResult};
+use anyhow::{anyhow, bail, Result};
+use std::collections::{HashMap, HashSet};
+use std::str::FromStr;
+
+// Identifiers that clash with SQL keywords get a trailing underscore
+const SQL_RESERVED: &[&str] = &[
+    "all", "and", "as", "asc", "between", "by", "case", "check", "column", "constraint", "create", "date",
+    "default", "delete", "desc", "distinct", "drop", "else", "end", "exists", "from", "group", "having", "in",
+    "index", "insert", "into", "is", "join", "key", "like", "limit", "not", "null", "on", "or", "order",
+    "primary", "references", "select", "set", "table", "then", "time", "timestamp", "to", "union", "unique",
+    "update", "user", "values", "when", "where", "with",
+];
+
+const ADJECTIVES: &[&str] = &[
+    "amber", "bold", "calm", "dark", "eager", "fast", "grand", "happy", "icy", "jolly", "keen", "light", "mild",
+    "neat", "odd", "pale", "quick", "rapid", "sharp", "tall", "urban", "vast", "warm", "young",
+];
+
+const NOUNS: &[&str] = &[
+    "apple", "bird", "cloud", "delta", "ember", "field", "grove", "harbor", "island", "jade", "kite", "lake",
+    "meadow", "north", "ocean", "pine", "quartz", "river", "stone", "tiger", "valley", "willow", "yard", "zephyr",
+];
+
+const ALPHANUMERIC: &[u8] = b"abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ0123456789";
+const SQL_FIRST: &[u8] = b"abcdefghijklmnopqrstuvwxyz_";
+const SQL_REST: &[u8] = b"abcdefghijklmnopqrstuvwxyz0123456789_";
+const UPPER: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZ";
+
+/// How header names are chosen for generated columns.
+///
+/// Every strategy yields unique names that need no CSV quoting and are no longer than their
+/// column's planned width, so the header line never outgrows a data row. Names that do not
+/// fit are truncated, then made unique with a numeric suffix or, for very narrow columns,
+/// the first free short name.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum HeaderStrategy {
+    /// Type-based names (`id`, `email_1`, `col3`) padded to the column width
+    #[default]
+    Padded,
+    /// Lower-case `[a-z_][a-z0-9_]*` identifiers that avoid common SQL keywords
+    SqlSafe,
+    /// `A`, `B`, ..., `Z`, `AA`, ... by column position
+    Spreadsheet,
+    /// The prefix followed by the 1-based column position, e.g. `col_1`
+    Prefix(String),
+    /// snake_case adjective and noun pairs such as `amber_river`
+    Words,
+    /// Names in column order
+    List(Vec<String>),
+}
+
+impl FromStr for HeaderStrategy {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let (kind, arg) = s.split_once(':').unwrap_or((s, ""));
+        match kind.to_lowercase().as_str() {
+            "padded" => Ok(HeaderStrategy::Padded),
+            "sql" => Ok(HeaderStrategy::SqlSafe),
+            "spreadsheet" => Ok(HeaderStrategy::Spreadsheet),
+            "prefix" => {
+                let prefix = if arg.is_empty() { "col_" } else { arg };
+                validate_name(prefix)?;
+                Ok(HeaderStrategy::Prefix(prefix.to_string()))
+            }
+            "words" => Ok(HeaderStrategy::Words),
+            "list" => {
+                let names: Vec<String> = arg.split(',').map(str::to_string).collect();
+                for name in &names {
+                    validate_name(name)?;
+                }
+                Ok(HeaderStrategy::List(names))
+            }
+            _ => Err(anyhow!(
+                "Unknown header strategy '{}'. Use padded, sql, spreadsheet, prefix[:PREFIX], words or list:NAME,...",
+                s
+            )),
+        }
+    }
+}
+
+// Names that would need quoting would make the header longer than planned
+pub(crate) fn validate_name(name: &str) -> Result<()> {
+    if name.is_empty() {
+        bail!("Header names cannot be empty");
+    }
+    if name.contains([',', '"', '\r', '\n']) {
+        bail!("Header name '{}' cannot contain commas, quotes or line breaks", name);
+    }
+    Ok(())
+}
+
+impl HeaderStrategy {
+    /// Names one column per `(base, width)` pair, where `base` is the type-based name used by
+    /// the padded strategy.
+    pub fn name_columns(&self, columns: &[(String, usize)]) -> PlanResult<Vec<String>> {
+        if let HeaderStrategy::List(names) = self
+            && names.len() < columns.len()
+        {
+            return Err(PlanError::InvalidSchema(format!(
+                "Header list has {} names but the schema has {} columns",
+                names.len(),
+                columns.len()
+            )));
+        }
+
+        let mut namer = UniqueNamer::new(self);
+        columns
+            .iter()
+            .enumerate()
+            .map(|(index, (base, width))| {
+                let width = (*width).max(1);
+                namer.fit(&self.candidate(index, base, width), width)
+            })
+            .collect()
+    }
+
+    fn candidate(&self, index: usize, base: &str, width: usize) -> String {
+        match self {
+            HeaderStrategy::Padded => padded_name(base, width),
+            HeaderStrategy::SqlSafe => sql_identifier(base),
+            HeaderStrategy::Spreadsheet => spreadsheet_name(index),
+            HeaderStrategy::Prefix(prefix) => format!("{}{}", prefix, index + 1),
+            HeaderStrategy::Words => word_name(index, width),
+            HeaderStrategy::List(names) => names[index].clone(),
+        }
+    }
+
+    /// Whether `num_columns` columns can surely all be named when the regular ones are
+    /// `narrowest` bytes wide: narrow columns fall back to short names, so there must be
+    /// enough of those to go around.
+    pub(crate) fn can_name(&self, num_columns: usize, narrowest: usize) -> bool {
+        if let HeaderStrategy::List(names) = self {
+            return names.len() >= num_columns;
+        }
+        let (first, rest) = self.fallback_alphabet();
+        let short_names: usize = (1..=narrowest.clamp(1, 4))
+            .map(|len| first.len() * rest.len().pow(len as u32 - 1))
+            .sum();
+        num_columns.saturating_add(SQL_RESERVED.len()) <= short_names
+    }
+
+    // Alphabets for short names when truncation and suffixes run out
+    fn fallback_alphabet(&self) -> (&'static [u8], &'static [u8]) {
+        match self {
+            HeaderStrategy::SqlSafe => (SQL_FIRST, SQL_REST),
+            HeaderStrategy::Spreadsheet => (UPPER, UPPER),
+            _ => (ALPHANUMERIC, ALPHANUMERIC),
+        }
+    }
+}
+
+// Hands out unique names, remembering where each search stopped so that naming stays
+// linear even when thousands of candidates truncate to the same prefix
+struct UniqueNamer<'a> {
+    strategy: &'a HeaderStrategy,
+    used: HashSet<String>,
+    next_suffix: HashMap<(String, usize), usize>,
+    next_short: [usize; 5],
+}
+
+impl<'a> UniqueNamer<'a> {
+    fn new(strategy: &'a HeaderStrategy) -> Self {
+        Self {
+            strategy,
+            used: HashSet::new(),
+            next_suffix: HashMap::new(),
+            next_short: [0; 5],
+        }
+    }
+
+    fn taken(&self, name: &str) -> bool {
+        name.is_empty()
+            || self.used.contains(name)
+            || (*self.strategy == HeaderStrategy::SqlSafe && SQL_RESERVED.contains(&name))
+    }
+
+    fn fit(&mut self, candidate: &str, width: usize) -> PlanResult<String> {
+        let name = self.find(candidate, width)?;
+        self.used.insert(name.clone());
+        Ok(name)
+    }
+
+    fn find(&mut self, candidate: &str, width: usize) -> PlanResult<String> {
+        let truncated = truncate(candidate, width);
+        if !self.taken(truncated) {
+            return Ok(truncated.to_string());
+        }
+
+        // Candidates that truncate alike try the same suffixes, so resume where the last
+        // one stopped; each suffix gives a distinct name until they no longer fit
+        let key = (truncated.to_string(), width);
+        let mut suffix = self.next_suffix.get(&key).copied().unwrap_or(1);
+        loop {
+            let digits = suffix.to_string();
+            if digits.len() >= width {
+                break;
+            }
+            let name = format!("{}{}", truncate(candidate, width - digits.len()), digits);
+            suffix += 1;
+            if !self.taken(&name) {
+                self.next_suffix.insert(key, suffix);
+                return Ok(name);
+            }
+        }
+        self.next_suffix.insert(key, suffix);
+
+        let (first, rest) = self.strategy.fallback_alphabet();
+        for len in 1..=width.min(4) {
+            let count = first.len() * rest.len().pow(len as u32 - 1);
+            while self.next_short[len] < count {
+                let name = short_name(self.next_short[len], len, first, rest);
+                if !self.taken(&name) {
+                    return Ok(name);
+                }
+                self.next_short[len] += 1;
+            }
+        }
+        Err(PlanError::Impossible(format!(
+            "Cannot fit {} unique header names into {}-byte columns",
+            self.used.len() + 1,
+            width
+        )))
+    }
+}
+
+fn truncate(name: &str, width: usize) -> &str {
+    let mut end = width.min(name.len());
+    while !name.is_char_boundary(end) {
+        end -= 1;
+    }
+    &name[..end]
+}
+
+// The `n`th name of length `len`, with the first character drawn from `first`
+fn short_name(mut n: usize, len: usize, first: &[u8], rest: &[u8]) -> String {
+    let mut bytes = vec![0u8; len];
+    for slot in bytes[1..].iter_mut().rev() {
+        *slot = rest[n % rest.len()];
+        n /= rest.len();
+    }
+    bytes[0] = first[n % first.len()];
+    String::from_utf8(bytes).unwrap_or_default()
+}
+
+fn padded_name(base: &str, width: usize) -> String {
+    let number = base.strip_prefix("col").and_then(|n| n.parse::<usize>().ok());
+
+    // Very narrow regular columns get one letter per column
+    if width <= 2
+        && let Some(number) = number
+    {
+        return ((b'a' + (number % 26) as u8) as char).to_string();
+    }
+    if base.len() >= width {
+        return truncate(base, width).to_string();
+    }
+
+    // Vary the padding of regular columns so names stay unique after truncation
+    let padding_char = match number {
+        Some(number) if base.starts_with('c') => (b'a' + (number % 10) as u8) as char,
+        _ => 'x',
+    };
+    format!("{}{}", base, padding_char.to_string().repeat(width - base.len()))
+}
+
+/// Lower-cases `name` and replaces anything outside `[a-z0-9_]` so it is a valid unquoted SQL
+/// identifier that is not a common keyword.
+pub fn sql_identifier(name: &str) -> String {
+    let mut identifier = String::with_capacity(name.len());
+    for c in name.chars() {
+        let c = c.to_ascii_lowercase();
+        let c = if c.is_ascii_alphanumeric() { c } else { '_' };
+        // Collapse runs of replaced characters
+        if !(c == '_' && identifier.ends_with('_')) {
+            identifier.push(c);
+        }
+    }
+    if !identifier.starts_with(|c: char| c.is_ascii_lowercase() || c == '_') {
+        identifier.insert(0, '_');
+    }
+    if SQL_RESERVED.contains(&identifier.as_str()) {
+        identifier.push('_');
+    }
+    identifier
+}
+
+/// Spreadsheet column letters for a 0-based index: `A`, ..., `Z`, `AA`, ..., `ZZ`, `AAA`, ...
+pub fn spreadsheet_name(index: usize) -> String {
+    let mut name = Vec::new();
+    let mut n = index + 1;
+    while n > 0 {
+        n -= 1;
+        name.push(b'A' + (n % 26) as u8);
+        n /= 26;
+    }
+    name.reverse();
+    String::from_utf8(name).unwrap_or_default()
+}
+
+// Adjective and noun pairs in a fixed order; narrow columns get just the noun
+fn word_name(index: usize, width: usize) -> String {
+    let noun = NOUNS[index % NOUNS.len()];
+    let adjective = ADJECTIVES[(index / NOUNS.len()) % ADJECTIVES.len()];
+    let round = index / (NOUNS.len() * ADJECTIVES.len());
+
+    let mut name = format!("{}_{}", adjective, noun);
+    if name.len() > width {
+        name = noun.to_string();
+    }
+    if round > 0 {
+        name = format!("{}_{}", name, round + 1);
+    }
+    name
+}
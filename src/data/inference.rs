@@ -0,0 +1,197 @@
+use crate::data::types::{ColumnSpec, DataType, SchemaSpec};
+use crate::utils::dates;
+use anyhow::Result;
+use csv::ReaderBuilder;
+use std::collections::HashSet;
+use std::fs::File;
+use std::io::Read;
+
+/// Columns with at most this many distinct values are inferred as enums by default.
+pub const DEFAULT_MAX_ENUM_VALUES: usize = 20;
+
+/// Infers a `SchemaSpec` from the CSV file at `path`.
+pub fn infer_schema_from_file(path: &str, max_enum_values: usize) -> Result<SchemaSpec> {
+    let file = File::open(path)?;
+    infer_schema(file, max_enum_values)
+}
+
+/// Infers per-column types, length ranges, null rates and uniqueness from a headed CSV.
+///
+/// Empty fields count as nulls and are ignored when choosing a type.
+pub fn infer_schema<R: Read>(reader: R, max_enum_values: usize) -> Result<SchemaSpec> {
+    let mut reader = ReaderBuilder::new().from_reader(reader);
+    let headers = reader.headers()?.clone();
+    let mut stats: Vec<ColumnStats> = headers.iter().map(|_| ColumnStats::new()).collect();
+    let mut sample_rows = 0;
+
+    for record in reader.records() {
+        let record = record?;
+        for (i, value) in record.iter().enumerate() {
+            if let Some(column) = stats.get_mut(i) {
+                column.observe(value);
+            }
+        }
+        sample_rows += 1;
+    }
+
+    let columns = headers
+        .iter()
+        .zip(stats)
+        .map(|(name, column)| column.into_spec(name, sample_rows, max_enum_values))
+        .collect();
+
+    Ok(SchemaSpec { columns, sample_rows })
+}
+
+struct ColumnStats {
+    non_null: usize,
+    min_len: usize,
+    max_len: usize,
+    total_len: usize,
+    all_integer: bool,
+    // Bounds of the integer values and whether each was above the one before
+    min_integer: i64,
+    max_integer: i64,
+    ascending: bool,
+    all_decimal: bool,
+    max_scale: usize,
+    all_date: bool,
+    all_email: bool,
+    all_domain: bool,
+    distinct: HashSet<String>,
+}
+
+impl ColumnStats {
+    fn new() -> Self {
+        Self {
+            non_null: 0,
+            min_len: usize::MAX,
+            max_len: 0,
+            total_len: 0,
+            all_integer: true,
+            min_integer: i64::MAX,
+            max_integer: i64::MIN,
+            ascending: true,
+            all_decimal: true,
+            max_scale: 0,
+            all_date: true,
+            all_email: true,
+            all_domain: true,
+            distinct: HashSet::new(),
+        }
+    }
+
+    fn observe(&mut self, value: &str) {
+        if value.is_empty() {
+            return;
+        }
+
+        self.non_null += 1;
+        self.min_len = self.min_len.min(value.len());
+        self.max_len = self.max_len.max(value.len());
+        self.total_len += value.len();
+
+        self.all_integer &= is_integer(value);
+        if self.all_integer {
+            let integer: i64 = value.parse().unwrap_or_default();
+            self.ascending &= self.non_null == 1 || integer > self.max_integer;
+            self.min_integer = self.min_integer.min(integer);
+            self.max_integer = self.max_integer.max(integer);
+        }
+        match decimal_scale(value) {
+            Some(scale) => self.max_scale = self.max_scale.max(scale),
+            None => self.all_decimal = false,
+        }
+        self.all_date &= dates::parse_iso_date(value).is_some();
+        self.all_email &= is_email(value);
+        self.all_domain &= is_domain(value);
+
+        if !self.distinct.contains(value) {
+            self.distinct.insert(value.to_string());
+        }
+    }
+
+    fn into_spec(self, name: &str, sample_rows: usize, max_enum_values: usize) -> ColumnSpec {
+        let null_rate = if sample_rows == 0 {
+            0.0
+        } else {
+            (sample_rows - self.non_null) as f64 / sample_rows as f64
+        };
+        let unique = self.non_null > 0 && self.distinct.len() == self.non_null;
+
+        let data_type = if self.non_null == 0 {
+            DataType::String
+        } else if self.all_integer && unique && (self.ascending || looks_like_id(name)) {
+            DataType::UniqueId
+        } else if self.all_integer && unique {
+            // Distinct in a sample but not an ID, like prices or counts: keep the sample's bounds
+            DataType::Range { min: self.min_integer, max: self.max_integer }
+        } else if self.all_integer {
+            DataType::Number
+        } else if self.all_decimal {
+            DataType::Decimal { scale: self.max_scale }
+        } else if self.all_date {
+            DataType::Date
+        } else if self.all_email {
+            DataType::Email
+        } else if self.all_domain {
+            DataType::Domain
+        } else if self.distinct.len() <= max_enum_values && self.distinct.len() * 2 <= self.non_null {
+            let mut values: Vec<String> = self.distinct.into_iter().collect();
+            values.sort();
+            DataType::Enum(values)
+        } else {
+            DataType::String
+        };
+
+        ColumnSpec {
+            name: name.to_string(),
+            data_type,
+            min_len: if self.non_null == 0 { 0 } else { self.min_len },
+            max_len: self.max_len,
+            avg_len: if self.non_null == 0 { 0.0 } else { self.total_len as f64 / self.non_null as f64 },
+            null_rate,
+            unique,
+        }
+    }
+}
+
+// `id`, `ID`, `user_id`, `user id` or `userId`
+fn looks_like_id(name: &str) -> bool {
+    let name = name.trim();
+    let lower = name.to_ascii_lowercase();
+    lower == "id" || lower.ends_with("_id") || lower.ends_with(" id") || name.ends_with("Id")
+}
+
+pub(crate) fn is_integer(value: &str) -> bool {
+    let digits = value.strip_prefix('-').unwrap_or(value);
+    !digits.is_empty() && digits.len() <= 18 && digits.bytes().all(|b| b.is_ascii_digit())
+}
+
+// Number of fraction digits if `value` is a plain decimal such as `-12.50`
+pub(crate) fn decimal_scale(value: &str) -> Option<usize> {
+    let (int_part, frac_part) = value.split_once('.').unwrap_or((value, ""));
+    let int_ok = int_part.is_empty() || is_integer(int_part);
+    let frac_ok = frac_part.bytes().all(|b| b.is_ascii_digit());
+    (int_ok && frac_ok && (!int_part.is_empty() || !frac_part.is_empty())).then_some(frac_part.len())
+}
+
+pub(crate) fn is_email(value: &str) -> bool {
+    match value.split_once('@') {
+        Some((local, domain)) => {
+            !local.is_empty() && !local.contains(char::is_whitespace) && is_domain(domain)
+        }
+        None => false,
+    }
+}
+
+pub(crate) fn is_domain(value: &str) -> bool {
+    let labels: Vec<&str> = value.split('.').collect();
+    labels.len() >= 2
+        && labels
+            .iter()
+            .all(|label| !label.is_empty() && label.bytes().all(|b| b.is_ascii_alphanumeric() || b == b'-'))
+        && labels
+            .last()
+            .is_some_and(|tld| tld.len() >= 2 && tld.bytes().all(|b| b.is_ascii_alphabetic()))
+}
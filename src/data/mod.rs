@@ -1,6 +1,13 @@
 pub mod types;
 pub mod generators;
 pub mod schema;
+pub mod headers;
+pub mod planner;
+pub mod pattern;
+pub mod inference;
+pub mod verify;
+pub mod timeseries;
 
 pub use types::CsvSchema;
-pub use generators::DataGenerator;
\ No newline at end of file
+pub use generators::DataGenerator;
+pub use pattern::Pattern;
\ No newline at end of file
@@ -0,0 +1,474 @@
+use anyhow::{anyhow, bail, Result};
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+
+// Characters used for `.` and negated classes. Commas and double quotes are left out so
+// generated values never force the CSV writer to quote them (which would break sizing).
+const SAFE_CHARS: &str = " !#$%&'()*+-./0123456789:;<=>?@ABCDEFGHIJKLMNOPQRSTUVWXYZ[\\]^_`abcdefghijklmnopqrstuvwxyz{|}~";
+const DOT_CHARS: &str = "abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ0123456789";
+// Characters the CSV writer quotes a field for; a pattern can't produce them
+const QUOTED_CHARS: &str = ",\"\r\n\t";
+
+/// A random string generator driven by a subset of regex syntax.
+///
+/// Supported: literals, escapes (`\d`, `\w`, `\s` and their negations), `.`, character
+/// classes (`[a-z0-9_]`, `[^...]`), groups (`(...)`, `(?:...)`), alternation (`|`) and the
+/// quantifiers `*`, `+`, `?`, `{n}`, `{n,}` and `{n,m}`. Anchors are accepted and ignored.
+///
+/// Patterns with unbounded quantifiers need a length cap so the column can be sized. Commas,
+/// double quotes, tabs and line breaks are rejected: the CSV writer would quote the value and
+/// make it wider than planned. Class ranges that span them leave them out.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(try_from = "PatternSpec", into = "PatternSpec")]
+pub struct Pattern {
+    source: String,
+    max_len_cap: Option<usize>,
+    node: Node,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct PatternSpec {
+    regex: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    max_len: Option<usize>,
+}
+
+#[derive(Debug, Clone)]
+enum Node {
+    Literal(char),
+    Class(Vec<char>),
+    Concat(Vec<Node>),
+    Alternation(Vec<Node>),
+    Repeat {
+        node: Box<Node>,
+        min: usize,
+        max: Option<usize>,
+    },
+}
+
+impl Pattern {
+    /// Parses a bounded pattern. Fails if the pattern can match arbitrarily long strings.
+    pub fn new(source: &str) -> Result<Self> {
+        Self::build(source, None)
+    }
+
+    /// Parses a pattern whose generated values are capped at `max_len` bytes.
+    pub fn with_max_len(source: &str, max_len: usize) -> Result<Self> {
+        Self::build(source, Some(max_len))
+    }
+
+    fn build(source: &str, max_len_cap: Option<usize>) -> Result<Self> {
+        let chars: Vec<char> = source.chars().collect();
+        let mut parser = Parser { chars: &chars, pos: 0 };
+        let node = parser.parse_alternation()?;
+        if parser.pos < chars.len() {
+            bail!("Unmatched ')' at position {} in pattern '{}'", parser.pos, source);
+        }
+
+        if node.len_range().is_none() {
+            bail!("Pattern '{}' repeats too many times: its length doesn't fit in memory", source);
+        }
+
+        let pattern = Self {
+            source: source.to_string(),
+            max_len_cap,
+            node,
+        };
+
+        match (pattern.node.max_len(), max_len_cap) {
+            (None, None) => bail!(
+                "Pattern '{}' is unbounded; provide a length cap to use it as a column",
+                source
+            ),
+            (_, Some(cap)) if pattern.min_len() > cap => bail!(
+                "Pattern '{}' needs at least {} bytes but is capped at {}",
+                source,
+                pattern.min_len(),
+                cap
+            ),
+            _ => Ok(pattern),
+        }
+    }
+
+    pub fn source(&self) -> &str {
+        &self.source
+    }
+
+    /// Shortest value this pattern can produce, in bytes.
+    pub fn min_len(&self) -> usize {
+        self.node.min_len()
+    }
+
+    /// Longest value this pattern can produce, in bytes, after applying the length cap.
+    pub fn max_len(&self) -> usize {
+        match (self.node.max_len(), self.max_len_cap) {
+            (Some(max), Some(cap)) => max.min(cap),
+            (Some(max), None) => max,
+            (None, Some(cap)) => cap,
+            // Unbounded patterns are rejected at construction
+            (None, None) => self.min_len(),
+        }
+    }
+
+    /// Width the schema planner reserves for this pattern: the midpoint of its length range.
+    pub fn planned_len(&self) -> usize {
+        self.min_len() + (self.max_len() - self.min_len()).div_ceil(2)
+    }
+
+    /// Whether `value` is one this pattern could produce: it matches the whole pattern and
+    /// fits the length cap.
+    pub fn matches(&self, value: &str) -> bool {
+        let chars: Vec<char> = value.chars().collect();
+        value.len() <= self.max_len() && self.node.match_ends(&chars, 0).contains(&chars.len())
+    }
+
+    pub fn generate<R: Rng + ?Sized>(&self, rng: &mut R) -> String {
+        let mut out = String::with_capacity(self.max_len());
+        self.node.generate(rng, self.max_len(), &mut out);
+        out
+    }
+}
+
+impl TryFrom<PatternSpec> for Pattern {
+    type Error = anyhow::Error;
+
+    fn try_from(spec: PatternSpec) -> Result<Self> {
+        Self::build(&spec.regex, spec.max_len)
+    }
+}
+
+impl From<Pattern> for PatternSpec {
+    fn from(pattern: Pattern) -> Self {
+        Self {
+            regex: pattern.source,
+            max_len: pattern.max_len_cap,
+        }
+    }
+}
+
+impl Node {
+    // Patterns are checked with `len_range` when built, so these can't overflow
+    fn min_len(&self) -> usize {
+        self.len_range().map_or(usize::MAX, |(min, _)| min)
+    }
+
+    fn max_len(&self) -> Option<usize> {
+        self.len_range().and_then(|(_, max)| max)
+    }
+
+    // Shortest and longest match in bytes, the longest `None` if unbounded; `None` if either
+    // doesn't fit in a usize
+    fn len_range(&self) -> Option<(usize, Option<usize>)> {
+        match self {
+            Node::Literal(c) => Some((c.len_utf8(), Some(c.len_utf8()))),
+            Node::Class(chars) => {
+                let lens = chars.iter().map(|c| c.len_utf8());
+                Some((lens.clone().min().unwrap_or(0), Some(lens.max().unwrap_or(0))))
+            }
+            Node::Concat(nodes) => nodes.iter().try_fold((0, Some(0)), |(min, max): (usize, Option<usize>), node| {
+                let (node_min, node_max) = node.len_range()?;
+                let max = match (max, node_max) {
+                    (Some(max), Some(node_max)) => Some(max.checked_add(node_max)?),
+                    _ => None,
+                };
+                Some((min.checked_add(node_min)?, max))
+            }),
+            Node::Alternation(nodes) => {
+                let ranges = nodes.iter().map(Node::len_range).collect::<Option<Vec<_>>>()?;
+                let min = ranges.iter().map(|(min, _)| *min).min().unwrap_or(0);
+                let max = ranges.iter().try_fold(0, |acc, (_, max)| max.map(|max| acc.max(max)));
+                Some((min, max))
+            }
+            Node::Repeat { node, min, max } => {
+                let (node_min, node_max) = node.len_range()?;
+                let max = match (node_max, max) {
+                    (Some(0), _) => Some(0),
+                    (Some(len), Some(max)) => Some(len.checked_mul(*max)?),
+                    _ => None,
+                };
+                Some((node_min.checked_mul(*min)?, max))
+            }
+        }
+    }
+
+    // Every position where a match of this node starting at `start` can end
+    fn match_ends(&self, chars: &[char], start: usize) -> BTreeSet<usize> {
+        let from_all = |node: &Node, starts: &BTreeSet<usize>| -> BTreeSet<usize> {
+            starts.iter().flat_map(|&pos| node.match_ends(chars, pos)).collect()
+        };
+        match self {
+            Node::Literal(c) => chars.get(start).filter(|&next| next == c).map(|_| start + 1).into_iter().collect(),
+            Node::Class(class) => chars.get(start).filter(|next| class.contains(next)).map(|_| start + 1).into_iter().collect(),
+            Node::Concat(nodes) => nodes.iter().fold(BTreeSet::from([start]), |ends, node| from_all(node, &ends)),
+            Node::Alternation(nodes) => nodes.iter().flat_map(|node| node.match_ends(chars, start)).collect(),
+            Node::Repeat { node, min, max } => {
+                let mut ends = BTreeSet::new();
+                if *min == 0 {
+                    ends.insert(start);
+                }
+                // Past `min`, more repeats than characters left cannot reach anywhere new
+                let limit = max.unwrap_or(usize::MAX).min(min.saturating_add(chars.len() - start + 1));
+                let mut current = BTreeSet::from([start]);
+                for count in 1..=limit {
+                    current = from_all(node, &current);
+                    if current.is_empty() {
+                        break;
+                    }
+                    if count >= *min {
+                        ends.extend(&current);
+                    }
+                }
+                ends
+            }
+        }
+    }
+
+    // Appends a value of at most `budget` bytes. Callers guarantee `budget >= self.min_len()`.
+    fn generate<R: Rng + ?Sized>(&self, rng: &mut R, budget: usize, out: &mut String) {
+        match self {
+            Node::Literal(c) => out.push(*c),
+            Node::Class(chars) => {
+                let fitting: Vec<char> = chars.iter().copied().filter(|c| c.len_utf8() <= budget).collect();
+                out.push(fitting[rng.gen_range(0..fitting.len())]);
+            }
+            Node::Concat(nodes) => {
+                // Reserve enough room for the minimum of every node still to come
+                let mut rest_min: usize = nodes.iter().map(Node::min_len).sum();
+                let mut used = 0;
+                for node in nodes {
+                    rest_min -= node.min_len();
+                    let start = out.len();
+                    node.generate(rng, budget - used - rest_min, out);
+                    used += out.len() - start;
+                }
+            }
+            Node::Alternation(nodes) => {
+                let fitting: Vec<&Node> = nodes.iter().filter(|n| n.min_len() <= budget).collect();
+                fitting[rng.gen_range(0..fitting.len())].generate(rng, budget, out);
+            }
+            Node::Repeat { node, min, max } => {
+                let item_min = node.min_len();
+                let fit = budget.checked_div(item_min).unwrap_or(budget.max(*min));
+                let upper = max.map_or(fit, |max| max.min(fit)).max(*min);
+                let count = rng.gen_range(*min..=upper);
+
+                let mut used = 0;
+                for i in 0..count {
+                    let start = out.len();
+                    let remaining_min = item_min * (count - i - 1);
+                    node.generate(rng, budget - used - remaining_min, out);
+                    used += out.len() - start;
+                }
+            }
+        }
+    }
+}
+
+struct Parser<'a> {
+    chars: &'a [char],
+    pos: usize,
+}
+
+impl Parser<'_> {
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.pos).copied()
+    }
+
+    fn next(&mut self) -> Result<char> {
+        let c = self.peek().ok_or_else(|| anyhow!("Unexpected end of pattern"))?;
+        self.pos += 1;
+        Ok(c)
+    }
+
+    fn eat(&mut self, expected: char) -> bool {
+        if self.peek() == Some(expected) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn parse_alternation(&mut self) -> Result<Node> {
+        let mut branches = vec![self.parse_concat()?];
+        while self.eat('|') {
+            branches.push(self.parse_concat()?);
+        }
+        Ok(match <[Node; 1]>::try_from(branches) {
+            Ok([branch]) => branch,
+            Err(branches) => Node::Alternation(branches),
+        })
+    }
+
+    fn parse_concat(&mut self) -> Result<Node> {
+        let mut nodes = Vec::new();
+        while let Some(c) = self.peek() {
+            if c == '|' || c == ')' {
+                break;
+            }
+            if let Some(atom) = self.parse_atom()? {
+                nodes.push(self.parse_quantifier(atom)?);
+            }
+        }
+        Ok(match <[Node; 1]>::try_from(nodes) {
+            Ok([node]) => node,
+            Err(nodes) => Node::Concat(nodes),
+        })
+    }
+
+    fn parse_atom(&mut self) -> Result<Option<Node>> {
+        let c = self.next()?;
+        let node = match c {
+            '^' | '$' => return Ok(None),
+            '(' => {
+                if self.eat('?') && !self.eat(':') {
+                    bail!("Only non-capturing groups '(?:...)' are supported");
+                }
+                let inner = self.parse_alternation()?;
+                if !self.eat(')') {
+                    bail!("Unclosed group in pattern");
+                }
+                inner
+            }
+            '[' => self.parse_class()?,
+            '.' => Node::Class(DOT_CHARS.chars().collect()),
+            '\\' => self.parse_escape()?,
+            '*' | '+' | '?' | '{' => bail!("Quantifier '{}' at position {} has nothing to repeat", c, self.pos - 1),
+            _ => Node::Literal(unquoted(c)?),
+        };
+        Ok(Some(node))
+    }
+
+    fn parse_escape(&mut self) -> Result<Node> {
+        let c = self.next()?;
+        Ok(match escape_class(c) {
+            Some(chars) => Node::Class(chars),
+            None => Node::Literal(unquoted(escape_literal(c))?),
+        })
+    }
+
+    fn parse_class(&mut self) -> Result<Node> {
+        let negated = self.eat('^');
+        let mut chars = Vec::new();
+        let mut first = true;
+
+        loop {
+            let c = self.next().map_err(|_| anyhow!("Unclosed character class in pattern"))?;
+            if c == ']' && !first {
+                break;
+            }
+            first = false;
+
+            let start = if c == '\\' {
+                let escaped = self.next()?;
+                if let Some(class) = escape_class(escaped) {
+                    chars.extend(class);
+                    continue;
+                }
+                unquoted(escape_literal(escaped))?
+            } else {
+                unquoted(c)?
+            };
+
+            if self.peek() == Some('-') && self.chars.get(self.pos + 1).is_some_and(|&n| n != ']') {
+                self.pos += 1;
+                let end = match self.next()? {
+                    '\\' => unquoted(escape_literal(self.next()?))?,
+                    end => unquoted(end)?,
+                };
+                if end < start {
+                    bail!("Invalid class range '{}-{}'", start, end);
+                }
+                chars.extend((start..=end).filter(|c| !QUOTED_CHARS.contains(*c)));
+            } else {
+                chars.push(start);
+            }
+        }
+
+        if negated {
+            chars = SAFE_CHARS.chars().filter(|c| !chars.contains(c)).collect();
+        } else {
+            chars.sort_unstable();
+            chars.dedup();
+        }
+        if chars.is_empty() {
+            bail!("Character class matches nothing");
+        }
+        Ok(Node::Class(chars))
+    }
+
+    fn parse_quantifier(&mut self, atom: Node) -> Result<Node> {
+        let (min, max) = match self.peek() {
+            Some('*') => (0, None),
+            Some('+') => (1, None),
+            Some('?') => (0, Some(1)),
+            Some('{') => {
+                self.pos += 1;
+                let min = self.parse_number()?;
+                let max = if self.eat(',') {
+                    if self.peek() == Some('}') { None } else { Some(self.parse_number()?) }
+                } else {
+                    Some(min)
+                };
+                if self.peek() != Some('}') {
+                    bail!("Unclosed repetition '{{' in pattern");
+                }
+                if max.is_some_and(|max| max < min) {
+                    bail!("Invalid repetition {{{},{}}}", min, max.unwrap());
+                }
+                (min, max)
+            }
+            _ => return Ok(atom),
+        };
+        self.pos += 1;
+        // Lazy modifiers don't change what can be generated
+        self.eat('?');
+
+        Ok(Node::Repeat {
+            node: Box::new(atom),
+            min,
+            max,
+        })
+    }
+
+    fn parse_number(&mut self) -> Result<usize> {
+        let start = self.pos;
+        while self.peek().is_some_and(|c| c.is_ascii_digit()) {
+            self.pos += 1;
+        }
+        let digits: String = self.chars[start..self.pos].iter().collect();
+        digits
+            .parse()
+            .map_err(|_| anyhow!("Expected a number in repetition at position {}", start))
+    }
+}
+
+fn escape_class(c: char) -> Option<Vec<char>> {
+    let class: Vec<char> = match c {
+        'd' => ('0'..='9').collect(),
+        'w' => ('a'..='z').chain('A'..='Z').chain('0'..='9').chain(['_']).collect(),
+        's' => vec![' '],
+        'D' | 'W' | 'S' => {
+            let excluded = escape_class(c.to_ascii_lowercase())?;
+            SAFE_CHARS.chars().filter(|c| !excluded.contains(c)).collect()
+        }
+        _ => return None,
+    };
+    Some(class)
+}
+
+fn escape_literal(c: char) -> char {
+    match c {
+        'n' => '\n',
+        't' => '\t',
+        _ => c,
+    }
+}
+
+fn unquoted(c: char) -> Result<char> {
+    if QUOTED_CHARS.contains(c) {
+        bail!("Patterns can't contain '{}': the CSV writer would quote the value", c.escape_default());
+    }
+    Ok(c)
+}
@@ -0,0 +1,334 @@
+use crate::data::headers::HeaderStrategy;
+use crate::data::schema::SchemaBuilder;
+use crate::data::types::{CsvSchema, DataType, FixedColumn, SchemaSpec};
+use anyhow::{anyhow, Result};
+use std::str::FromStr;
+
+/// Width given to each regular column when no size forces one and no cell width is asked for
+pub const DEFAULT_COLUMN_WIDTH: usize = 12;
+/// Column count used when only one of size and rows is known and no count is forced
+pub const DEFAULT_COLUMN_COUNT: usize = 10;
+/// Longest row the planner lays out, in bytes. Headers are padded to the column widths, so
+/// the header line is about a row long and has to fit in memory.
+pub const MAX_ROW_SIZE: usize = 1024 * 1024 * 1024;
+
+const NEWLINE_SIZE: usize = 1;
+// Widths the planner gives email and domain columns when it is free to choose
+const NATURAL_EMAIL_WIDTH: usize = 20;
+const NATURAL_DOMAIN_WIDTH: usize = 15;
+
+/// Why a schema could not be planned.
+#[derive(Debug, thiserror::Error)]
+pub enum PlanError {
+    /// The size, row and column constraints cannot all be met
+    #[error("{0}")]
+    Impossible(String),
+    /// The columns asked for are invalid whatever the size
+    #[error("{0}")]
+    InvalidSchema(String),
+}
+
+pub type PlanResult<T> = std::result::Result<T, PlanError>;
+
+/// The quantity the planner derived from the others.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Solved {
+    /// Both size and rows were given
+    Nothing,
+    Rows,
+    Size,
+}
+
+/// A planned schema together with the size and row count it was planned for.
+#[derive(Debug, Clone)]
+pub struct Plan {
+    pub schema: CsvSchema,
+    pub num_rows: usize,
+    /// Expected size of the planned file in bytes, which may differ a little from a given size
+    pub target_size: usize,
+    /// The size that was asked for, if one was
+    pub requested_size: Option<usize>,
+    pub solved: Solved,
+}
+
+/// What the solver optimizes once the target size can be hit.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SolverObjective {
+    /// As few columns as allowed, so each is as wide as possible
+    #[default]
+    FewestColumns,
+    /// As many columns as fit, down to two bytes each
+    MostColumns,
+    /// Regular columns close to this many bytes wide
+    CellWidth(usize),
+}
+
+/// Parses `fewest`, `most` or `width:N`.
+impl FromStr for SolverObjective {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_lowercase().split_once(':') {
+            None if s.eq_ignore_ascii_case("fewest") => Ok(SolverObjective::FewestColumns),
+            None if s.eq_ignore_ascii_case("most") => Ok(SolverObjective::MostColumns),
+            Some(("width", width)) => match width.parse() {
+                Ok(width) if width > 0 => Ok(SolverObjective::CellWidth(width)),
+                _ => Err(anyhow!("Cell width '{}' must be a positive integer", width)),
+            },
+            _ => Err(anyhow!("Unknown objective '{}'. Use fewest, most or width:N", s)),
+        }
+    }
+}
+
+/// Column options for a generated (not inferred) schema.
+#[derive(Debug, Clone)]
+pub struct ColumnOptions {
+    pub min_columns: usize,
+    pub max_columns: usize,
+    pub email_columns: usize,
+    pub domain_columns: usize,
+    pub fixed_columns: Vec<FixedColumn>,
+    pub header_strategy: HeaderStrategy,
+    pub objective: SolverObjective,
+}
+
+impl ColumnOptions {
+    fn typed_columns(&self) -> usize {
+        1 + self.email_columns + self.domain_columns + self.fixed_columns.len()
+    }
+
+    /// Column count used when the planner is free to choose one: the most allowed when
+    /// preferring more columns, otherwise the default count.
+    fn natural_column_count(&self) -> PlanResult<usize> {
+        if self.min_columns > self.max_columns {
+            return Err(PlanError::Impossible(format!(
+                "Minimum columns ({}) exceeds maximum columns ({})",
+                self.min_columns,
+                self.max_columns
+            )));
+        }
+        let preferred = match self.objective {
+            SolverObjective::MostColumns => self.max_columns,
+            _ => DEFAULT_COLUMN_COUNT,
+        };
+        let count = preferred
+            .max(self.typed_columns())
+            .clamp(self.min_columns, self.max_columns);
+        if count < self.typed_columns() {
+            return Err(PlanError::Impossible(format!("{} columns cannot hold all requested typed columns", count)));
+        }
+        Ok(count)
+    }
+
+    fn natural_column_width(&self) -> usize {
+        match self.objective {
+            SolverObjective::CellWidth(width) => width,
+            _ => DEFAULT_COLUMN_WIDTH,
+        }
+    }
+
+    // Row size (separators included) when every column gets its natural width; an error if
+    // that is more than `MAX_ROW_SIZE`
+    fn natural_row_size(&self, num_columns: usize, num_rows: usize) -> PlanResult<usize> {
+        let id_width = num_rows.to_string().len() + 3;
+        let regular = num_columns - self.typed_columns();
+        let size = self
+            .fixed_columns
+            .iter()
+            .try_fold(id_width, |size, c| size.checked_add(c.size_bytes))
+            .and_then(|size| size.checked_add(self.email_columns.checked_mul(NATURAL_EMAIL_WIDTH)?))
+            .and_then(|size| size.checked_add(self.domain_columns.checked_mul(NATURAL_DOMAIN_WIDTH)?))
+            .and_then(|size| size.checked_add(regular.checked_mul(self.natural_column_width())?))
+            .and_then(|size| size.checked_add(num_columns))
+            .filter(|&size| size <= MAX_ROW_SIZE);
+        size.ok_or_else(|| PlanError::Impossible(format!("Rows of these columns would be more than {} bytes", MAX_ROW_SIZE)))
+    }
+}
+
+/// Plans a generated schema from a target size, a row count, or both.
+///
+/// With both, this is [`SchemaBuilder::build_schema_with_options`]. With only a row
+/// count, columns get their natural widths (the objective's cell width, if it has one) and
+/// the size follows. With only a size, the row
+/// count is the number of natural-width rows that fit, and widths are then tuned to hit the
+/// size exactly. The plan reports the size it comes to, not the one asked for.
+pub fn plan_generated(target_size: Option<usize>, num_rows: Option<usize>, options: &ColumnOptions) -> PlanResult<Plan> {
+    let build = |target_size: usize, num_rows: usize, min_columns: usize, max_columns: usize| {
+        let options = ColumnOptions {
+            min_columns,
+            max_columns,
+            ..options.clone()
+        };
+        SchemaBuilder::build_schema_with_options(target_size, num_rows, &options)
+    };
+
+    if let (Some(target_size), Some(num_rows)) = (target_size, num_rows)
+        && num_rows > 0
+        && target_size / num_rows > MAX_ROW_SIZE
+    {
+        return Err(PlanError::Impossible(format!(
+            "{} bytes over {} rows makes rows longer than the {}-byte limit; ask for more rows",
+            target_size,
+            num_rows,
+            MAX_ROW_SIZE
+        )));
+    }
+
+    match (target_size, num_rows) {
+        (Some(target_size), Some(num_rows)) => {
+            let schema = build(target_size, num_rows, options.min_columns, options.max_columns)?;
+            Ok(Plan {
+                target_size: expected_size(&schema, num_rows)?,
+                schema,
+                num_rows,
+                requested_size: Some(target_size),
+                solved: Solved::Nothing,
+            })
+        }
+        (None, Some(num_rows)) => {
+            let num_columns = options.natural_column_count()?;
+            // The header is padded to the column widths, so it is about one row long
+            let row_size = options.natural_row_size(num_columns, num_rows)?;
+            let natural_size = num_rows
+                .checked_add(1)
+                .and_then(|lines| lines.checked_mul(row_size))
+                .ok_or_else(|| PlanError::Impossible(format!("{} rows would be more bytes than fit in a usize", num_rows)))?;
+            let schema = build(natural_size, num_rows, num_columns, num_columns)?;
+            Ok(Plan {
+                target_size: expected_size(&schema, num_rows)?,
+                schema,
+                num_rows,
+                requested_size: None,
+                solved: Solved::Size,
+            })
+        }
+        (Some(target_size), None) => {
+            let num_columns = options.natural_column_count()?;
+            // The ID width depends on the row count, so refine the estimate once
+            let mut num_rows = target_size / options.natural_row_size(num_columns, 1)?;
+            num_rows = (target_size / options.natural_row_size(num_columns, num_rows.max(1))?).saturating_sub(1);
+            if num_rows == 0 {
+                return Err(PlanError::Impossible(format!("Target size of {} bytes is too small for a single row", target_size)));
+            }
+            let schema = build(target_size, num_rows, num_columns, num_columns)?;
+            Ok(Plan {
+                target_size: expected_size(&schema, num_rows)?,
+                schema,
+                num_rows,
+                requested_size: Some(target_size),
+                solved: Solved::Rows,
+            })
+        }
+        (None, None) => Err(PlanError::Impossible("Specify a target size, a row count, or both".to_string())),
+    }
+}
+
+/// Plans a schema file from a target size, a row count, or both.
+///
+/// With only a row count, columns keep their sampled widths. With only a size, the row count
+/// is the number of sampled-width rows that fit.
+pub fn plan_from_spec(spec: &SchemaSpec, target_size: Option<usize>, num_rows: Option<usize>) -> PlanResult<Plan> {
+    let header_line = spec.columns.iter().map(|c| c.name.len() + 1).sum::<usize>();
+
+    match (target_size, num_rows) {
+        (Some(target_size), Some(num_rows)) => Ok(Plan {
+            schema: SchemaBuilder::build_schema_from_spec(spec, target_size, num_rows)?,
+            num_rows,
+            target_size,
+            requested_size: Some(target_size),
+            solved: Solved::Nothing,
+        }),
+        (None, Some(num_rows)) => {
+            let natural_size = header_line + (num_rows as f64 * SchemaBuilder::natural_spec_row_size(spec, num_rows)).round() as usize;
+            Ok(Plan {
+                schema: SchemaBuilder::build_schema_from_spec(spec, natural_size, num_rows)?,
+                num_rows,
+                target_size: natural_size,
+                requested_size: None,
+                solved: Solved::Size,
+            })
+        }
+        (Some(target_size), None) => {
+            let mut num_rows = 1;
+            // The ID width depends on the row count, so refine the estimate a few times
+            for _ in 0..3 {
+                num_rows = (target_size.saturating_sub(header_line) as f64 / SchemaBuilder::natural_spec_row_size(spec, num_rows)) as usize;
+                num_rows = num_rows.max(1);
+            }
+            Ok(Plan {
+                schema: SchemaBuilder::build_schema_from_spec(spec, target_size, num_rows)?,
+                num_rows,
+                target_size,
+                requested_size: Some(target_size),
+                solved: Solved::Rows,
+            })
+        }
+        (None, None) => Err(PlanError::Impossible("Specify a target size, a row count, or both".to_string())),
+    }
+}
+
+/// Adjusts a plan for fixed-width output, where each record is its fields' full widths plus a
+/// newline, with no separators and no header.
+///
+/// A derived size becomes the row count times the record length, and a derived row count is
+/// the number of records that fit the size. When the size is given, free-text columns then
+/// take up or give back the difference, so the file lands on the target.
+pub fn plan_fixed_width(mut plan: Plan) -> PlanResult<Plan> {
+    let record_len = |schema: &CsvSchema| schema.columns.iter().map(|c| c.max_width()).sum::<usize>() + NEWLINE_SIZE;
+    let requested_size = plan.requested_size.unwrap_or(plan.target_size);
+
+    if plan.solved == Solved::Rows {
+        plan.num_rows = requested_size / record_len(&plan.schema);
+        if plan.num_rows == 0 {
+            return Err(PlanError::Impossible(format!("Target size of {} bytes is too small for a single record", requested_size)));
+        }
+    }
+    // More rows may need wider IDs
+    let id_width = plan.num_rows.to_string().len();
+    for column in &mut plan.schema.columns {
+        if matches!(column.data_type, DataType::UniqueId) {
+            column.size_bytes = column.size_bytes.max(id_width);
+        }
+    }
+
+    if plan.solved != Solved::Size {
+        let target = (requested_size / plan.num_rows) as isize;
+        let text: Vec<usize> = (0..plan.schema.columns.len())
+            .filter(|&i| matches!(plan.schema.columns[i].data_type, DataType::String))
+            .collect();
+        let mut missing = target - record_len(&plan.schema) as isize;
+        // Spread the difference evenly, the first few columns taking a byte of the remainder each
+        let shares = |bytes: usize, columns: usize| (0..columns).map(move |k| bytes / columns + usize::from(k < bytes % columns));
+        if missing > 0 && !text.is_empty() {
+            for (&i, share) in text.iter().zip(shares(missing as usize, text.len())) {
+                plan.schema.columns[i].size_bytes += share;
+            }
+        }
+        // Never shrink a column below one byte; columns that bottom out leave the rest of their
+        // share to the others
+        while missing < 0 {
+            let shrinkable: Vec<usize> = text.iter().copied().filter(|&i| plan.schema.columns[i].size_bytes > 1).collect();
+            if shrinkable.is_empty() {
+                break;
+            }
+            for (&i, share) in shrinkable.iter().zip(shares(missing.unsigned_abs(), shrinkable.len())) {
+                let column = &mut plan.schema.columns[i];
+                let cut = share.min(column.size_bytes - 1);
+                column.size_bytes -= cut;
+                missing += cut as isize;
+            }
+        }
+    }
+
+    plan.schema.target_row_size = record_len(&plan.schema);
+    plan.schema.header_size = 0;
+    plan.target_size = plan.num_rows * plan.schema.target_row_size;
+    Ok(plan)
+}
+
+fn expected_size(schema: &CsvSchema, num_rows: usize) -> PlanResult<usize> {
+    num_rows
+        .checked_mul(schema.target_row_size)
+        .and_then(|rows| rows.checked_add(schema.header_size + NEWLINE_SIZE))
+        .ok_or_else(|| PlanError::Impossible(format!("{} rows of {} bytes don't fit in a usize", num_rows, schema.target_row_size)))
+}
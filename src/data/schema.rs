@@ -1,8 +1,26 @@
-use crate::data::types::{CsvSchema, ColumnConfig, DataType};
-use anyhow::Result;
+use crate::data::generators::DataGenerator;
+use crate::data::headers::HeaderStrategy;
+use crate::data::planner::{ColumnOptions, PlanError, PlanResult, SolverObjective};
+use crate::data::pattern::Pattern;
+use crate::data::types::{CsvSchema, ColumnConfig, DataType, FixedColumn, SchemaSpec};
 
 pub struct SchemaBuilder;
 
+// Widths `SchemaBuilder::size_columns` gives each kind of column
+struct ColumnSizes {
+    first: usize,
+    email: usize,
+    domain: usize,
+    regular: usize,
+    regular_columns: usize,
+    // Regular columns that get a byte more than `regular`
+    remainder: usize,
+    // Row length with separators and the newline
+    row_size: usize,
+}
+
+const NEWLINE_SIZE: usize = 1;
+
 impl SchemaBuilder {
     pub fn build_schema(
         target_size: usize,
@@ -11,316 +29,546 @@ impl SchemaBuilder {
         max_columns: usize,
         email_columns: usize,
         domain_columns: usize,
-    ) -> Result<CsvSchema> {
-        // Use iterative approach to account for header size
-        let (columns, target_row_size, header_size) = Self::build_schema_iterative(
+    ) -> PlanResult<CsvSchema> {
+        Self::build_schema_with_patterns(
             target_size,
             num_rows,
             min_columns,
             max_columns,
             email_columns,
             domain_columns,
-        )?;
+            &[],
+        )
+    }
 
-        Ok(CsvSchema {
-            columns,
-            target_row_size,
-            header_size,
-        })
+    /// Like [`SchemaBuilder::build_schema`], plus one `Pattern` column per entry in
+    /// `pattern_columns`. Each pattern column is reserved its planned width.
+    pub fn build_schema_with_patterns(
+        target_size: usize,
+        num_rows: usize,
+        min_columns: usize,
+        max_columns: usize,
+        email_columns: usize,
+        domain_columns: usize,
+        pattern_columns: &[Pattern],
+    ) -> PlanResult<CsvSchema> {
+        let fixed_columns: Vec<FixedColumn> = pattern_columns
+            .iter()
+            .enumerate()
+            .map(|(i, pattern)| FixedColumn::pattern(i + 1, pattern))
+            .collect();
+
+        Self::build_schema_with_fixed_columns(
+            target_size,
+            num_rows,
+            min_columns,
+            max_columns,
+            email_columns,
+            domain_columns,
+            &fixed_columns,
+        )
     }
 
-    fn build_schema_iterative(
+    /// Like [`SchemaBuilder::build_schema`], plus columns of a known width (patterns,
+    /// timestamps, series values) placed after the email and domain columns.
+    pub fn build_schema_with_fixed_columns(
         target_size: usize,
         num_rows: usize,
         min_columns: usize,
         max_columns: usize,
         email_columns: usize,
         domain_columns: usize,
-    ) -> Result<(Vec<crate::data::types::ColumnConfig>, usize, usize)> {
-        let mut best_result = None;
-        let mut best_error = usize::MAX;
-        
-        // Try different column counts to find the best fit
-        for num_columns in min_columns..=max_columns {
-            // Calculate what row size we need to hit the target
-            let estimated_header_size = Self::estimate_header_for_columns(num_columns);
-            let available_for_data = target_size.saturating_sub(estimated_header_size);
-            
-            if available_for_data == 0 || num_rows == 0 {
-                continue;
+        fixed_columns: &[FixedColumn],
+    ) -> PlanResult<CsvSchema> {
+        let options = ColumnOptions {
+            min_columns,
+            max_columns,
+            email_columns,
+            domain_columns,
+            fixed_columns: fixed_columns.to_vec(),
+            header_strategy: HeaderStrategy::default(),
+            objective: SolverObjective::default(),
+        };
+        Self::build_schema_with_options(target_size, num_rows, &options)
+    }
+
+    /// Plans a schema of `target_size` bytes over `num_rows` rows from full column options.
+    ///
+    /// The objective picks a preferred column count directly: `min_columns` for the fewest
+    /// columns, the analytic upper bound refined by binary search for the most, or the size
+    /// per row divided by the cell width. A few neighbouring counts are then compared, since
+    /// each shifts the header length and rounding, and the first one within 100 bytes of the
+    /// target wins. Counts are checked and compared from their column widths alone, so
+    /// headers are only named for the count chosen and very wide tables plan fast.
+    pub fn build_schema_with_options(target_size: usize, num_rows: usize, options: &ColumnOptions) -> PlanResult<CsvSchema> {
+        const SEARCH_WINDOW: usize = 8;
+        const GOOD_ENOUGH_ERROR: usize = 100;
+
+        if num_rows == 0 {
+            return Err(PlanError::Impossible("Cannot plan a schema for zero rows".to_string()));
+        }
+        if options.min_columns > options.max_columns {
+            return Err(PlanError::Impossible(format!(
+                "Minimum columns ({}) exceeds maximum columns ({})",
+                options.min_columns,
+                options.max_columns
+            )));
+        }
+        let typed_columns = 1 + options.email_columns + options.domain_columns + options.fixed_columns.len();
+        let lowest = options.min_columns.max(typed_columns);
+        let highest = options.max_columns;
+        if lowest > highest {
+            return Err(PlanError::Impossible(format!("{} columns cannot hold all requested typed columns", highest)));
+        }
+        // Fixed columns can't shrink, so check they fit before naming any headers
+        let narrowest_row = Self::narrowest_row_size(num_rows, options);
+        let row_budget = target_size / num_rows;
+        if narrowest_row.is_none_or(|narrowest_row| narrowest_row > row_budget) {
+            return Err(PlanError::Impossible(format!(
+                "The ID and fixed-width columns need rows of at least {} bytes, but {} bytes over {} rows leaves {} per row",
+                narrowest_row.map_or_else(|| "more than usize::MAX".to_string(), |size| size.to_string()),
+                target_size,
+                num_rows,
+                row_budget
+            )));
+        }
+
+        // Names are never longer than their columns, so a count whose columns fit the rows
+        // left after a header line as long as a row fits whatever the names turn out to be
+        let fits = |num_columns: usize| {
+            Self::size_columns(num_columns, target_size / (num_rows + 1), options, num_rows).is_ok_and(|sizes| {
+                let narrowest = if sizes.regular_columns > 0 { sizes.regular } else { usize::MAX };
+                options.header_strategy.can_name(num_columns, narrowest)
+            })
+        };
+        let most_columns = || {
+            let upper = Self::column_upper_bound(target_size, num_rows, options).clamp(lowest, highest);
+            Self::most_feasible_columns(lowest, upper, fits)
+        };
+
+        let preferred = match options.objective {
+            SolverObjective::FewestColumns => lowest,
+            SolverObjective::MostColumns => most_columns(),
+            SolverObjective::CellWidth(width) => (target_size / (num_rows + 1) / (width + 1)).clamp(lowest, highest),
+        };
+        let window = |preferred: usize| -> Vec<usize> {
+            match options.objective {
+                SolverObjective::FewestColumns => (preferred..=highest).take(SEARCH_WINDOW).collect(),
+                SolverObjective::MostColumns => (lowest..=preferred).rev().take(SEARCH_WINDOW).collect(),
+                // Nearest counts first, alternating above and below
+                SolverObjective::CellWidth(_) => (0..SEARCH_WINDOW)
+                    .filter_map(|i| {
+                        let offset = i.div_ceil(2);
+                        if i % 2 == 1 { preferred.checked_add(offset) } else { preferred.checked_sub(offset) }
+                    })
+                    .filter(|n| (lowest..=highest).contains(n))
+                    .collect(),
             }
-            
-            let target_row_size = available_for_data / num_rows;
-            
-            // Try to create a schema with this column count
-            match Self::create_schema_for_exact_columns(num_columns, target_row_size, email_columns, domain_columns, num_rows) {
-                Ok((columns, actual_row_size)) => {
-                    let actual_header_size = Self::calculate_header_size(&columns);
-                    let total_size = actual_header_size + (num_rows * actual_row_size);
-                    
-                    let size_error = if total_size > target_size {
-                        total_size - target_size
-                    } else {
-                        target_size - total_size
-                    };
-                    
-                    // Update best result if this is closer to target
-                    if size_error < best_error {
-                        best_error = size_error;
-                        best_result = Some((columns, actual_row_size, actual_header_size));
-                        
-                        // If we're extremely close (within 100 bytes), we can stop
-                        if size_error < 100 {
-                            break;
-                        }
-                    }
+        };
+
+        let mut candidates: Vec<usize> = window(preferred).into_iter().filter(|&n| fits(n)).collect();
+        // A narrow cell width may ask for more columns than fit; settle for the most that do
+        if candidates.is_empty() && matches!(options.objective, SolverObjective::CellWidth(_)) {
+            candidates = vec![Self::most_feasible_columns(lowest, preferred, fits)];
+        }
+        // Tiny files may only fit once the real, shorter header is known
+        if !candidates.iter().any(|&n| fits(n)) {
+            candidates = window(preferred);
+        }
+
+        // Counts within reach of the target keep the objective's order, the rest follow by
+        // how close they come
+        let error = |num_columns| {
+            Self::padded_size(target_size, num_rows, num_columns, options).map_or(usize::MAX, |size| size.abs_diff(target_size))
+        };
+        candidates.sort_by_cached_key(|&n| Some(error(n)).filter(|&error| error >= GOOD_ENOUGH_ERROR));
+        let mut last_error = None;
+        for num_columns in candidates {
+            match Self::solve_for_columns(target_size, num_rows, num_columns, options) {
+                Ok(schema) => return Ok(schema),
+                // Another count won't fix an invalid schema
+                Err(error @ PlanError::InvalidSchema(_)) => return Err(error),
+                Err(error) => last_error = Some(error),
+            }
+        }
+        Err(last_error.unwrap_or_else(|| PlanError::Impossible("Could not find suitable column configuration".to_string())))
+    }
+
+    /// Scales an inferred `SchemaSpec` to `target_size` bytes over `num_rows` rows.
+    ///
+    /// Free-text (`String`) columns absorb the difference, in proportion to their sampled
+    /// widths; every other column keeps its sampled mean width. Null rates are taken into
+    /// account, since empty fields contribute no bytes.
+    pub fn build_schema_from_spec(spec: &SchemaSpec, target_size: usize, num_rows: usize) -> PlanResult<CsvSchema> {
+        const COMMA_SIZE: usize = 1;
+
+        if spec.columns.is_empty() {
+            return Err(PlanError::InvalidSchema("Schema spec has no columns".to_string()));
+        }
+        if num_rows == 0 {
+            return Err(PlanError::Impossible("Cannot scale a schema to zero rows".to_string()));
+        }
+
+        let header_size = spec.columns.iter().map(|c| c.name.len()).sum::<usize>() + (spec.columns.len() - 1);
+        let separator_overhead = (spec.columns.len() - 1) * COMMA_SIZE + NEWLINE_SIZE;
+        let target_row_size = target_size.saturating_sub(header_size + NEWLINE_SIZE) as f64 / num_rows as f64;
+
+        let mut widths = Self::sampled_widths(spec, num_rows);
+
+        // Expected bytes per row from the fixed columns, then share the rest among text columns
+        let is_text = |i: usize| matches!(spec.columns[i].data_type, DataType::String);
+        let fixed_bytes = Self::fixed_spec_bytes(spec, &widths);
+        let text_weight: f64 = (0..widths.len())
+            .filter(|&i| is_text(i))
+            .map(|i| spec.columns[i].avg_len.max(1.0) * (1.0 - spec.columns[i].null_rate))
+            .sum();
+        let text_bytes = target_row_size - separator_overhead as f64 - fixed_bytes;
+
+        if text_weight > 0.0 {
+            for (i, width) in widths.iter_mut().enumerate() {
+                if is_text(i) {
+                    let share = spec.columns[i].avg_len.max(1.0) / text_weight;
+                    *width = (text_bytes * share).round().max(1.0) as usize;
                 }
-                Err(_) => continue,
             }
         }
-        
-        best_result.ok_or_else(|| anyhow::anyhow!("Could not find suitable column configuration"))
+
+        // Without free text to absorb the difference, or with text already at its narrowest,
+        // the target can be out of reach; allow being a row off, as from a derived row count
+        let text_columns = (0..widths.len()).filter(|&i| is_text(i)).count();
+        let text_row_bytes: f64 = (0..widths.len())
+            .filter(|&i| is_text(i))
+            .map(|i| widths[i] as f64 * (1.0 - spec.columns[i].null_rate))
+            .sum();
+        let row_bytes = separator_overhead as f64 + fixed_bytes + text_row_bytes;
+        let expected_size = (header_size + NEWLINE_SIZE) as f64 + row_bytes * num_rows as f64;
+        let absorbed = text_columns > 0 && text_bytes >= text_columns as f64;
+        if !absorbed && (expected_size - target_size as f64).abs() > row_bytes.max(1.0) {
+            return Err(PlanError::Impossible(format!(
+                "These columns over {} rows come to about {:.0} bytes, and no free-text column can make up the difference to {} bytes",
+                num_rows,
+                expected_size,
+                target_size
+            )));
+        }
+
+        let columns: Vec<ColumnConfig> = spec
+            .columns
+            .iter()
+            .zip(&widths)
+            .map(|(c, &size_bytes)| ColumnConfig {
+                name: c.name.clone(),
+                size_bytes,
+                data_type: c.data_type.clone(),
+                null_rate: c.null_rate,
+            })
+            .collect();
+
+        Ok(CsvSchema {
+            target_row_size: separator_overhead + widths.iter().sum::<usize>(),
+            header_size: Self::calculate_header_size(&columns),
+            columns,
+        })
+    }
+
+    /// Expected bytes per row of `spec` (separators included) when every column keeps its
+    /// sampled width.
+    pub fn natural_spec_row_size(spec: &SchemaSpec, num_rows: usize) -> f64 {
+        let text_bytes: f64 = spec
+            .columns
+            .iter()
+            .filter(|c| matches!(c.data_type, DataType::String))
+            .map(|c| c.avg_len.max(1.0) * (1.0 - c.null_rate))
+            .sum();
+        spec.columns.len() as f64 + Self::fixed_spec_bytes(spec, &Self::sampled_widths(spec, num_rows)) + text_bytes
     }
 
-    fn estimate_header_for_columns(num_columns: usize) -> usize {
-        // Estimate header size based on column count
-        let avg_column_name_length = 8; // Average like "col1xxxx"
-        num_columns * avg_column_name_length + (num_columns - 1)
+    fn sampled_widths(spec: &SchemaSpec, num_rows: usize) -> Vec<usize> {
+        spec.columns
+            .iter()
+            .map(|c| match c.data_type {
+                DataType::UniqueId => num_rows.to_string().len().max(c.max_len),
+                _ => (c.avg_len.round() as usize).max(1),
+            })
+            .collect()
+    }
+
+    // Expected bytes per row of the columns other than free text. Generators such as Email
+    // or Decimal often fall short of their width, so measure them.
+    fn fixed_spec_bytes(spec: &SchemaSpec, widths: &[usize]) -> f64 {
+        spec.columns
+            .iter()
+            .zip(widths)
+            .filter(|(c, _)| !matches!(c.data_type, DataType::String))
+            .map(|(c, &width)| Self::measure_mean_length(&c.data_type, width) * (1.0 - c.null_rate))
+            .sum()
+    }
+
+    fn measure_mean_length(data_type: &DataType, size_bytes: usize) -> f64 {
+        const SAMPLES: usize = 256;
+
+        if let DataType::UniqueId = data_type {
+            return size_bytes as f64;
+        }
+        let column = ColumnConfig {
+            name: String::new(),
+            size_bytes,
+            data_type: data_type.clone(),
+            null_rate: 0.0,
+        };
+        // A fixed seed keeps plans reproducible
+        let mut generator = DataGenerator::with_seed(0);
+        let total: usize = (0..SAMPLES).map(|_| generator.generate_value(&column).len()).sum();
+        total as f64 / SAMPLES as f64
+    }
+
+    // Largest count in `lowest..=upper` that is feasible, assuming feasibility only ever
+    // drops as columns are added; `lowest` if none is
+    fn most_feasible_columns(lowest: usize, upper: usize, feasible: impl Fn(usize) -> bool) -> usize {
+        if feasible(upper) {
+            return upper;
+        }
+        let (mut low, mut high) = (lowest, upper);
+        while low + 1 < high {
+            let mid = low + (high - low) / 2;
+            if feasible(mid) {
+                low = mid;
+            } else {
+                high = mid;
+            }
+        }
+        low
+    }
+
+    // Bytes of the ID and fixed columns and their separators, which no column count can
+    // shrink; `None` on overflow
+    fn narrowest_row_size(num_rows: usize, options: &ColumnOptions) -> Option<usize> {
+        let fixed = options
+            .fixed_columns
+            .iter()
+            .try_fold(0usize, |sum, column| sum.checked_add(column.size_bytes))?;
+        (num_rows.to_string().len() + 3 + 1)
+            .checked_add(fixed)?
+            .checked_add(options.fixed_columns.len())
+    }
+
+    // Column count at which even the minimum widths use up the data bytes, ignoring the header
+    fn column_upper_bound(target_size: usize, num_rows: usize, options: &ColumnOptions) -> usize {
+        const MIN_REGULAR_COST: usize = 3; // two data bytes and a separator
+
+        let id_cost = num_rows.to_string().len() + 3 + 1;
+        let typed_cost = id_cost
+            + options.email_columns * 16
+            + options.domain_columns * 11
+            + options.fixed_columns.iter().map(|c| c.size_bytes + 1).sum::<usize>();
+        let typed_columns = 1 + options.email_columns + options.domain_columns + options.fixed_columns.len();
+        typed_columns + (target_size / num_rows).saturating_sub(typed_cost) / MIN_REGULAR_COST
+    }
+
+    // Widths and names for exactly `num_columns` columns. Padded names fill their columns, so
+    // the header line is first taken to be a row long; other names come out shorter, and the
+    // widths are then refined once with the real header.
+    fn solve_for_columns(target_size: usize, num_rows: usize, num_columns: usize, options: &ColumnOptions) -> PlanResult<CsvSchema> {
+        let build = |target_row_size| {
+            Self::create_schema_for_exact_columns(num_columns, target_row_size, options, num_rows)
+        };
+
+        let guess = Self::header_line_guess(target_size, num_rows, num_columns, options)
+            .ok_or_else(|| PlanError::Impossible(format!("No room for data after a {}-column header", num_columns)))?;
+        let (mut columns, mut target_row_size) = build((target_size - guess) / num_rows)?;
+        let header_line = Self::calculate_header_size(&columns) + NEWLINE_SIZE;
+        if header_line != guess {
+            (columns, target_row_size) = build(target_size.saturating_sub(header_line) / num_rows)?;
+        }
+
+        Ok(CsvSchema {
+            header_size: Self::calculate_header_size(&columns),
+            columns,
+            target_row_size,
+        })
+    }
+
+    // Header line length at which a header as long as a row and the rows fill the target,
+    // found from the widths alone; `None` if the columns don't fit
+    fn header_line_guess(
+        target_size: usize,
+        num_rows: usize,
+        num_columns: usize,
+        options: &ColumnOptions,
+    ) -> Option<usize> {
+        // Rows usually fill their budget exactly, so this is normally right the first time
+        let mut header_line = target_size / (num_rows + 1);
+        for _ in 0..2 {
+            let target_row_size = target_size.checked_sub(header_line)? / num_rows;
+            let row_size = Self::size_columns(num_columns, target_row_size, options, num_rows).ok()?.row_size;
+            if row_size == header_line {
+                break;
+            }
+            header_line = row_size;
+        }
+        Some(header_line)
+    }
+
+    // Planned file size for `num_columns` columns if their names fill them, as padded ones do
+    fn padded_size(
+        target_size: usize,
+        num_rows: usize,
+        num_columns: usize,
+        options: &ColumnOptions,
+    ) -> Option<usize> {
+        let header_line = Self::header_line_guess(target_size, num_rows, num_columns, options)?;
+        let target_row_size = target_size.checked_sub(header_line)? / num_rows;
+        let row_size = Self::size_columns(num_columns, target_row_size, options, num_rows).ok()?.row_size;
+        num_rows.checked_mul(row_size)?.checked_add(header_line)
     }
 
     fn create_schema_for_exact_columns(
         num_columns: usize,
         target_row_size: usize,
-        email_columns: usize,
-        domain_columns: usize,
+        options: &ColumnOptions,
         num_rows: usize,
-    ) -> Result<(Vec<crate::data::types::ColumnConfig>, usize)> {
+    ) -> PlanResult<(Vec<ColumnConfig>, usize)> {
+        let sizes = Self::size_columns(num_columns, target_row_size, options, num_rows)?;
+
+        // Build column sizes array
+        let mut column_sizes = vec![sizes.first];
+        column_sizes.extend(std::iter::repeat_n(sizes.email, options.email_columns));
+        column_sizes.extend(std::iter::repeat_n(sizes.domain, options.domain_columns));
+        column_sizes.extend(options.fixed_columns.iter().map(|column| column.size_bytes));
+        // Regular columns, the first few a byte wider to use up the remainder
+        column_sizes.extend((0..sizes.regular_columns).map(|i| sizes.regular + usize::from(i < sizes.remainder)));
+
+        let columns = Self::create_columns(
+            column_sizes,
+            options.email_columns,
+            options.domain_columns,
+            &options.fixed_columns,
+            &options.header_strategy,
+        )?;
+        Ok((columns, sizes.row_size))
+    }
+
+    // Widths for exactly `num_columns` columns in rows of `target_row_size` bytes, without
+    // building the columns, so the solver can compare counts cheaply
+    fn size_columns(num_columns: usize, target_row_size: usize, options: &ColumnOptions, num_rows: usize) -> PlanResult<ColumnSizes> {
         const COMMA_SIZE: usize = 1;
-        const NEWLINE_SIZE: usize = 1;
         const MIN_COLUMN_DATA_SIZE: usize = 2;
-        
+
+        let (email_columns, domain_columns, fixed_columns) =
+            (options.email_columns, options.domain_columns, &options.fixed_columns);
+
+        if num_columns < 1 + email_columns + domain_columns + fixed_columns.len() {
+            return Err(PlanError::Impossible(format!("{} columns cannot hold all requested typed columns", num_columns)));
+        }
+
         let separator_overhead = (num_columns - 1) * COMMA_SIZE + NEWLINE_SIZE;
         let available_data_bytes = target_row_size.saturating_sub(separator_overhead);
-        
-        if available_data_bytes < num_columns * MIN_COLUMN_DATA_SIZE {
-            return Err(anyhow::anyhow!("Not enough space for {} columns", num_columns));
+
+        if available_data_bytes < num_columns.saturating_mul(MIN_COLUMN_DATA_SIZE) {
+            return Err(PlanError::Impossible(format!("Not enough space for {} columns", num_columns)));
         }
-        
+
         // Smart ID column sizing based on actual row count
         let max_id = num_rows;
         let required_digits = max_id.to_string().len();
         let first_column_size = required_digits + 3; // Small safety buffer
-        
+
         // Calculate minimum requirements for special columns
         let min_email_size = 15; // Minimum viable email (john@x.com)
         let min_domain_size = 10; // Minimum viable domain (x.com)
-        
+
         // Reserve space for special columns first
-        let reserved_for_special = (email_columns * min_email_size) + (domain_columns * min_domain_size);
+        // Fixed columns come with their own width
+        let reserved_for_fixed = fixed_columns
+            .iter()
+            .try_fold(first_column_size, |sum, c| sum.checked_add(c.size_bytes))
+            .filter(|&reserved| reserved <= available_data_bytes)
+            .ok_or_else(|| PlanError::Impossible(format!("Fixed-width columns don't fit in {} bytes per row", available_data_bytes)))?
+            - first_column_size;
+        let reserved_for_special = (email_columns * min_email_size) + (domain_columns * min_domain_size) + reserved_for_fixed;
         let remaining_for_regular = available_data_bytes.saturating_sub(first_column_size + reserved_for_special);
-        
+
         // Calculate sizes for remaining columns
-        let remaining_columns = num_columns - 1 - email_columns - domain_columns;
-        let regular_column_size = if remaining_columns > 0 {
-            remaining_for_regular / remaining_columns
-        } else {
-            0
-        };
-        
+        let remaining_columns = num_columns - 1 - email_columns - domain_columns - fixed_columns.len();
+        let regular_column_size = remaining_for_regular.checked_div(remaining_columns).unwrap_or(0);
+
         // Distribute remaining space to email/domain columns
         let total_special_columns = email_columns + domain_columns;
         let extra_space_per_special = if remaining_columns > 0 && total_special_columns > 0 {
             remaining_for_regular % remaining_columns / total_special_columns
-        } else if total_special_columns > 0 {
-            remaining_for_regular / total_special_columns
         } else {
-            0
+            remaining_for_regular.checked_div(total_special_columns).unwrap_or(0)
         };
-        
+
         let email_column_size = min_email_size + extra_space_per_special;
         let domain_column_size = min_domain_size + extra_space_per_special;
-        
-        // Build column sizes array
-        let mut column_sizes = vec![first_column_size];
-        
-        // Add email columns
-        for _ in 0..email_columns {
-            column_sizes.push(email_column_size);
-        }
-        
-        // Add domain columns  
-        for _ in 0..domain_columns {
-            column_sizes.push(domain_column_size);
-        }
-        
-        // Add regular columns and distribute remainder
-        let remainder = remaining_for_regular % remaining_columns.max(1);
-        for i in 0..remaining_columns {
-            let size = if i < remainder {
-                regular_column_size + 1
-            } else {
-                regular_column_size
-            };
-            column_sizes.push(size);
-        }
-        
-        let actual_row_size = separator_overhead + column_sizes.iter().sum::<usize>();
-        let columns = Self::create_columns(column_sizes, email_columns, domain_columns)?;
-        
-        Ok((columns, actual_row_size))
-    }
-
 
+        // Regular columns share all of their bytes, the remainder going one each to the first few
+        let regular_bytes = if remaining_columns > 0 { remaining_for_regular } else { 0 };
+        let row_size = separator_overhead
+            + first_column_size
+            + email_columns * email_column_size
+            + domain_columns * domain_column_size
+            + reserved_for_fixed
+            + regular_bytes;
 
-    
+        Ok(ColumnSizes {
+            first: first_column_size,
+            email: email_column_size,
+            domain: domain_column_size,
+            regular: regular_column_size,
+            regular_columns: remaining_columns,
+            remainder: remaining_for_regular % remaining_columns.max(1),
+            row_size,
+        })
+    }
 
-    fn create_columns(column_sizes: Vec<usize>, email_columns: usize, domain_columns: usize) -> Result<Vec<ColumnConfig>> {
-        let mut columns = Vec::new();
+    fn create_columns(
+        column_sizes: Vec<usize>,
+        email_columns: usize,
+        domain_columns: usize,
+        fixed_columns: &[FixedColumn],
+        headers: &HeaderStrategy,
+    ) -> PlanResult<Vec<ColumnConfig>> {
         let mut email_count = 0;
         let mut domain_count = 0;
-        let mut used_names = std::collections::HashSet::new();
+        let mut fixed_count = 0;
+        let mut bases = Vec::new();
+        let mut data_types = Vec::new();
         
         for (i, size) in column_sizes.iter().enumerate() {
-            let (name, data_type) = if i == 0 {
-                (Self::generate_unique_header_name("id", *size, &mut used_names), DataType::UniqueId)
+            let (base, data_type) = if i == 0 {
+                ("id".to_string(), DataType::UniqueId)
             } else if email_count < email_columns {
                 email_count += 1;
-                (Self::generate_unique_header_name(&format!("email_{}", email_count), *size, &mut used_names), DataType::Email)
+                (format!("email_{}", email_count), DataType::Email)
             } else if domain_count < domain_columns {
                 domain_count += 1;
-                (Self::generate_unique_header_name(&format!("domain_{}", domain_count), *size, &mut used_names), DataType::Domain)
+                (format!("domain_{}", domain_count), DataType::Domain)
+            } else if fixed_count < fixed_columns.len() {
+                let column = &fixed_columns[fixed_count];
+                fixed_count += 1;
+                (column.name.clone(), column.data_type.clone())
             } else {
-                (Self::generate_unique_header_name(&format!("col{}", i), *size, &mut used_names), DataType::String)
+                (format!("col{}", i), DataType::String)
             };
 
-            columns.push(ColumnConfig {
-                name,
-                size_bytes: *size,
-                data_type,
-            });
-        }
-
-        Ok(columns)
-    }
-
-fn generate_unique_header_name(base: &str, target_size: usize, used_names: &mut std::collections::HashSet<String>) -> String {
-        let mut candidate = Self::generate_header_name(base, target_size);
-        let mut suffix = 1;
-        
-        // Keep adding suffix until we find a unique name
-        while used_names.contains(&candidate) {
-            let suffix_str = suffix.to_string();
-            
-            if target_size == 1 {
-                // For single character columns, use a much larger set of options
-                let chars = "abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ0123456789!@#$%^&*()_+-=[]{}|;:,.<>?";
-                let char_idx = (suffix - 1) % chars.len();
-                candidate = chars.chars().nth(char_idx).unwrap().to_string();
-            } else if target_size == 2 {
-                // For two character columns, use combinations with larger charset
-                let chars = "abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ0123456789";
-                let total_combinations = chars.len() * chars.len();
-                let combo_idx = (suffix - 1) % total_combinations;
-                let first_idx = combo_idx / chars.len();
-                let second_idx = combo_idx % chars.len();
-                candidate = format!("{}{}", 
-                    chars.chars().nth(first_idx).unwrap(),
-                    chars.chars().nth(second_idx).unwrap()
-                );
-            } else {
-                let base_len = base.len();
-                if base_len + suffix_str.len() <= target_size {
-                    // If we can fit the suffix, append it
-                    candidate = format!("{}{}", base.chars().take(target_size - suffix_str.len()).collect::<String>(), suffix_str);
-                } else {
-                    // Truncate base to make room for suffix
-                    let available_for_base = target_size.saturating_sub(suffix_str.len());
-                    candidate = format!("{}{}", base.chars().take(available_for_base).collect::<String>(), suffix_str);
-                }
-            }
-            
-            suffix += 1;
-            
-            // Safety check to prevent infinite loops - increase threshold for very small columns
-            let max_attempts = if target_size <= 2 { 100000 } else { 10000 };
-            if suffix > max_attempts {
-                // For very small columns, fall back to a different strategy
-                if target_size == 1 {
-                    // Use sequential numbers as strings, take last character
-                    candidate = format!("{}", suffix).chars().last().unwrap().to_string();
-                } else if target_size == 2 {
-                    // Use sequential numbers, take last 2 characters
-                    candidate = format!("{:02}", suffix).chars().take(2).collect();
-                } else {
-                    panic!("Could not generate unique header name for base: {}, target_size: {}", base, target_size);
-                }
-                
-                // If still not unique, continue with incremented suffix
-                if used_names.contains(&candidate) {
-                    suffix += 1;
-                    continue;
-                } else {
-                    break;
-                }
-            }
+            bases.push((base, *size));
+            data_types.push(data_type);
         }
-        
-        used_names.insert(candidate.clone());
-        candidate
-    }
 
-    fn generate_header_name(base: &str, target_size: usize) -> String {
-        // For very small columns, use unique single characters
-        if target_size <= 2 {
-            match base {
-                "id" => "id".chars().take(target_size).collect(),
-                _ => {
-                    // Extract column number for unique single character
-                    if let Some(num_str) = base.strip_prefix("col") {
-                        let num = num_str.parse::<usize>().unwrap_or(0);
-                        let unique_char = match num % 26 {
-                            0 => 'a', 1 => 'b', 2 => 'c', 3 => 'd', 4 => 'e',
-                            5 => 'f', 6 => 'g', 7 => 'h', 8 => 'i', 9 => 'j',
-                            10 => 'k', 11 => 'l', 12 => 'm', 13 => 'n', 14 => 'o',
-                            15 => 'p', 16 => 'q', 17 => 'r', 18 => 's', 19 => 't',
-                            20 => 'u', 21 => 'v', 22 => 'w', 23 => 'x', 24 => 'y',
-                            _ => 'z'
-                        };
-                        unique_char.to_string().chars().take(target_size).collect()
-                    } else {
-                        base.chars().take(target_size).collect()
-                    }
-                }
-            }
-        } else {
-            // Normal case: ensure minimum size for uniqueness
-            let min_size = std::cmp::max(target_size, 4);
-            
-            if base.len() >= min_size {
-                base.chars().take(min_size).collect()
-            } else {
-                let padding = min_size - base.len();
-                // Use different padding characters to ensure uniqueness
-                let padding_char = match base.chars().next() {
-                    Some('i') => 'x',  // id -> xxx
-                    Some('c') => {
-                        // Extract column number for unique padding
-                        if let Some(num_str) = base.strip_prefix("col") {
-                            let num = num_str.parse::<usize>().unwrap_or(0);
-                            match num % 10 {
-                                0 => 'a', 1 => 'b', 2 => 'c', 3 => 'd', 4 => 'e',
-                                5 => 'f', 6 => 'g', 7 => 'h', 8 => 'i', 9 => 'j',
-                                _ => 'x'
-                            }
-                        } else {
-                            'x'
-                        }
-                    }
-                    _ => 'x',
-                };
-                format!("{}{}", base, padding_char.to_string().repeat(padding))
-            }
-        }
+        let names = headers.name_columns(&bases)?;
+        Ok(names
+            .into_iter()
+            .zip(column_sizes)
+            .zip(data_types)
+            .map(|((name, size_bytes), data_type)| ColumnConfig {
+                name,
+                size_bytes,
+                data_type,
+                null_rate: 0.0,
+            })
+            .collect())
     }
 
     fn calculate_header_size(columns: &[ColumnConfig]) -> usize {
@@ -0,0 +1,233 @@
+use crate::utils::dates;
+use anyhow::{anyhow, bail, Result};
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use std::f64::consts::TAU;
+use std::str::FromStr;
+
+/// Output format of a timestamp column. Every format has a fixed width.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TimestampFormat {
+    /// `2024-01-01T00:00:00Z`
+    Iso8601,
+    /// `2024-01-01T00:00:00.000Z`
+    Iso8601Millis,
+    /// `2024-01-01 00:00:00`
+    DateTime,
+    /// Unix seconds, zero-padded to 10 digits
+    UnixSeconds,
+    /// Unix milliseconds, zero-padded to 13 digits
+    UnixMillis,
+}
+
+impl TimestampFormat {
+    pub fn width(&self) -> usize {
+        match self {
+            TimestampFormat::Iso8601 => 20,
+            TimestampFormat::Iso8601Millis => 24,
+            TimestampFormat::DateTime => 19,
+            TimestampFormat::UnixSeconds => 10,
+            TimestampFormat::UnixMillis => 13,
+        }
+    }
+
+    /// Formats milliseconds since the epoch. Times outside years 0-9999 (or before the epoch
+    /// for Unix formats) are clamped so the width stays exact.
+    pub fn format(&self, millis: i64) -> String {
+        match self {
+            TimestampFormat::UnixSeconds => format!("{:010}", millis.clamp(0, 9_999_999_999_999) / 1000),
+            TimestampFormat::UnixMillis => format!("{:013}", millis.clamp(0, 9_999_999_999_999)),
+            _ => {
+                let (days, (hour, minute, second, milli)) = dates::split_millis(millis.clamp(dates::MIN_TIMESTAMP_MS, dates::MAX_TIMESTAMP_MS));
+                let date = dates::format_iso_date(days);
+                match self {
+                    TimestampFormat::Iso8601 => format!("{}T{:02}:{:02}:{:02}Z", date, hour, minute, second),
+                    TimestampFormat::Iso8601Millis => {
+                        format!("{}T{:02}:{:02}:{:02}.{:03}Z", date, hour, minute, second, milli)
+                    }
+                    _ => format!("{} {:02}:{:02}:{:02}", date, hour, minute, second),
+                }
+            }
+        }
+    }
+}
+
+impl FromStr for TimestampFormat {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_lowercase().as_str() {
+            "iso" | "iso8601" => Ok(TimestampFormat::Iso8601),
+            "iso-millis" | "iso8601-millis" => Ok(TimestampFormat::Iso8601Millis),
+            "datetime" => Ok(TimestampFormat::DateTime),
+            "unix" => Ok(TimestampFormat::UnixSeconds),
+            "unix-millis" => Ok(TimestampFormat::UnixMillis),
+            _ => Err(anyhow!(
+                "Unknown timestamp format '{}'. Use iso, iso-millis, datetime, unix or unix-millis",
+                s
+            )),
+        }
+    }
+}
+
+/// An ordered timestamp column: each row advances by `interval_ms` from `start_ms`.
+///
+/// Jitter shifts each emitted time by up to `jitter_ms` either way; keep it below half the
+/// interval for timestamps to stay monotonic. After a row, a gap of `gap_ms` follows with
+/// probability `gap_rate`, and with probability `out_of_order_rate` a row is emitted one to
+/// three intervals in the past.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TimestampSpec {
+    pub start_ms: i64,
+    pub interval_ms: i64,
+    pub jitter_ms: i64,
+    pub gap_rate: f64,
+    pub gap_ms: i64,
+    pub out_of_order_rate: f64,
+    pub format: TimestampFormat,
+}
+
+impl TimestampSpec {
+    /// Emits the timestamp for a row at `nominal_ms`, with its jitter and disorder.
+    pub(crate) fn emit<R: Rng + ?Sized>(&self, nominal_ms: i64, rng: &mut R) -> String {
+        let mut millis = nominal_ms;
+        if self.jitter_ms > 0 {
+            millis = millis.saturating_add(rng.gen_range(-self.jitter_ms..=self.jitter_ms));
+        }
+        if self.out_of_order_rate > 0.0 && rng.gen_bool(self.out_of_order_rate.min(1.0)) {
+            millis = millis.saturating_sub(self.interval_ms.saturating_mul(rng.gen_range(1..=3)));
+        }
+        self.format.format(millis)
+    }
+
+    /// Nominal time of the row after one at `nominal_ms`, with a gap if one follows it.
+    pub(crate) fn advance<R: Rng + ?Sized>(&self, nominal_ms: i64, rng: &mut R) -> i64 {
+        let mut next = nominal_ms.saturating_add(self.interval_ms);
+        if self.gap_rate > 0.0 && rng.gen_bool(self.gap_rate.min(1.0)) {
+            next = next.saturating_add(self.gap_ms);
+        }
+        next
+    }
+
+    /// Nominal time of row `row` if no gaps are possible, so it needn't be walked up to.
+    /// Times past the range of an i64 saturate, which formatting clamps anyway.
+    pub(crate) fn nominal_without_gaps(&self, row: u64) -> Option<i64> {
+        let offset = self.interval_ms.saturating_mul(i64::try_from(row).unwrap_or(i64::MAX));
+        (self.gap_rate <= 0.0).then(|| self.start_ms.saturating_add(offset))
+    }
+}
+
+/// How a value column evolves from one row to the next.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum SeriesKind {
+    /// Starts at `start` and moves by up to `step` either way each row
+    RandomWalk { start: f64, step: f64 },
+    /// `base + amplitude * sin(2π row / period)`, plus up to `noise` either way
+    Seasonal { base: f64, amplitude: f64, period: f64, noise: f64 },
+    /// `start + slope * row`, plus up to `noise` either way
+    Trend { start: f64, slope: f64, noise: f64 },
+}
+
+impl SeriesKind {
+    /// Value for row `row`. A random walk's value is `walked`, the sum of its steps up to the
+    /// row; the other kinds draw their noise from `rng`.
+    pub(crate) fn value<R: Rng + ?Sized>(&self, row: u64, walked: f64, rng: &mut R) -> f64 {
+        match *self {
+            SeriesKind::RandomWalk { .. } => walked,
+            SeriesKind::Seasonal { base, amplitude, period, noise } => {
+                base + amplitude * (TAU * row as f64 / period).sin() + spread(noise, rng)
+            }
+            SeriesKind::Trend { start, slope, noise } => start + slope * row as f64 + spread(noise, rng),
+        }
+    }
+
+    /// A random walk's move into a row from the one before; 0 for the other kinds.
+    pub(crate) fn step<R: Rng + ?Sized>(&self, rng: &mut R) -> f64 {
+        match *self {
+            SeriesKind::RandomWalk { step, .. } => spread(step, rng),
+            _ => 0.0,
+        }
+    }
+
+    /// The value a random walk starts from.
+    pub(crate) fn start(&self) -> f64 {
+        match *self {
+            SeriesKind::RandomWalk { start, .. } => start,
+            _ => 0.0,
+        }
+    }
+}
+
+// Up to `amount` either way
+fn spread<R: Rng + ?Sized>(amount: f64, rng: &mut R) -> f64 {
+    if amount > 0.0 { rng.gen_range(-amount..=amount) } else { 0.0 }
+}
+
+/// Parses `KIND` or `KIND:param=value,...`, e.g. `seasonal:period=60,amplitude=5`.
+impl FromStr for SeriesKind {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let (kind, params) = s.split_once(':').unwrap_or((s, ""));
+        let mut kind = match kind.to_lowercase().as_str() {
+            "random-walk" | "walk" => SeriesKind::RandomWalk { start: 100.0, step: 1.0 },
+            "seasonal" => SeriesKind::Seasonal { base: 100.0, amplitude: 20.0, period: 24.0, noise: 2.0 },
+            "trend" => SeriesKind::Trend { start: 100.0, slope: 0.1, noise: 2.0 },
+            _ => bail!("Unknown series kind '{}'. Use random-walk, seasonal or trend", kind),
+        };
+
+        for param in params.split(',').filter(|p| !p.is_empty()) {
+            let (key, value) = param
+                .split_once('=')
+                .ok_or_else(|| anyhow!("Series parameter '{}' must be key=value", param))?;
+            let value: f64 = value.parse()?;
+            let field = match (&mut kind, key) {
+                (SeriesKind::RandomWalk { start, .. }, "start") => start,
+                (SeriesKind::RandomWalk { step, .. }, "step") => step,
+                (SeriesKind::Seasonal { base, .. }, "base") => base,
+                (SeriesKind::Seasonal { amplitude, .. }, "amplitude") => amplitude,
+                (SeriesKind::Seasonal { period, .. }, "period") => period,
+                (SeriesKind::Seasonal { noise, .. }, "noise") => noise,
+                (SeriesKind::Trend { start, .. }, "start") => start,
+                (SeriesKind::Trend { slope, .. }, "slope") => slope,
+                (SeriesKind::Trend { noise, .. }, "noise") => noise,
+                _ => bail!("Unknown parameter '{}' for series '{}'", key, s),
+            };
+            *field = value;
+        }
+
+        if let SeriesKind::Seasonal { period, .. } = kind
+            && period <= 0.0
+        {
+            bail!("Seasonal period must be positive");
+        }
+        Ok(kind)
+    }
+}
+
+/// A value column of a time series, written as a zero-padded decimal of its column width.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SeriesSpec {
+    pub kind: SeriesKind,
+    pub precision: usize,
+}
+
+impl SeriesSpec {
+    /// Formats `value` in exactly `width` bytes, clamping it to the representable range.
+    pub fn format(&self, value: f64, width: usize) -> String {
+        let fraction = if self.precision > 0 { self.precision + 1 } else { 0 };
+        let int_digits = width.saturating_sub(fraction).max(1) as i32;
+        let step = 10f64.powi(-(self.precision as i32));
+        let max = 10f64.powi(int_digits) - step;
+        let min = -(10f64.powi(int_digits - 1) - step);
+
+        let scale = 10f64.powi(self.precision as i32);
+        let value = ((value * scale).round() / scale).clamp(min, max);
+        format!("{:0width$.precision$}", value, width = width, precision = self.precision)
+    }
+
+    /// Smallest width that can hold a sign, one integer digit and the fraction.
+    pub fn min_width(&self) -> usize {
+        2 + if self.precision > 0 { self.precision + 1 } else { 0 }
+    }
+}
@@ -1,10 +1,40 @@
 use serde::{Deserialize, Serialize};
+use std::fs;
+use crate::data::headers::{self, HeaderStrategy};
+use crate::data::pattern::Pattern;
+use crate::data::timeseries::{SeriesSpec, TimestampFormat, TimestampSpec};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ColumnConfig {
     pub name: String,
     pub size_bytes: usize,
     pub data_type: DataType,
+    /// Fraction of values written as empty fields
+    #[serde(default)]
+    pub null_rate: f64,
+}
+
+impl ColumnConfig {
+    /// Widest value the column's generator can produce: its width, unless the data type
+    /// needs more.
+    pub fn max_width(&self) -> usize {
+        let needed = match &self.data_type {
+            // Cut or padded to the column width
+            DataType::UniqueId
+            | DataType::String
+            | DataType::Number
+            | DataType::Email
+            | DataType::Name
+            | DataType::Domain
+            | DataType::Series(_) => 0,
+            DataType::Pattern(pattern) => pattern.max_len(),
+            // At least one integer digit
+            DataType::Decimal { scale: 0 } => 1,
+            DataType::Decimal { scale } => scale + 2,
+            data_type => data_type.natural_width(),
+        };
+        self.size_bytes.max(needed).max(1)
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -15,9 +45,85 @@ pub enum DataType {
     Email,
     Name,
     Domain,
+    Pattern(Pattern),
+    Decimal { scale: usize },
+    Date,
+    Enum(Vec<String>),
+    Timestamp(TimestampSpec),
+    Series(SeriesSpec),
+    /// Integers drawn uniformly from `min..=max`
+    Range { min: i64, max: i64 },
 }
 
+impl DataType {
+    /// Typical width of a value when nothing forces one. `UniqueId` is 0: its width follows
+    /// from the row count.
+    pub fn natural_width(&self) -> usize {
+        match self {
+            DataType::UniqueId => 0,
+            DataType::String | DataType::Name => 12,
+            DataType::Number => 8,
+            DataType::Email => 20,
+            DataType::Domain => 15,
+            DataType::Pattern(pattern) => pattern.planned_len(),
+            DataType::Decimal { scale } => scale + 6,
+            DataType::Date => 10,
+            DataType::Enum(values) => values.iter().map(String::len).max().unwrap_or(0),
+            DataType::Timestamp(spec) => spec.format.width(),
+            DataType::Series(spec) => spec.min_width().max(10),
+            DataType::Range { min, max } => min.to_string().len().max(max.to_string().len()),
+        }
+    }
+
+    /// Whether the values are numbers, including Unix timestamps, rather than text.
+    pub fn is_numeric(&self) -> bool {
+        match self {
+            DataType::UniqueId | DataType::Number | DataType::Range { .. } | DataType::Decimal { .. } | DataType::Series(_) => true,
+            DataType::Timestamp(spec) => matches!(spec.format, TimestampFormat::UnixSeconds | TimestampFormat::UnixMillis),
+            _ => false,
+        }
+    }
+}
+
+/// A column whose width is dictated by its data type rather than by the size planner.
 #[derive(Debug, Clone)]
+pub struct FixedColumn {
+    /// Base header name; shortened or suffixed like any other header if needed
+    pub name: String,
+    pub data_type: DataType,
+    pub size_bytes: usize,
+}
+
+impl FixedColumn {
+    /// The `index`th pattern column, reserved the pattern's planned width.
+    pub fn pattern(index: usize, pattern: &Pattern) -> Self {
+        Self {
+            name: format!("pattern_{}", index),
+            size_bytes: pattern.planned_len(),
+            data_type: DataType::Pattern(pattern.clone()),
+        }
+    }
+
+    /// An ordered timestamp column, exactly as wide as its format.
+    pub fn timestamp(spec: TimestampSpec) -> Self {
+        Self {
+            name: "timestamp".to_string(),
+            size_bytes: spec.format.width(),
+            data_type: DataType::Timestamp(spec),
+        }
+    }
+
+    /// The `index`th value column of a time series.
+    pub fn series(index: usize, spec: SeriesSpec, size_bytes: usize) -> Self {
+        Self {
+            name: format!("value_{}", index),
+            size_bytes: size_bytes.max(spec.min_width()),
+            data_type: DataType::Series(spec),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CsvSchema {
     pub columns: Vec<ColumnConfig>,
     pub target_row_size: usize,
@@ -31,4 +137,136 @@ impl CsvSchema {
             .map(|c| c.name.len())
             .sum::<usize>() + (self.columns.len() - 1)
     }
-}
\ No newline at end of file
+}
+
+/// Size-independent description of one column, as stored in a schema file.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ColumnSpec {
+    pub name: String,
+    pub data_type: DataType,
+    pub min_len: usize,
+    pub max_len: usize,
+    /// Mean byte length of the non-null values
+    pub avg_len: f64,
+    pub null_rate: f64,
+    pub unique: bool,
+}
+
+impl ColumnSpec {
+    /// A column of `data_type` at its natural width, with no nulls.
+    pub fn new(name: impl Into<String>, data_type: DataType) -> Self {
+        let width = data_type.natural_width();
+        Self {
+            name: name.into(),
+            unique: matches!(data_type, DataType::UniqueId),
+            data_type,
+            min_len: width,
+            max_len: width,
+            avg_len: width as f64,
+            null_rate: 0.0,
+        }
+    }
+
+    /// Sets the column's width. Free-text columns still stretch to hit a target size.
+    pub fn with_width(mut self, width: usize) -> Self {
+        self.min_len = width;
+        self.max_len = width;
+        self.avg_len = width as f64;
+        self
+    }
+
+    pub fn with_null_rate(mut self, null_rate: f64) -> Self {
+        self.null_rate = null_rate;
+        self
+    }
+}
+
+/// A schema file: the shape of a CSV that `SchemaBuilder` can scale to any size and row count.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SchemaSpec {
+    pub columns: Vec<ColumnSpec>,
+    /// Number of data rows the spec was inferred from
+    pub sample_rows: usize,
+}
+
+impl SchemaSpec {
+    /// The size-independent shape of a planned schema, each column at its planned width, so
+    /// it can be edited and scaled to other sizes.
+    pub fn from_schema(schema: &CsvSchema) -> Self {
+        let columns = schema
+            .columns
+            .iter()
+            .map(|column| {
+                let mut spec = ColumnSpec::new(column.name.clone(), column.data_type.clone())
+                    .with_width(column.size_bytes.max(1))
+                    .with_null_rate(column.null_rate);
+                spec.max_len = column.max_width();
+                spec
+            })
+            .collect();
+        Self { columns, sample_rows: 0 }
+    }
+
+    /// Mistakes that would make the spec fail to plan or generate nonsense: no columns,
+    /// invalid or repeated names, null rates outside 0..=1, lengths out of order, empty enums
+    /// and inverted ranges. Empty when the spec is fine.
+    pub fn validate(&self) -> Vec<String> {
+        let mut problems = Vec::new();
+        if self.columns.is_empty() {
+            problems.push("no columns".to_string());
+        }
+        for (i, column) in self.columns.iter().enumerate() {
+            let label = format!("column {} ({})", i + 1, column.name);
+            if let Err(e) = headers::validate_name(&column.name) {
+                problems.push(format!("{}: {}", label, e));
+            } else if self.columns[..i].iter().any(|c| c.name == column.name) {
+                problems.push(format!("{}: name is repeated", label));
+            }
+            if !(0.0..=1.0).contains(&column.null_rate) {
+                problems.push(format!("{}: null_rate {} is outside 0 to 1", label, column.null_rate));
+            }
+            if column.min_len > column.max_len {
+                problems.push(format!("{}: min_len {} exceeds max_len {}", label, column.min_len, column.max_len));
+            } else if !(column.avg_len >= column.min_len as f64 && column.avg_len <= column.max_len as f64) {
+                problems.push(format!(
+                    "{}: avg_len {} is outside min_len {} to max_len {}",
+                    label, column.avg_len, column.min_len, column.max_len
+                ));
+            }
+            match &column.data_type {
+                DataType::Enum(values) if values.is_empty() => problems.push(format!("{}: enum has no values", label)),
+                DataType::Range { min, max } if min > max => {
+                    problems.push(format!("{}: range minimum {} exceeds maximum {}", label, min, max))
+                }
+                _ => {}
+            }
+        }
+        problems
+    }
+
+    pub fn load_from_file(path: &str) -> anyhow::Result<Self> {
+        let content = fs::read_to_string(path)?;
+        let spec: SchemaSpec = serde_json::from_str(&content)?;
+        Ok(spec)
+    }
+
+    pub fn save_to_file(&self, path: &str) -> anyhow::Result<()> {
+        let content = serde_json::to_string_pretty(self)?;
+        fs::write(path, content)?;
+        Ok(())
+    }
+
+    /// Renames the columns with `headers`. A name may be as long as the original name or the
+    /// column's mean value length, whichever is larger.
+    pub fn rename_columns(&mut self, headers: &HeaderStrategy) -> anyhow::Result<()> {
+        let bases: Vec<(String, usize)> = self
+            .columns
+            .iter()
+            .map(|c| (c.name.clone(), c.name.len().max(c.avg_len.round() as usize)))
+            .collect();
+        for (column, name) in self.columns.iter_mut().zip(headers.name_columns(&bases)?) {
+            column.name = name;
+        }
+        Ok(())
+    }
+}
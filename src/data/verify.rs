@@ -0,0 +1,325 @@
+use crate::data::inference::{decimal_scale, is_domain, is_email};
+use crate::data::timeseries::TimestampFormat;
+use crate::data::types::{ColumnSpec, DataType, SchemaSpec};
+use crate::utils::dates;
+use anyhow::Result;
+use csv::ReaderBuilder;
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::fmt;
+use std::fs::File;
+use std::io::Read;
+
+/// Violations kept in a report unless set otherwise; the rest are only counted.
+pub const DEFAULT_MAX_VIOLATIONS: usize = 50;
+/// Allowed relative difference between the file size and `--size`.
+pub const DEFAULT_SIZE_TOLERANCE: f64 = 0.02;
+// Null rates may differ from the schema's by this much, or by four standard deviations
+const MIN_NULL_RATE_TOLERANCE: f64 = 0.01;
+
+/// One way a file fails its schema.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Violation {
+    /// 1-based line in the file (the header is line 1); `None` for whole-file checks
+    pub line: Option<u64>,
+    pub column: Option<String>,
+    pub message: String,
+}
+
+impl fmt::Display for Violation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if let Some(line) = self.line {
+            write!(f, "line {}: ", line)?;
+        }
+        if let Some(column) = &self.column {
+            write!(f, "column {}: ", column)?;
+        }
+        write!(f, "{}", self.message)
+    }
+}
+
+/// The outcome of verifying a file.
+#[derive(Debug, Clone)]
+pub struct Report {
+    /// Data rows read
+    pub rows: usize,
+    pub bytes: u64,
+    /// The first violations found, up to the verifier's limit
+    pub violations: Vec<Violation>,
+    /// All violations found, including those not kept
+    pub total_violations: usize,
+}
+
+impl Report {
+    pub fn passed(&self) -> bool {
+        self.total_violations == 0
+    }
+}
+
+/// Checks a CSV file against a schema file, streaming it row by row.
+///
+/// Header names and the column count of each row must match the schema. Each non-empty value
+/// must fit its column's type: digits for IDs and numbers, the scale for decimals, valid
+/// dates, timestamps in the column's format, emails and domains, an enum's values, a range's
+/// bounds or a pattern. Columns other than free text and IDs, whose widths scale with the
+/// target size and row count, must also be no wider than the schema's widest value. Unique
+/// ID columns must ascend, columns without nulls must not be empty, and the null rate of the
+/// others must be close to the schema's. The row count and size are checked when expected
+/// values are given.
+///
+/// Memory doesn't grow with the file, unless [`with_unique_limit`](Verifier::with_unique_limit)
+/// asks for other unique columns to be checked too.
+pub struct Verifier {
+    spec: SchemaSpec,
+    rows: Option<usize>,
+    size: Option<usize>,
+    size_tolerance: f64,
+    max_violations: usize,
+    unique_limit: usize,
+}
+
+impl Verifier {
+    pub fn new(spec: SchemaSpec) -> Self {
+        Self {
+            spec,
+            rows: None,
+            size: None,
+            size_tolerance: DEFAULT_SIZE_TOLERANCE,
+            max_violations: DEFAULT_MAX_VIOLATIONS,
+            unique_limit: 0,
+        }
+    }
+
+    /// Expects exactly this many data rows.
+    pub fn with_rows(mut self, rows: usize) -> Self {
+        self.rows = Some(rows);
+        self
+    }
+
+    /// Expects the file to be this many bytes, within the size tolerance.
+    pub fn with_size(mut self, size: usize) -> Self {
+        self.size = Some(size);
+        self
+    }
+
+    /// Allowed relative size difference, e.g. 0.02 for 2%.
+    pub fn with_size_tolerance(mut self, tolerance: f64) -> Self {
+        self.size_tolerance = tolerance;
+        self
+    }
+
+    pub fn with_max_violations(mut self, max_violations: usize) -> Self {
+        self.max_violations = max_violations;
+        self
+    }
+
+    /// Also checks that columns the schema marks unique, other than IDs, don't repeat a value,
+    /// remembering up to `limit` values per column. A column with more fails the check.
+    pub fn with_unique_limit(mut self, limit: usize) -> Self {
+        self.unique_limit = limit;
+        self
+    }
+
+    pub fn verify_file(&self, path: &str) -> Result<Report> {
+        self.verify(File::open(path)?)
+    }
+
+    pub fn verify<R: Read>(&self, reader: R) -> Result<Report> {
+        let mut reader = ReaderBuilder::new().flexible(true).from_reader(reader);
+        let mut report = Report { rows: 0, bytes: 0, violations: Vec::new(), total_violations: 0 };
+        let columns = &self.spec.columns;
+
+        let headers = reader.headers()?.clone();
+        if headers.len() != columns.len() {
+            let message = format!("{} columns in the header, expected {}", headers.len(), columns.len());
+            self.record(&mut report, Some(1), None, message);
+        }
+        for (i, name) in headers.iter().enumerate() {
+            if let Some(column) = columns.get(i)
+                && column.name != name
+            {
+                let message = format!("header {} is '{}', expected '{}'", i + 1, name, column.name);
+                self.record(&mut report, Some(1), None, message);
+            }
+            if headers.iter().take(i).any(|earlier| earlier == name) {
+                self.record(&mut report, Some(1), None, format!("header '{}' is repeated", name));
+            }
+        }
+
+        let mut nulls = vec![0usize; columns.len()];
+        // IDs are generated in order, so the last one and its line are all an ID column needs
+        let mut last_ids: Vec<Option<(String, u64)>> = vec![None; columns.len()];
+        // Inference marks any column unique whose sample had no repeats, but only IDs are
+        // generated unique, so other columns are checked only on request
+        let mut seen: Vec<Option<HashMap<String, u64>>> = columns
+            .iter()
+            .map(|c| (c.unique && self.unique_limit > 0 && !matches!(c.data_type, DataType::UniqueId)).then(HashMap::new))
+            .collect();
+        for record in reader.records() {
+            let record = record?;
+            let line = record.position().map(|p| p.line());
+            report.rows += 1;
+            if record.len() != columns.len() {
+                self.record(&mut report, line, None, format!("{} fields, expected {}", record.len(), columns.len()));
+                continue;
+            }
+
+            for (i, (value, column)) in record.iter().zip(columns).enumerate() {
+                if value.is_empty() {
+                    nulls[i] += 1;
+                    if column.null_rate <= 0.0 {
+                        self.record(&mut report, line, Some(column), "empty value in a column without nulls".to_string());
+                    }
+                    continue;
+                }
+                if let Some(problem) = check_value(value, column) {
+                    self.record(&mut report, line, Some(column), problem);
+                }
+                if column.unique && matches!(column.data_type, DataType::UniqueId) && digits(value) {
+                    if let Some((last, last_line)) = &last_ids[i]
+                        && compare_digits(value, last) != Ordering::Greater
+                    {
+                        let message = format!("'{}' is not above the ID '{}' on line {}", value, last, last_line);
+                        self.record(&mut report, line, Some(column), message);
+                    }
+                    last_ids[i] = Some((value.to_string(), line.unwrap_or(0)));
+                }
+                if let Some(values) = &mut seen[i] {
+                    match values.get(value) {
+                        Some(first) => {
+                            let message = format!("'{}' repeats the value on line {}", value, first);
+                            self.record(&mut report, line, Some(column), message);
+                        }
+                        None if values.len() >= self.unique_limit => {
+                            let message = format!(
+                                "more than {} distinct values to check for repeats; raise the unique limit",
+                                self.unique_limit
+                            );
+                            self.record(&mut report, line, Some(column), message);
+                            seen[i] = None;
+                        }
+                        None => {
+                            values.insert(value.to_string(), line.unwrap_or(0));
+                        }
+                    }
+                }
+            }
+        }
+        report.bytes = reader.position().byte();
+
+        if report.rows > 0 {
+            for (column, nulls) in columns.iter().zip(nulls) {
+                let rate = nulls as f64 / report.rows as f64;
+                let expected = column.null_rate.clamp(0.0, 1.0);
+                let deviation = (expected * (1.0 - expected) / report.rows as f64).sqrt();
+                let tolerance = (4.0 * deviation).max(MIN_NULL_RATE_TOLERANCE);
+                if expected > 0.0 && (rate - expected).abs() > tolerance {
+                    let message = format!("null rate {:.1}%, expected {:.1}%", rate * 100.0, expected * 100.0);
+                    self.record(&mut report, None, Some(column), message);
+                }
+            }
+        }
+        if let Some(rows) = self.rows
+            && rows != report.rows
+        {
+            let message = format!("{} data rows, expected {}", report.rows, rows);
+            self.record(&mut report, None, None, message);
+        }
+        if let Some(size) = self.size {
+            let error = (report.bytes as f64 - size as f64).abs() / size.max(1) as f64;
+            if error > self.size_tolerance {
+                let message = format!(
+                    "{} bytes is {:.1}% off the expected {} bytes (tolerance {:.1}%)",
+                    report.bytes,
+                    error * 100.0,
+                    size,
+                    self.size_tolerance * 100.0
+                );
+                self.record(&mut report, None, None, message);
+            }
+        }
+
+        Ok(report)
+    }
+
+    fn record(&self, report: &mut Report, line: Option<u64>, column: Option<&ColumnSpec>, message: String) {
+        report.total_violations += 1;
+        if report.violations.len() < self.max_violations {
+            report.violations.push(Violation { line, column: column.map(|c| c.name.clone()), message });
+        }
+    }
+}
+
+fn digits(value: &str) -> bool {
+    !value.is_empty() && value.bytes().all(|b| b.is_ascii_digit())
+}
+
+// Orders digit strings by value, however long and zero-padded
+fn compare_digits(a: &str, b: &str) -> Ordering {
+    let a = a.trim_start_matches('0');
+    let b = b.trim_start_matches('0');
+    a.len().cmp(&b.len()).then_with(|| a.cmp(b))
+}
+
+/// What is wrong with a non-empty value, if anything.
+fn check_value(value: &str, column: &ColumnSpec) -> Option<String> {
+    let valid = match &column.data_type {
+        DataType::String => true,
+        DataType::UniqueId => digits(value),
+        DataType::Number => digits(value.strip_prefix('-').unwrap_or(value)),
+        DataType::Decimal { scale } => decimal_scale(value).is_some_and(|s| s <= *scale),
+        DataType::Series(spec) => decimal_scale(value) == Some(spec.precision),
+        DataType::Range { min, max } => value.parse::<i64>().is_ok_and(|n| (*min..=*max).contains(&n)),
+        DataType::Date => dates::parse_iso_date(value).is_some(),
+        DataType::Timestamp(spec) => is_timestamp(value, spec.format),
+        DataType::Email => is_email(value),
+        DataType::Domain => is_domain(value),
+        DataType::Name => value.chars().all(|c| c.is_alphabetic() || " '-.".contains(c)),
+        DataType::Enum(values) => values.iter().any(|v| v == value),
+        DataType::Pattern(pattern) => pattern.matches(value),
+    };
+    if !valid {
+        return Some(format!("'{}' is not a valid {}", value, type_name(&column.data_type)));
+    }
+
+    let scales = matches!(column.data_type, DataType::String | DataType::UniqueId);
+    let max_width = column.max_len.max(column.data_type.natural_width());
+    if !scales && value.len() > max_width {
+        return Some(format!("'{}' is {} bytes, wider than the column's {}", value, value.len(), max_width));
+    }
+    None
+}
+
+fn is_timestamp(value: &str, format: TimestampFormat) -> bool {
+    if value.len() != format.width() || !value.is_ascii() {
+        return false;
+    }
+    match format {
+        TimestampFormat::UnixSeconds | TimestampFormat::UnixMillis => value.bytes().all(|b| b.is_ascii_digit()),
+        TimestampFormat::Iso8601 => value.as_bytes()[10] == b'T' && value.ends_with('Z') && dates::parse_timestamp_ms(value).is_some(),
+        TimestampFormat::Iso8601Millis => {
+            let (seconds, millis) = value.split_at(19);
+            value.as_bytes()[10] == b'T'
+                && millis.len() == 5
+                && millis.starts_with('.')
+                && millis.ends_with('Z')
+                && millis[1..4].bytes().all(|b| b.is_ascii_digit())
+                && dates::parse_timestamp_ms(seconds).is_some()
+        }
+        TimestampFormat::DateTime => value.as_bytes()[10] == b' ' && dates::parse_timestamp_ms(value).is_some(),
+    }
+}
+
+fn type_name(data_type: &DataType) -> String {
+    match data_type {
+        DataType::UniqueId => "ID".to_string(),
+        DataType::Decimal { scale } => format!("decimal with up to {} places", scale),
+        DataType::Series(spec) => format!("number with {} places", spec.precision),
+        DataType::Range { min, max } => format!("integer in {}..={}", min, max),
+        DataType::Date => "YYYY-MM-DD date".to_string(),
+        DataType::Timestamp(spec) => format!("{:?} timestamp", spec.format),
+        DataType::Enum(_) => "value of the enum".to_string(),
+        DataType::Pattern(pattern) => format!("match for /{}/", pattern.source()),
+        data_type => format!("{:?}", data_type).to_lowercase(),
+    }
+}
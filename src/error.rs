@@ -0,0 +1,55 @@
+use crate::data::planner::PlanError;
+use std::io;
+
+/// Errors returned by the [`CsvGen`](crate::CsvGen) library API.
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    /// The size, row and column constraints cannot all be met
+    #[error("impossible constraints: {0}")]
+    ImpossibleConstraints(String),
+    /// A column or schema definition is invalid
+    #[error("invalid schema: {0}")]
+    InvalidSchema(String),
+    /// Reading or writing failed
+    #[error("I/O error: {0}")]
+    Io(#[from] io::Error),
+    /// A record could not be written as CSV
+    #[error("CSV error: {0}")]
+    Csv(csv::Error),
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+impl From<csv::Error> for Error {
+    fn from(error: csv::Error) -> Self {
+        if !error.is_io_error() {
+            return Error::Csv(error);
+        }
+        match error.into_kind() {
+            csv::ErrorKind::Io(error) => Error::Io(error),
+            _ => Error::Io(io::Error::other("CSV I/O error")),
+        }
+    }
+}
+
+impl From<PlanError> for Error {
+    fn from(error: PlanError) -> Self {
+        match error {
+            PlanError::Impossible(message) => Error::ImpossibleConstraints(message),
+            PlanError::InvalidSchema(message) => Error::InvalidSchema(message),
+        }
+    }
+}
+
+impl Error {
+    // Generation errors come back as anyhow errors; keep I/O causes as I/O
+    pub(crate) fn from_generation(error: anyhow::Error) -> Self {
+        match error.downcast::<io::Error>() {
+            Ok(error) => Error::Io(error),
+            Err(error) => match error.downcast::<csv::Error>() {
+                Ok(error) => error.into(),
+                Err(error) => Error::Io(io::Error::other(error.to_string())),
+            },
+        }
+    }
+}
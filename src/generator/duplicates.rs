@@ -0,0 +1,231 @@
+use crate::data::types::{ColumnConfig, DataType};
+use anyhow::{bail, Result};
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+
+// Originals kept around as duplicate sources; bounds memory on very large runs
+const POOL_CAPACITY: usize = 10_000;
+
+/// Rates of duplicate and near-duplicate rows, and where to record which rows they copy.
+#[derive(Debug, Clone)]
+pub struct DuplicateConfig {
+    /// Probability that a row repeats an earlier row exactly
+    pub duplicate_rate: f64,
+    /// Probability that a row repeats an earlier row with small perturbations
+    pub near_duplicate_rate: f64,
+    /// Sidecar CSV mapping each duplicate's `UniqueId` to its original's
+    pub ground_truth_path: String,
+}
+
+impl DuplicateConfig {
+    pub fn validate(&self) -> Result<()> {
+        for (name, rate) in [("duplicate", self.duplicate_rate), ("near-duplicate", self.near_duplicate_rate)] {
+            if !(0.0..=1.0).contains(&rate) {
+                bail!("The {} rate must be between 0 and 1, got {}", name, rate);
+            }
+        }
+        if self.duplicate_rate + self.near_duplicate_rate > 1.0 {
+            bail!("Duplicate and near-duplicate rates must add up to at most 1");
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DuplicateKind {
+    Exact,
+    Near,
+}
+
+impl DuplicateKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DuplicateKind::Exact => "exact",
+            DuplicateKind::Near => "near",
+        }
+    }
+}
+
+/// Turns some freshly generated rows into copies of earlier ones.
+///
+/// Duplicates keep their own `UniqueId` and timestamp columns, so IDs stay unique and time
+/// series stay ordered; every other field comes from the original row. Near-duplicates then
+/// get one or two fields perturbed: typos, swapped name tokens, case changes, whitespace
+/// differences or reformatted emails.
+pub struct DuplicateInjector {
+    config: DuplicateConfig,
+    pool: Vec<Vec<String>>,
+    originals_seen: usize,
+    rng: ChaCha8Rng,
+}
+
+impl DuplicateInjector {
+    pub fn new(config: DuplicateConfig) -> Self {
+        Self {
+            config,
+            pool: Vec::new(),
+            originals_seen: 0,
+            rng: ChaCha8Rng::from_entropy(),
+        }
+    }
+
+    /// Makes the choice of rows and perturbations reproducible.
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.rng = ChaCha8Rng::seed_from_u64(seed);
+        self
+    }
+
+    /// Possibly rewrites `row` as a duplicate, returning the original's ID and the kind.
+    pub fn process(&mut self, columns: &[ColumnConfig], row: &mut [String]) -> Option<(String, DuplicateKind)> {
+        let roll: f64 = self.rng.r#gen();
+        let kind = if self.pool.is_empty() {
+            None
+        } else if roll < self.config.duplicate_rate {
+            Some(DuplicateKind::Exact)
+        } else if roll < self.config.duplicate_rate + self.config.near_duplicate_rate {
+            Some(DuplicateKind::Near)
+        } else {
+            None
+        };
+
+        let Some(kind) = kind else {
+            self.remember(row);
+            return None;
+        };
+
+        let original = &self.pool[self.rng.gen_range(0..self.pool.len())];
+        let id_index = columns.iter().position(|c| matches!(c.data_type, DataType::UniqueId));
+        let original_id = id_index.map(|i| original[i].clone()).unwrap_or_default();
+
+        for (i, column) in columns.iter().enumerate() {
+            if !Self::is_identity(column) {
+                row[i].clone_from(&original[i]);
+            }
+        }
+
+        if kind == DuplicateKind::Near {
+            self.perturb(columns, row);
+        }
+        Some((original_id, kind))
+    }
+
+    fn is_identity(column: &ColumnConfig) -> bool {
+        matches!(column.data_type, DataType::UniqueId | DataType::Timestamp(_))
+    }
+
+    // Reservoir sampling keeps a uniform sample of all originals once the pool is full
+    fn remember(&mut self, row: &[String]) {
+        self.originals_seen += 1;
+        if self.pool.len() < POOL_CAPACITY {
+            self.pool.push(row.to_vec());
+        } else {
+            let slot = self.rng.gen_range(0..self.originals_seen);
+            if slot < POOL_CAPACITY {
+                self.pool[slot] = row.to_vec();
+            }
+        }
+    }
+
+    fn perturb(&mut self, columns: &[ColumnConfig], row: &mut [String]) {
+        let candidates: Vec<usize> = (0..columns.len())
+            .filter(|&i| !Self::is_identity(&columns[i]) && !row[i].is_empty())
+            .collect();
+        if candidates.is_empty() {
+            return;
+        }
+
+        let fields = self.rng.gen_range(1..=2.min(candidates.len()));
+        for i in rand::seq::index::sample(&mut self.rng, candidates.len(), fields) {
+            let i = candidates[i];
+            let perturbed = match columns[i].data_type {
+                DataType::Name => match self.rng.gen_range(0..4) {
+                    0 => swap_tokens(&row[i]),
+                    1 => change_case(&mut self.rng, &row[i]),
+                    2 => add_whitespace(&mut self.rng, &row[i]),
+                    _ => typo(&mut self.rng, &row[i]),
+                },
+                DataType::Email => match self.rng.gen_range(0..2) {
+                    0 => reformat_email(&mut self.rng, &row[i]),
+                    _ => typo(&mut self.rng, &row[i]),
+                },
+                _ => match self.rng.gen_range(0..3) {
+                    0 => change_case(&mut self.rng, &row[i]),
+                    1 => add_whitespace(&mut self.rng, &row[i]),
+                    _ => typo(&mut self.rng, &row[i]),
+                },
+            };
+            // Case changes and token swaps can be no-ops; fall back to a typo
+            row[i] = if perturbed == row[i] { typo(&mut self.rng, &row[i]) } else { perturbed };
+        }
+    }
+}
+
+// Substitutes, transposes, drops or doubles one character; the result always differs
+fn typo(rng: &mut impl Rng, value: &str) -> String {
+    let mut chars: Vec<char> = value.chars().collect();
+    let i = rng.gen_range(0..chars.len());
+    let j = if i + 1 < chars.len() { i + 1 } else { i.saturating_sub(1) };
+    match rng.gen_range(0..4) {
+        0 if chars[i] != chars[j] => chars.swap(i, j),
+        1 if chars.len() > 1 => {
+            chars.remove(i);
+        }
+        2 => {
+            let replacement = loop {
+                let c = if chars[i].is_ascii_digit() {
+                    rng.gen_range(b'0'..=b'9') as char
+                } else {
+                    rng.gen_range(b'a'..=b'z') as char
+                };
+                if c != chars[i] {
+                    break c;
+                }
+            };
+            chars[i] = replacement;
+        }
+        _ => chars.insert(i, chars[i]),
+    }
+    chars.into_iter().collect()
+}
+
+fn swap_tokens(value: &str) -> String {
+    match value.split_once(' ') {
+        Some((first, rest)) => format!("{} {}", rest, first),
+        None => value.to_string(),
+    }
+}
+
+fn change_case(rng: &mut impl Rng, value: &str) -> String {
+    match rng.gen_range(0..3) {
+        0 => value.to_uppercase(),
+        1 => value.to_lowercase(),
+        _ => value
+            .chars()
+            .enumerate()
+            .map(|(i, c)| if i == 0 { c.to_ascii_uppercase() } else { c.to_ascii_lowercase() })
+            .collect(),
+    }
+}
+
+fn add_whitespace(rng: &mut impl Rng, value: &str) -> String {
+    match (rng.gen_range(0..3), value.find(' ')) {
+        (0, _) => format!(" {}", value),
+        (1, Some(space)) => format!("{} {}", &value[..space], &value[space..]),
+        _ => format!("{} ", value),
+    }
+}
+
+// Upper-cases the domain, or adds a `+tag` or a dot to the local part
+fn reformat_email(rng: &mut impl Rng, value: &str) -> String {
+    let Some((local, domain)) = value.split_once('@') else {
+        return value.to_string();
+    };
+    match rng.gen_range(0..3) {
+        0 => format!("{}@{}", local, domain.to_uppercase()),
+        1 => format!("{}+{}@{}", local, rng.gen_range(1..100), domain),
+        _ => match local.char_indices().nth(1) {
+            Some((split, _)) => format!("{}.{}@{}", &local[..split], &local[split..], domain),
+            None => format!("{}@{}", local.to_uppercase(), domain),
+        },
+    }
+}
@@ -0,0 +1,238 @@
+use crate::data::generators::DataGenerator;
+use crate::data::timeseries::TimestampFormat;
+use crate::data::types::{ColumnConfig, CsvSchema, DataType};
+use crate::generator::rows::RowGenerator;
+use crate::utils::progress::Progress;
+use anyhow::Result;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+
+/// Longest data name a copybook allows.
+const MAX_COBOL_NAME: usize = 30;
+// Common column names that are COBOL reserved words
+const COBOL_RESERVED: &[&str] = &[
+    "ADDRESS", "COUNT", "DATA", "DATE", "DAY", "FILE", "KEY", "RECORD", "STATUS", "TIME", "VALUE",
+];
+
+/// Where a value sits within its field.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Alignment {
+    /// Text: padded with trailing spaces
+    Left,
+    /// Numbers: padded with leading spaces
+    Right,
+}
+
+/// One field of a fixed-width record.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FixedField {
+    pub name: String,
+    /// 1-based position of the field's first byte in the record
+    pub start: usize,
+    pub length: usize,
+    pub alignment: Alignment,
+    /// COBOL picture clause describing the field
+    pub picture: String,
+}
+
+impl FixedField {
+    /// The record's fields, in order: one per column, as wide as the column's widest value.
+    pub fn layout(schema: &CsvSchema) -> Vec<FixedField> {
+        let mut start = 1;
+        schema
+            .columns
+            .iter()
+            .map(|column| {
+                let length = column.max_width();
+                let field = FixedField {
+                    name: column.name.clone(),
+                    start,
+                    length,
+                    alignment: if column.data_type.is_numeric() { Alignment::Right } else { Alignment::Left },
+                    picture: picture(column, length),
+                };
+                start += length;
+                field
+            })
+            .collect()
+    }
+}
+
+/// Writes generated rows as fixed-width text: no header and no delimiters, each field padded
+/// to its column's width, and each record ending in a newline.
+///
+/// Text is left-aligned and numbers right-aligned, both padded with spaces; nulls are blank
+/// fields. Every record has the same length, so the file size is exactly the row count times
+/// [`record_len`](FixedWidthGenerator::record_len). [`copybook`](FixedWidthGenerator::copybook)
+/// describes the layout for mainframe-style readers.
+pub struct FixedWidthGenerator {
+    rows: RowGenerator,
+    fields: Vec<FixedField>,
+    progress: Progress,
+}
+
+impl FixedWidthGenerator {
+    pub fn new(schema: CsvSchema) -> Self {
+        Self {
+            fields: FixedField::layout(&schema),
+            rows: RowGenerator::new(schema, DataGenerator::new()),
+            progress: Progress::default(),
+        }
+    }
+
+    /// Makes the generated values reproducible, as for [`CsvGenerator::with_seed`](super::size_based::CsvGenerator::with_seed).
+    pub fn with_seed(self, seed: u64) -> Self {
+        Self {
+            rows: RowGenerator::new(self.rows.schema().clone(), DataGenerator::with_seed(seed)),
+            ..self
+        }
+    }
+
+    /// Reports progress as `progress` says instead of with a bar.
+    pub fn with_progress(mut self, progress: Progress) -> Self {
+        self.progress = progress;
+        self
+    }
+
+    pub fn fields(&self) -> &[FixedField] {
+        &self.fields
+    }
+
+    /// Bytes in one record, newline included.
+    pub fn record_len(&self) -> usize {
+        self.fields.iter().map(|f| f.length).sum::<usize>() + 1
+    }
+
+    /// A COBOL copybook for the records, as the group item `record_name`. A comment block
+    /// lists each field's start position, length and alignment.
+    pub fn copybook(&self, record_name: &str) -> String {
+        let mut used = Vec::new();
+        let record_name = cobol_name(record_name, &mut used);
+
+        let mut copybook = format!(
+            "      * {}: {}-byte records plus a newline\n      *\n      * {:<30} {:>6} {:>6}  ALIGN\n",
+            record_name,
+            self.record_len() - 1,
+            "FIELD",
+            "START",
+            "LENGTH"
+        );
+        let names: Vec<String> = self.fields.iter().map(|f| cobol_name(&f.name, &mut used)).collect();
+        for (field, name) in self.fields.iter().zip(&names) {
+            let alignment = match field.alignment {
+                Alignment::Left => "LEFT",
+                Alignment::Right => "RIGHT",
+            };
+            copybook.push_str(&format!("      * {:<30} {:>6} {:>6}  {}\n", name, field.start, field.length, alignment));
+        }
+        copybook.push_str(&format!("       01  {}.\n", record_name));
+        for (field, name) in self.fields.iter().zip(&names) {
+            copybook.push_str(&format!("           05  {:<30} PIC {}.\n", name, field.picture));
+        }
+        copybook
+    }
+
+    pub fn generate(&mut self, output_path: &str, num_rows: usize) -> Result<usize> {
+        let file = File::create(output_path)?;
+        self.write(BufWriter::new(file), num_rows)
+    }
+
+    /// Writes `num_rows` records to `output`.
+    pub fn write<W: Write>(&mut self, mut output: W, num_rows: usize) -> Result<usize> {
+        let record_len = self.record_len() as u64;
+        let mut progress = self.progress.start(Some(num_rows as u64), Some(num_rows as u64 * record_len));
+        let mut record = Vec::new();
+        for row in self.rows.by_ref().take(num_rows) {
+            record.clear();
+            for (value, field) in row.iter().zip(&self.fields) {
+                pad(&mut record, value, field);
+            }
+            record.push(b'\n');
+            output.write_all(&record)?;
+            progress.inc(1, record_len);
+        }
+        output.flush()?;
+        progress.finish();
+
+        Ok(num_rows)
+    }
+}
+
+/// Appends `value` padded with spaces to the field's length. A value that would not fit is
+/// cut at the last whole character, so records stay aligned.
+fn pad(record: &mut Vec<u8>, value: &str, field: &FixedField) {
+    let mut end = value.len().min(field.length);
+    while !value.is_char_boundary(end) {
+        end -= 1;
+    }
+    let value = &value.as_bytes()[..end];
+    let fill = field.length - value.len();
+    if field.alignment == Alignment::Right {
+        record.resize(record.len() + fill, b' ');
+        record.extend_from_slice(value);
+    } else {
+        record.extend_from_slice(value);
+        record.resize(record.len() + fill, b' ');
+    }
+}
+
+/// The picture clause for a column's values at `length` bytes: zero-suppressed or signed
+/// edited numbers for space-padded numbers, `9(n)` for zero-padded ones, `X(n)` otherwise.
+fn picture(column: &ColumnConfig, length: usize) -> String {
+    let digits = |length: usize| format!("{}9", repeat('Z', length - 1));
+    match &column.data_type {
+        DataType::UniqueId => repeat('9', length),
+        DataType::Timestamp(spec) if matches!(spec.format, TimestampFormat::UnixSeconds | TimestampFormat::UnixMillis) => {
+            repeat('9', length)
+        }
+        DataType::Number => digits(length),
+        DataType::Range { min, .. } if *min < 0 && length > 1 => format!("{}9", repeat('-', length - 1)),
+        DataType::Range { .. } => digits(length),
+        DataType::Decimal { scale: 0 } => digits(length),
+        DataType::Decimal { scale } if length > scale + 1 => {
+            format!("{}.{}", digits(length - scale - 1), repeat('9', *scale))
+        }
+        _ => repeat('X', length),
+    }
+}
+
+/// `c` repeated `n` times, as `c(n)` when that is shorter.
+fn repeat(c: char, n: usize) -> String {
+    if n <= 3 {
+        c.to_string().repeat(n)
+    } else {
+        format!("{}({})", c, n)
+    }
+}
+
+/// A valid, unused COBOL data name: letters, digits and inner hyphens, at most 30 characters,
+/// starting with a letter and not a common reserved word.
+fn cobol_name(name: &str, used: &mut Vec<String>) -> String {
+    let mut clean = String::with_capacity(name.len());
+    for c in name.chars() {
+        let c = if c.is_ascii_alphanumeric() { c.to_ascii_uppercase() } else { '-' };
+        // Collapse runs of replaced characters
+        if !(c == '-' && (clean.is_empty() || clean.ends_with('-'))) {
+            clean.push(c);
+        }
+    }
+    let mut clean = clean.trim_end_matches('-').to_string();
+    if !clean.starts_with(|c: char| c.is_ascii_alphabetic()) {
+        clean.insert_str(0, "F-");
+    }
+    if COBOL_RESERVED.contains(&clean.as_str()) {
+        clean.push_str("-FLD");
+    }
+
+    let mut attempt = 1;
+    loop {
+        let suffix = if attempt == 1 { String::new() } else { format!("-{}", attempt) };
+        let stem: String = clean.chars().take(MAX_COBOL_NAME - suffix.len()).collect();
+        let candidate = format!("{}{}", stem.trim_end_matches('-'), suffix);
+        if !used.contains(&candidate) {
+            used.push(candidate.clone());
+            return candidate;
+        }
+        attempt += 1;
+    }
+}
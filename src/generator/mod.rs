@@ -1 +1,13 @@
-pub mod size_based;
\ No newline at end of file
+pub mod size_based;
+pub mod rows;
+pub mod mask;
+pub mod duplicates;
+pub mod sql;
+pub mod fixed_width;
+pub mod throttle;
+#[cfg(feature = "sqlite")]
+pub mod sqlite;
+#[cfg(feature = "xlsx")]
+pub mod xlsx;
+#[cfg(feature = "async")]
+pub mod stream;
@@ -0,0 +1,125 @@
+use crate::data::{generators::DataGenerator, types::CsvSchema};
+use std::thread;
+
+/// An endless stream of generated rows for a schema, without any I/O.
+///
+/// Take as many rows as needed; IDs, timestamps and series continue from one row to the next.
+/// Every row is a function of the seed and its index alone, so the stream can also start
+/// anywhere with [`seek`](RowGenerator::seek) and yields the rows a run from the start would.
+///
+/// ```
+/// use csv_gen::data::{schema::SchemaBuilder, DataGenerator};
+/// use csv_gen::generator::rows::RowGenerator;
+///
+/// let schema = SchemaBuilder::build_schema(10_000, 100, 4, 4, 0, 0)?;
+/// let rows: Vec<Vec<String>> = RowGenerator::new(schema.clone(), DataGenerator::with_seed(1)).take(3).collect();
+/// assert_eq!(rows.len(), 3);
+/// assert_eq!(rows[0].len(), 4);
+///
+/// let mut from_two = RowGenerator::new(schema, DataGenerator::with_seed(1));
+/// from_two.seek(2);
+/// assert_eq!(from_two.next_row(), rows[2]);
+/// # Ok::<(), anyhow::Error>(())
+/// ```
+#[derive(Clone)]
+pub struct RowGenerator {
+    schema: CsvSchema,
+    data_generator: DataGenerator,
+    position: u64,
+}
+
+impl RowGenerator {
+    pub fn new(schema: CsvSchema, data_generator: DataGenerator) -> Self {
+        Self { schema, data_generator, position: 0 }
+    }
+
+    pub fn schema(&self) -> &CsvSchema {
+        &self.schema
+    }
+
+    /// The header record: one name per column.
+    pub fn header(&self) -> Vec<String> {
+        self.schema.columns.iter().map(|c| c.name.clone()).collect()
+    }
+
+    /// Index of the row [`next_row`](RowGenerator::next_row) generates.
+    pub fn position(&self) -> u64 {
+        self.position
+    }
+
+    /// Continues from row `index`, counting from 0.
+    pub fn seek(&mut self, index: u64) {
+        self.position = index;
+    }
+
+    /// Generates row `index` without moving the stream.
+    pub fn row(&mut self, index: u64) -> Vec<String> {
+        self.schema
+            .columns
+            .iter()
+            .enumerate()
+            .map(|(col, column)| self.data_generator.generate_cell(index, col, column))
+            .collect()
+    }
+
+    /// Generates the next row.
+    pub fn next_row(&mut self) -> Vec<String> {
+        let row = self.row(self.position);
+        self.position += 1;
+        row
+    }
+
+    /// Generates the next `count` rows on up to `threads` threads, each taking an equal
+    /// share. The rows are the ones [`next_row`](RowGenerator::next_row) would give.
+    pub fn next_rows(&mut self, count: usize, threads: usize) -> Vec<Vec<String>> {
+        let threads = threads.clamp(1, count.max(1));
+        if threads == 1 {
+            return self.by_ref().take(count).collect();
+        }
+
+        let share = count.div_ceil(threads);
+        // No worker without rows, so the last one ends where the batch does
+        let threads = count.div_ceil(share);
+        let start = self.position;
+        let workers: Vec<RowGenerator> = (0..threads)
+            .map(|i| {
+                let mut worker = self.clone();
+                worker.seek(start + (i * share) as u64);
+                worker
+            })
+            .collect();
+        let mut finished = thread::scope(|scope| {
+            let handles: Vec<_> = workers
+                .into_iter()
+                .enumerate()
+                .map(|(i, mut worker)| {
+                    let rows = share.min(count - i * share);
+                    scope.spawn(move || {
+                        let rows: Vec<Vec<String>> = worker.by_ref().take(rows).collect();
+                        (worker, rows)
+                    })
+                })
+                .collect();
+            handles.into_iter().map(|handle| handle.join().expect("Row generation panicked")).collect::<Vec<_>>()
+        });
+
+        // The last worker's carried state is the nearest to the rows still to come
+        let (last, _) = finished.last_mut().expect("At least one worker");
+        std::mem::swap(self, last);
+        self.seek(start + count as u64);
+        finished.into_iter().flat_map(|(_, rows)| rows).collect()
+    }
+}
+
+impl Iterator for RowGenerator {
+    type Item = Vec<String>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        Some(self.next_row())
+    }
+
+    fn nth(&mut self, n: usize) -> Option<Self::Item> {
+        self.position += n as u64;
+        self.next()
+    }
+}
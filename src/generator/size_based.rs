@@ -1,55 +1,277 @@
-use crate::data::{types::CsvSchema, generators::DataGenerator};
-use anyhow::Result;
+use crate::data::{types::{CsvSchema, DataType}, generators::DataGenerator};
+use crate::generator::rows::RowGenerator;
+use crate::generator::duplicates::{DuplicateConfig, DuplicateInjector};
+use crate::generator::throttle::{Burst, Rate, Throttle};
+use crate::report::{Report, ReportCollector};
+use crate::utils::progress::Progress;
+use anyhow::{bail, Result};
 use csv::WriterBuilder;
-use indicatif::{ProgressBar, ProgressStyle};
 use std::fs::File;
+use std::io::{self, Write};
+use std::ops::Range;
+use std::thread;
 
+// Rows each thread generates at a time
+const BATCH_ROWS: usize = 1000;
+
+/// Writes generated rows to a CSV file, optionally injecting duplicates or pacing the rows
+/// like a live feed.
 pub struct CsvGenerator {
-    schema: CsvSchema,
-    data_generator: DataGenerator,
+    rows: RowGenerator,
+    duplicates: Option<DuplicateConfig>,
+    seed: Option<u64>,
+    progress: Progress,
+    rate: Option<(Rate, Burst)>,
+    collect_report: bool,
+    report: Option<Report>,
+    threads: usize,
 }
 
 impl CsvGenerator {
     pub fn new(schema: CsvSchema) -> Self {
         Self {
-            schema,
-            data_generator: DataGenerator::new(),
+            rows: RowGenerator::new(schema, DataGenerator::new()),
+            duplicates: None,
+            seed: None,
+            progress: Progress::default(),
+            rate: None,
+            collect_report: false,
+            report: None,
+            threads: 1,
         }
     }
 
+    /// Makes the generated values reproducible: the same schema, seed and row count always
+    /// produce the same file, duplicates included.
+    pub fn with_seed(self, seed: u64) -> Self {
+        Self {
+            rows: RowGenerator::new(self.rows.schema().clone(), DataGenerator::with_seed(seed)),
+            seed: Some(seed),
+            ..self
+        }
+    }
+
+    /// Re-emits earlier rows as exact or near duplicates at the configured rates.
+    pub fn with_duplicates(mut self, config: DuplicateConfig) -> Self {
+        self.duplicates = Some(config);
+        self
+    }
+
+    /// Paces the rows to `rate`, varied by `burst`, flushing the output before each pause so
+    /// a reader tailing it sees rows as they are written.
+    pub fn with_rate(mut self, rate: Rate, burst: Burst) -> Self {
+        self.rate = Some((rate, burst));
+        self
+    }
+
+    /// Reports progress as `progress` says instead of with a bar.
+    pub fn with_progress(mut self, progress: Progress) -> Self {
+        self.progress = progress;
+        self
+    }
+
+    /// Hides the progress bar, e.g. when many files are written at once.
+    pub fn without_progress(self) -> Self {
+        self.with_progress(Progress::Hidden)
+    }
+
+    /// Collects statistics of the rows as they are written, for [`report`](CsvGenerator::report).
+    pub fn with_report(mut self) -> Self {
+        self.collect_report = true;
+        self
+    }
+
+    /// Generates rows on `threads` threads. The output is the same as on one: rows are
+    /// generated in batches, each thread taking a share, and written in order.
+    pub fn with_threads(mut self, threads: usize) -> Self {
+        self.threads = threads.max(1);
+        self
+    }
+
+    /// Statistics of the last write, if [`with_report`](CsvGenerator::with_report) was set.
+    pub fn report(&self) -> Option<&Report> {
+        self.report.as_ref()
+    }
+
     pub fn generate(&mut self, output_path: &str, num_rows: usize) -> Result<usize> {
+        if let Some(config) = &self.duplicates {
+            config.validate()?;
+        }
+
+        let file = File::create(output_path)?;
+        self.write(file, num_rows)
+    }
+
+    /// Writes rows `rows.start` up to `rows.end` to the file at `output_path`; see
+    /// [`write_range`](CsvGenerator::write_range).
+    pub fn generate_range(&mut self, output_path: &str, rows: Range<usize>) -> Result<usize> {
         let file = File::create(output_path)?;
-        let mut writer = WriterBuilder::new().from_writer(file);
-
-        let pb = ProgressBar::new(num_rows as u64);
-        pb.set_style(
-            ProgressStyle::default_bar()
-                .template("{spinner:.green} [{elapsed_precise}] [{bar:40.cyan/blue}] {pos}/{len} ({eta})")
-                .unwrap()
-                .progress_chars("#>-"),
+        self.write_range(file, rows)
+    }
+
+    /// Writes the header and `num_rows` rows to `output`.
+    pub fn write<W: Write>(&mut self, output: W, num_rows: usize) -> Result<usize> {
+        self.write_rows(output, 0, Some(num_rows))
+    }
+
+    /// Writes rows `rows.start` up to `rows.end`, counting from 0, exactly as they appear in
+    /// a full write with the same seed, and the header only if the range starts at row 0.
+    /// The rows before the range aren't generated, so a large file can be written in pieces,
+    /// in parallel or resumed, and the pieces concatenate into the full file.
+    pub fn write_range<W: Write>(&mut self, output: W, rows: Range<usize>) -> Result<usize> {
+        if rows.start > rows.end {
+            bail!("Row range {}..{} is empty", rows.start, rows.end);
+        }
+        self.write_rows(output, rows.start, Some(rows.end))
+    }
+
+    /// Writes the header and then rows without end, like a log producer, until the process
+    /// is interrupted or the reader of a pipe goes away. Returns the rows written.
+    ///
+    /// Set a rate with [`with_rate`](CsvGenerator::with_rate), or rows are written as fast
+    /// as the output takes them.
+    pub fn follow<W: Write>(&mut self, output: W) -> Result<usize> {
+        self.write_rows(output, 0, None)
+    }
+
+    /// Writes from row `start` up to row `end`, or without end.
+    fn write_rows<W: Write>(&mut self, output: W, start: usize, end: Option<usize>) -> Result<usize> {
+        if let Some(config) = &self.duplicates {
+            config.validate()?;
+            // Duplicates are copies of earlier rows, so they can't start partway
+            if start > 0 {
+                bail!("Duplicates can only be injected into rows written from the first");
+            }
+        }
+        let limit = end.map(|end| end - start);
+
+        let mut writer = WriterBuilder::new().from_writer(UntilClosed { inner: output, closed: false, bytes: 0 });
+
+        let mut duplicates = match &self.duplicates {
+            Some(config) => {
+                let mut truth_writer = WriterBuilder::new().from_path(&config.ground_truth_path)?;
+                truth_writer.write_record(["duplicate_id", "original_id", "kind"])?;
+                let injector = DuplicateInjector::new(config.clone());
+                let injector = match self.seed {
+                    Some(seed) => injector.with_seed(seed),
+                    None => injector,
+                };
+                Some((injector, truth_writer))
+            }
+            None => None,
+        };
+        let columns = self.rows.schema().columns.clone();
+        let id_index = columns.iter().position(|c| matches!(c.data_type, DataType::UniqueId));
+
+        let mut progress = self.progress.start(
+            limit.map(|num_rows| num_rows as u64),
+            limit.map(|num_rows| {
+                let header_size = if start == 0 { self.rows.schema().header_size } else { 0 };
+                (header_size + num_rows * self.rows.schema().target_row_size) as u64
+            }),
         );
+        let mut throttle = self.rate.map(|(rate, burst)| Throttle::new(rate, burst));
+        let mut collector = self.collect_report.then(|| ReportCollector::new(self.rows.schema()));
+        if start == 0 {
+            let header = self.rows.header();
+            writer.write_record(&header)?;
+            // Through to the output, so everything it receives from here on is data
+            if collector.is_some() {
+                writer.flush()?;
+            }
+            progress.inc(0, header.iter().map(|name| name.len() + 1).sum::<usize>() as u64);
+        }
+        let header_bytes = writer.get_ref().bytes;
 
-        let header: Vec<String> = self.schema.columns.iter().map(|c| c.name.clone()).collect();
-        writer.write_record(&header)?;
+        self.rows.seek(start as u64);
+        let mut written = 0;
+        'rows: while limit.is_none_or(|limit| written < limit) {
+            let batch = limit.map_or(usize::MAX, |limit| limit - written).min(BATCH_ROWS * self.threads);
+            for mut row in self.rows.next_rows(batch, self.threads) {
+                if let Some((injector, truth_writer)) = &mut duplicates
+                    && let Some((original_id, kind)) = injector.process(&columns, &mut row)
+                {
+                    let duplicate_id = id_index.map(|i| row[i].as_str()).unwrap_or_default();
+                    truth_writer.write_record([duplicate_id, original_id.as_str(), kind.as_str()])?;
+                }
 
-        for _ in 0..num_rows {
-            let row: Vec<String> = self.schema
-                .columns
-                .iter()
-                .map(|col| self.data_generator.generate_value(col))
-                .collect();
-            
-            writer.write_record(&row)?;
-            pb.inc(1);
+                // Unquoted size: the values, a separator after each but the last, and a newline
+                let row_bytes = row.iter().map(|value| value.len() + 1).sum();
+                if let Some(wait) = throttle.as_mut().and_then(|t| t.pause(row_bytes)) {
+                    writer.flush()?;
+                    if let Some((_, truth_writer)) = &mut duplicates {
+                        truth_writer.flush()?;
+                    }
+                    thread::sleep(wait);
+                }
+                if writer.get_ref().closed {
+                    break 'rows;
+                }
+                writer.write_record(&row)?;
+                if let Some(collector) = &mut collector {
+                    collector.observe(&row);
+                }
+                written += 1;
+                progress.inc(1, row_bytes as u64);
+            }
         }
 
         writer.flush()?;
-        pb.finish_with_message("CSV generation complete!");
+        // Following ends when the reader goes away; a fixed row count must all arrive
+        if writer.get_ref().closed && limit.is_some() {
+            return Err(io::Error::from(io::ErrorKind::BrokenPipe).into());
+        }
+        if let Some((_, truth_writer)) = &mut duplicates {
+            truth_writer.flush()?;
+        }
+        progress.finish();
+        self.report = collector.map(|collector| collector.finish(header_bytes, writer.get_ref().bytes));
 
-        Ok(num_rows)
+        Ok(written)
     }
 
     pub fn get_estimated_size(&self, num_rows: usize) -> usize {
-        self.schema.header_size + (num_rows * self.schema.target_row_size)
+        let schema = self.rows.schema();
+        schema.header_size + (num_rows * schema.target_row_size)
     }
-}
\ No newline at end of file
+}
+
+/// Passes writes through until the reader of a pipe goes away, then drops them and
+/// remembers that it did, so following can stop cleanly. Counts the bytes that got through.
+struct UntilClosed<W> {
+    inner: W,
+    closed: bool,
+    bytes: u64,
+}
+
+impl<W: Write> Write for UntilClosed<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if self.closed {
+            return Ok(buf.len());
+        }
+        match self.inner.write(buf) {
+            Err(e) if e.kind() == io::ErrorKind::BrokenPipe => {
+                self.closed = true;
+                Ok(buf.len())
+            }
+            Ok(written) => {
+                self.bytes += written as u64;
+                Ok(written)
+            }
+            result => result,
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        if self.closed {
+            return Ok(());
+        }
+        match self.inner.flush() {
+            Err(e) if e.kind() == io::ErrorKind::BrokenPipe => {
+                self.closed = true;
+                Ok(())
+            }
+            result => result,
+        }
+    }
+}
@@ -0,0 +1,275 @@
+use crate::data::timeseries::TimestampFormat;
+use crate::data::types::{ColumnConfig, CsvSchema, DataType};
+use crate::data::generators::DataGenerator;
+use crate::generator::rows::RowGenerator;
+use crate::utils::progress::Progress;
+use anyhow::{anyhow, bail, Result};
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::str::FromStr;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SqlDialect {
+    Postgres,
+    MySql,
+    Sqlite,
+}
+
+impl FromStr for SqlDialect {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_lowercase().as_str() {
+            "postgres" | "postgresql" | "pg" => Ok(SqlDialect::Postgres),
+            "mysql" | "mariadb" => Ok(SqlDialect::MySql),
+            "sqlite" => Ok(SqlDialect::Sqlite),
+            _ => Err(anyhow!("Unknown SQL dialect '{}'. Use postgres, mysql or sqlite", s)),
+        }
+    }
+}
+
+/// How rows are loaded after the `CREATE TABLE`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SqlStatements {
+    /// Multi-row `INSERT`s of up to this many rows each
+    Insert { batch_size: usize },
+    /// One PostgreSQL `COPY ... FROM STDIN` block in text format
+    Copy,
+}
+
+impl Default for SqlStatements {
+    fn default() -> Self {
+        SqlStatements::Insert { batch_size: 1000 }
+    }
+}
+
+/// Writes a generated table as a SQL script: `CREATE TABLE`, then the rows.
+///
+/// Column types follow the data type and the column's width. Columns with a null rate are
+/// nullable and their empty values become `NULL`; every other column is `NOT NULL`, and the
+/// ID column is the primary key unless it has nulls. `--size` still targets the CSV form of
+/// the data, so the script is somewhat larger.
+pub struct SqlGenerator {
+    rows: RowGenerator,
+    dialect: SqlDialect,
+    table: String,
+    statements: SqlStatements,
+    progress: Progress,
+}
+
+impl SqlGenerator {
+    pub fn new(schema: CsvSchema, dialect: SqlDialect, table: impl Into<String>) -> Self {
+        Self {
+            rows: RowGenerator::new(schema, DataGenerator::new()),
+            dialect,
+            table: table.into(),
+            statements: SqlStatements::default(),
+            progress: Progress::default(),
+        }
+    }
+
+    /// Makes the generated values reproducible, as for [`CsvGenerator::with_seed`](super::size_based::CsvGenerator::with_seed).
+    pub fn with_seed(self, seed: u64) -> Self {
+        Self {
+            rows: RowGenerator::new(self.rows.schema().clone(), DataGenerator::with_seed(seed)),
+            ..self
+        }
+    }
+
+    pub fn with_statements(mut self, statements: SqlStatements) -> Self {
+        self.statements = statements;
+        self
+    }
+
+    /// Reports progress as `progress` says instead of with a bar.
+    pub fn with_progress(mut self, progress: Progress) -> Self {
+        self.progress = progress;
+        self
+    }
+
+    /// The `CREATE TABLE` statement for the schema.
+    pub fn create_table(&self) -> String {
+        let columns: Vec<String> = self
+            .rows
+            .schema()
+            .columns
+            .iter()
+            .map(|column| {
+                let mut definition = format!("    {} {}", self.identifier(&column.name), self.column_type(column));
+                if column.null_rate <= 0.0 {
+                    definition.push_str(" NOT NULL");
+                }
+                if matches!(column.data_type, DataType::UniqueId) && column.null_rate <= 0.0 {
+                    definition.push_str(" PRIMARY KEY");
+                }
+                definition
+            })
+            .collect();
+        format!("CREATE TABLE {} (\n{}\n);\n", self.identifier(&self.table), columns.join(",\n"))
+    }
+
+    pub fn generate(&mut self, output_path: &str, num_rows: usize) -> Result<usize> {
+        let file = File::create(output_path)?;
+        self.write(BufWriter::new(file), num_rows)
+    }
+
+    /// Writes the `CREATE TABLE` statement and `num_rows` rows to `output`.
+    pub fn write<W: Write>(&mut self, mut output: W, num_rows: usize) -> Result<usize> {
+        if self.statements == SqlStatements::Copy && self.dialect != SqlDialect::Postgres {
+            bail!("COPY FROM STDIN is only supported for PostgreSQL");
+        }
+        if let SqlStatements::Insert { batch_size: 0 } = self.statements {
+            bail!("The INSERT batch size must be positive");
+        }
+
+        output.write_all(self.create_table().as_bytes())?;
+        let columns: Vec<ColumnConfig> = self.rows.schema().columns.clone();
+        let column_list = columns.iter().map(|c| self.identifier(&c.name)).collect::<Vec<_>>().join(", ");
+        let table = self.identifier(&self.table);
+
+        let mut progress = self.progress.start(Some(num_rows as u64), None);
+        match self.statements {
+            SqlStatements::Copy => {
+                writeln!(output, "\nCOPY {} ({}) FROM STDIN;", table, column_list)?;
+                for row in self.rows.by_ref().take(num_rows) {
+                    let fields: Vec<String> = row
+                        .iter()
+                        .zip(&columns)
+                        .map(|(value, column)| copy_field(value, column))
+                        .collect();
+                    let line = fields.join("\t");
+                    writeln!(output, "{}", line)?;
+                    progress.inc(1, line.len() as u64 + 1);
+                }
+                writeln!(output, "\\.")?;
+            }
+            SqlStatements::Insert { batch_size } => {
+                for (i, row) in self.rows.by_ref().take(num_rows).enumerate() {
+                    if i % batch_size == 0 {
+                        if i > 0 {
+                            writeln!(output, ";")?;
+                        }
+                        writeln!(output, "\nINSERT INTO {} ({}) VALUES", table, column_list)?;
+                    } else {
+                        writeln!(output, ",")?;
+                    }
+                    let literals: Vec<String> = row
+                        .iter()
+                        .zip(&columns)
+                        .map(|(value, column)| literal(self.dialect, value, column))
+                        .collect();
+                    let values = literals.join(", ");
+                    write!(output, "({})", values)?;
+                    progress.inc(1, values.len() as u64 + 4);
+                }
+                if num_rows > 0 {
+                    writeln!(output, ";")?;
+                }
+            }
+        }
+        output.flush()?;
+        progress.finish();
+
+        Ok(num_rows)
+    }
+
+    fn column_type(&self, column: &ColumnConfig) -> String {
+        let width = column.max_width();
+        let text = match self.dialect {
+            SqlDialect::Sqlite => "TEXT".to_string(),
+            // Wider VARCHARs quickly exceed MySQL's 64 KB row limit
+            SqlDialect::MySql if width > 255 => "TEXT".to_string(),
+            SqlDialect::Postgres if width > 10_485_760 => "TEXT".to_string(),
+            _ => format!("VARCHAR({})", width),
+        };
+        let decimal = |scale: usize| match self.dialect {
+            SqlDialect::MySql => format!("DECIMAL({}, {})", width.clamp(scale.max(1), 65), scale),
+            // PostgreSQL caps the precision at 1000; unconstrained NUMERIC takes any value
+            SqlDialect::Postgres if width.max(scale) > 1000 => "NUMERIC".to_string(),
+            _ => format!("NUMERIC({}, {})", width.max(scale.max(1)), scale),
+        };
+        let integer = if self.dialect == SqlDialect::Sqlite { "INTEGER" } else { "BIGINT" };
+
+        match &column.data_type {
+            DataType::UniqueId => integer.to_string(),
+            // Up to 19 digits, which can overflow a BIGINT
+            DataType::Number if width > 18 => decimal(0),
+            DataType::Number => integer.to_string(),
+            DataType::Range { min, max } => {
+                let fits_int = i32::try_from(*min).is_ok() && i32::try_from(*max).is_ok();
+                if fits_int && self.dialect != SqlDialect::Sqlite { "INTEGER" } else { integer }.to_string()
+            }
+            DataType::Decimal { scale } => decimal(*scale),
+            DataType::Series(spec) => decimal(spec.precision),
+            DataType::Date if self.dialect != SqlDialect::Sqlite => "DATE".to_string(),
+            DataType::Timestamp(spec) => match (spec.format, self.dialect) {
+                (TimestampFormat::UnixSeconds | TimestampFormat::UnixMillis, _) => integer.to_string(),
+                (_, SqlDialect::Sqlite) => text,
+                (TimestampFormat::DateTime, SqlDialect::Postgres) => "TIMESTAMP".to_string(),
+                (_, SqlDialect::Postgres) => "TIMESTAMPTZ".to_string(),
+                (TimestampFormat::DateTime, SqlDialect::MySql) => "DATETIME".to_string(),
+                // MySQL rejects the trailing `Z` of ISO 8601 timestamps
+                (_, SqlDialect::MySql) => text,
+            },
+            DataType::Date
+            | DataType::String
+            | DataType::Email
+            | DataType::Name
+            | DataType::Domain
+            | DataType::Pattern(_)
+            | DataType::Enum(_) => text,
+        }
+    }
+
+    fn identifier(&self, name: &str) -> String {
+        match self.dialect {
+            SqlDialect::MySql => format!("`{}`", name.replace('`', "``")),
+            _ => format!("\"{}\"", name.replace('"', "\"\"")),
+        }
+    }
+}
+
+/// A value as a SQL literal in an `INSERT`.
+fn literal(dialect: SqlDialect, value: &str, column: &ColumnConfig) -> String {
+    if value.is_empty() && column.null_rate > 0.0 {
+        return "NULL".to_string();
+    }
+    if column.data_type.is_numeric() {
+        return trim_leading_zeros(value);
+    }
+    let escaped = match dialect {
+        // MySQL treats backslashes in strings as escapes by default
+        SqlDialect::MySql => value.replace('\\', "\\\\").replace('\'', "''"),
+        _ => value.replace('\'', "''"),
+    };
+    format!("'{}'", escaped)
+}
+
+/// A field in `COPY` text format: backslash escapes, `\N` for null.
+fn copy_field(value: &str, column: &ColumnConfig) -> String {
+    if value.is_empty() && column.null_rate > 0.0 {
+        return "\\N".to_string();
+    }
+    let mut field = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '\\' => field.push_str("\\\\"),
+            '\t' => field.push_str("\\t"),
+            '\n' => field.push_str("\\n"),
+            '\r' => field.push_str("\\r"),
+            c => field.push(c),
+        }
+    }
+    field
+}
+
+/// Drops zero padding (`-0012.50` becomes `-12.50`) so numbers are not read as octal or text.
+fn trim_leading_zeros(value: &str) -> String {
+    let (sign, digits) = match value.strip_prefix('-') {
+        Some(digits) => ("-", digits),
+        None => ("", value),
+    };
+    let trimmed = digits.trim_start_matches('0');
+    let trimmed = if trimmed.is_empty() || trimmed.starts_with('.') { format!("0{}", trimmed) } else { trimmed.to_string() };
+    format!("{}{}", sign, trimmed)
+}
@@ -0,0 +1,147 @@
+use crate::data::generators::DataGenerator;
+use crate::data::timeseries::TimestampFormat;
+use crate::data::types::{ColumnConfig, CsvSchema, DataType};
+use crate::generator::rows::RowGenerator;
+use crate::generator::sql::{SqlDialect, SqlGenerator};
+use crate::utils::progress::Progress;
+use anyhow::{bail, Result};
+use rusqlite::types::Value;
+use rusqlite::{params_from_iter, Connection};
+use std::path::Path;
+
+/// Rows per transaction unless set otherwise.
+pub const DEFAULT_TRANSACTION_ROWS: usize = 100_000;
+
+/// One table to generate into a SQLite database.
+pub struct SqliteTable {
+    name: String,
+    rows: RowGenerator,
+    num_rows: usize,
+    indexes: Vec<String>,
+}
+
+impl SqliteTable {
+    pub fn new(name: impl Into<String>, schema: CsvSchema, num_rows: usize) -> Self {
+        Self {
+            name: name.into(),
+            rows: RowGenerator::new(schema, DataGenerator::new()),
+            num_rows,
+            indexes: Vec::new(),
+        }
+    }
+
+    /// Makes the generated values reproducible.
+    pub fn with_seed(self, seed: u64) -> Self {
+        Self {
+            rows: RowGenerator::new(self.rows.schema().clone(), DataGenerator::with_seed(seed)),
+            ..self
+        }
+    }
+
+    /// Indexes a column once the rows are loaded.
+    pub fn with_index(mut self, column: impl Into<String>) -> Self {
+        self.indexes.push(column.into());
+        self
+    }
+}
+
+/// Generates tables straight into a SQLite database file.
+///
+/// Each table is created from its schema as in `--format sql --dialect sqlite`, with the ID
+/// column as the `INTEGER PRIMARY KEY`. Rows go through one prepared `INSERT`, committed every
+/// [`DEFAULT_TRANSACTION_ROWS`] rows; indexes are built after loading. Other tables in the
+/// file are left alone, so a database can be built up one table at a time.
+pub struct SqliteSink {
+    connection: Connection,
+    transaction_rows: usize,
+    progress: Progress,
+}
+
+impl SqliteSink {
+    /// Opens or creates the database at `path`.
+    pub fn open(path: impl AsRef<Path>) -> Result<Self> {
+        Ok(Self {
+            connection: Connection::open(path)?,
+            transaction_rows: DEFAULT_TRANSACTION_ROWS,
+            progress: Progress::default(),
+        })
+    }
+
+    pub fn with_transaction_rows(mut self, rows: usize) -> Self {
+        self.transaction_rows = rows.max(1);
+        self
+    }
+
+    /// Reports each table's progress as `progress` says instead of with a bar.
+    pub fn with_progress(mut self, progress: Progress) -> Self {
+        self.progress = progress;
+        self
+    }
+
+    /// Creates the table, replacing any table of the same name, and fills it. Returns the
+    /// number of rows inserted.
+    pub fn write_table(&mut self, mut table: SqliteTable) -> Result<usize> {
+        let columns = table.rows.schema().columns.clone();
+        for index in &table.indexes {
+            if !columns.iter().any(|c| &c.name == index) {
+                bail!("Cannot index '{}': table {} has no such column", index, table.name);
+            }
+        }
+
+        let ddl = SqlGenerator::new(table.rows.schema().clone(), SqlDialect::Sqlite, &table.name).create_table();
+        let name = quote(&table.name);
+        self.connection.execute_batch(&format!("DROP TABLE IF EXISTS {};\n{}", name, ddl))?;
+
+        let placeholders = (1..=columns.len()).map(|i| format!("?{}", i)).collect::<Vec<_>>().join(", ");
+        let insert = format!("INSERT INTO {} VALUES ({})", name, placeholders);
+
+        let mut progress = self.progress.start(Some(table.num_rows as u64), None);
+        let mut inserted = 0;
+        while inserted < table.num_rows {
+            let batch = self.transaction_rows.min(table.num_rows - inserted);
+            let transaction = self.connection.transaction()?;
+            {
+                let mut statement = transaction.prepare_cached(&insert)?;
+                for row in table.rows.by_ref().take(batch) {
+                    statement.execute(params_from_iter(row.into_iter().zip(&columns).map(|(v, c)| value(v, c))))?;
+                }
+            }
+            transaction.commit()?;
+            inserted += batch;
+            progress.inc(batch as u64, 0);
+        }
+
+        for column in &table.indexes {
+            let index = quote(&format!("idx_{}_{}", table.name, column));
+            self.connection
+                .execute_batch(&format!("CREATE INDEX {} ON {} ({});", index, name, quote(column)))?;
+        }
+        progress.finish();
+
+        Ok(inserted)
+    }
+}
+
+/// A generated value with the storage class its column's type calls for.
+fn value(value: String, column: &ColumnConfig) -> Value {
+    if value.is_empty() && column.null_rate > 0.0 {
+        return Value::Null;
+    }
+    let integer = match &column.data_type {
+        DataType::UniqueId | DataType::Number | DataType::Range { .. } => true,
+        DataType::Timestamp(spec) => matches!(spec.format, TimestampFormat::UnixSeconds | TimestampFormat::UnixMillis),
+        _ => false,
+    };
+    let real = matches!(column.data_type, DataType::Decimal { .. } | DataType::Series(_));
+    if integer && let Ok(number) = value.parse() {
+        return Value::Integer(number);
+    }
+    if real && let Ok(number) = value.parse() {
+        return Value::Real(number);
+    }
+    Value::Text(value)
+}
+
+fn quote(identifier: &str) -> String {
+    format!("\"{}\"", identifier.replace('"', "\"\""))
+}
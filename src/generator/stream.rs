@@ -0,0 +1,91 @@
+use crate::generator::rows::RowGenerator;
+use csv::WriterBuilder;
+use futures::{Stream, StreamExt};
+use std::io;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use tokio::io::{AsyncWrite, AsyncWriteExt};
+
+/// Bytes of CSV encoded per chunk unless set otherwise.
+pub const DEFAULT_CHUNK_SIZE: usize = 64 * 1024;
+
+/// A [`Stream`] of encoded CSV chunks: the header, then `num_rows` rows.
+///
+/// Rows are generated only when the stream is polled, so a slow consumer (a full channel,
+/// a congested socket) pauses generation instead of buffering the file. Each poll encodes
+/// about one chunk, which bounds how long it holds the runtime.
+///
+/// The bytes are exactly those [`CsvGenerator::write`](crate::generator::size_based::CsvGenerator::write)
+/// would write for the same rows.
+pub struct CsvStream {
+    rows: RowGenerator,
+    remaining: usize,
+    header_written: bool,
+    chunk_size: usize,
+    written: usize,
+}
+
+impl CsvStream {
+    pub fn new(rows: RowGenerator, num_rows: usize) -> Self {
+        Self {
+            rows,
+            remaining: num_rows,
+            header_written: false,
+            chunk_size: DEFAULT_CHUNK_SIZE,
+            written: 0,
+        }
+    }
+
+    /// Ends each chunk at the first row boundary past `bytes`; rows are never split.
+    pub fn with_chunk_size(mut self, bytes: usize) -> Self {
+        self.chunk_size = bytes.max(1);
+        self
+    }
+
+    /// Data rows encoded so far.
+    pub fn rows_written(&self) -> usize {
+        self.written
+    }
+
+    fn next_chunk(&mut self) -> io::Result<Option<Vec<u8>>> {
+        if self.header_written && self.remaining == 0 {
+            return Ok(None);
+        }
+
+        let mut writer = WriterBuilder::new().from_writer(Vec::with_capacity(self.chunk_size));
+        if !self.header_written {
+            writer.write_record(self.rows.header())?;
+            self.header_written = true;
+        }
+        // Flushing into a Vec is a copy, and keeps the chunk's length exact.
+        writer.flush()?;
+        while self.remaining > 0 && writer.get_ref().len() < self.chunk_size {
+            writer.write_record(self.rows.next_row())?;
+            writer.flush()?;
+            self.remaining -= 1;
+            self.written += 1;
+        }
+        writer.into_inner().map(Some).map_err(|e| e.into_error())
+    }
+
+    /// Writes every remaining chunk to `output` and flushes it, returning the number of data rows.
+    pub async fn write_to<W: AsyncWrite + Unpin>(mut self, mut output: W) -> io::Result<usize> {
+        while let Some(chunk) = self.next().await {
+            output.write_all(&chunk?).await?;
+        }
+        output.flush().await?;
+        Ok(self.written)
+    }
+}
+
+impl Stream for CsvStream {
+    type Item = io::Result<Vec<u8>>;
+
+    fn poll_next(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        Poll::Ready(self.get_mut().next_chunk().transpose())
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        if self.header_written && self.remaining == 0 { (0, Some(0)) } else { (1, None) }
+    }
+}
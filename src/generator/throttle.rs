@@ -0,0 +1,171 @@
+use crate::utils::units;
+use anyhow::{Result, anyhow, bail};
+use std::f64::consts::TAU;
+use std::str::FromStr;
+use std::time::{Duration, Instant};
+
+// Rows that are due within this long are written without sleeping, so each sleep ends a
+// batch of at least this much output
+const MIN_PAUSE: Duration = Duration::from_millis(10);
+
+/// How fast rows are written: the mean rate of a sine wave, or the rate between spikes.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Rate {
+    RowsPerSecond(f64),
+    BytesPerSecond(f64),
+}
+
+impl FromStr for Rate {
+    type Err = anyhow::Error;
+
+    /// Parses `500rows/s` (or `2.5k rows/s`) as a row rate and `2MB/s` as a byte rate.
+    fn from_str(s: &str) -> Result<Self> {
+        let per_second = s
+            .trim()
+            .strip_suffix("/s")
+            .ok_or_else(|| anyhow!("Invalid rate '{}'. Use e.g. 500rows/s or 2MB/s", s))?;
+        let rate = match per_second.strip_suffix("rows").or_else(|| per_second.strip_suffix("row")) {
+            Some(rows) => Rate::RowsPerSecond(units::parse_count(rows)? as f64),
+            None => Rate::BytesPerSecond(units::parse_byte_rate(per_second)? as f64),
+        };
+        if rate.value() <= 0.0 {
+            bail!("Rate must be positive");
+        }
+        Ok(rate)
+    }
+}
+
+impl Rate {
+    fn value(&self) -> f64 {
+        match *self {
+            Rate::RowsPerSecond(rate) | Rate::BytesPerSecond(rate) => rate,
+        }
+    }
+}
+
+/// How the rate varies over time.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum Burst {
+    /// The same rate throughout
+    #[default]
+    Steady,
+    /// `rate * (1 + amplitude * sin(2π t / period))`, with `period` in seconds and
+    /// `amplitude` from 0 to 1
+    Sine { period: f64, amplitude: f64 },
+    /// The rate, except for the first `length` seconds of every `every` seconds, when rows
+    /// come `factor` times as fast
+    Spikes { every: f64, length: f64, factor: f64 },
+}
+
+impl FromStr for Burst {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let (kind, params) = s.split_once(':').unwrap_or((s, ""));
+        let mut burst = match kind.to_lowercase().as_str() {
+            "steady" => Burst::Steady,
+            "sine" => Burst::Sine { period: 60.0, amplitude: 0.5 },
+            "spikes" => Burst::Spikes { every: 10.0, length: 1.0, factor: 10.0 },
+            _ => bail!("Unknown burst pattern '{}'. Use steady, sine or spikes", kind),
+        };
+
+        for param in params.split(',').filter(|p| !p.is_empty()) {
+            let (key, value) = param
+                .split_once('=')
+                .ok_or_else(|| anyhow!("Burst parameter '{}' must be key=value", param))?;
+            let value: f64 = value.parse()?;
+            let field = match (&mut burst, key) {
+                (Burst::Sine { period, .. }, "period") => period,
+                (Burst::Sine { amplitude, .. }, "amplitude") => amplitude,
+                (Burst::Spikes { every, .. }, "every") => every,
+                (Burst::Spikes { length, .. }, "length") => length,
+                (Burst::Spikes { factor, .. }, "factor") => factor,
+                _ => bail!("Unknown parameter '{}' for burst pattern '{}'", key, s),
+            };
+            *field = value;
+        }
+
+        match burst {
+            Burst::Sine { period, amplitude } if period <= 0.0 || !(0.0..=1.0).contains(&amplitude) => {
+                bail!("Sine bursts need a positive period and an amplitude from 0 to 1")
+            }
+            Burst::Spikes { every, length, factor } if every <= 0.0 || !(0.0..=every).contains(&length) || factor <= 0.0 => {
+                bail!("Spikes need a positive period, a length within it and a positive factor")
+            }
+            _ => Ok(burst),
+        }
+    }
+}
+
+impl Burst {
+    /// Rows or bytes allowed in the first `t` seconds at an average `rate`: the rate
+    /// integrated over time.
+    pub fn allowance(&self, rate: f64, t: f64) -> f64 {
+        match *self {
+            Burst::Steady => rate * t,
+            Burst::Sine { period, amplitude } => {
+                rate * (t + amplitude * period / TAU * (1.0 - (TAU * t / period).cos()))
+            }
+            Burst::Spikes { every, length, factor } => {
+                let cycles = (t / every).floor();
+                let into_cycle = t - cycles * every;
+                let per_cycle = every + (factor - 1.0) * length;
+                rate * (cycles * per_cycle + into_cycle + (factor - 1.0) * into_cycle.min(length))
+            }
+        }
+    }
+}
+
+/// Paces output to a [`Rate`] shaped by a [`Burst`] pattern.
+///
+/// Call [`pause`](Throttle::pause) before writing each row; it returns how long to wait
+/// first. Short waits are skipped, so rows go out in batches: flushing before each wait
+/// lets a reader tailing the file see every batch as soon as it is written.
+#[derive(Debug, Clone)]
+pub struct Throttle {
+    rate: Rate,
+    burst: Burst,
+    started: Instant,
+    sent: f64,
+}
+
+impl Throttle {
+    pub fn new(rate: Rate, burst: Burst) -> Self {
+        Self { rate, burst, started: Instant::now(), sent: 0.0 }
+    }
+
+    /// When, after the start, `total` rows or bytes are allowed out.
+    pub fn due(&self, total: f64) -> Duration {
+        let rate = self.rate.value();
+        if self.burst == Burst::Steady {
+            return Duration::from_secs_f64(total / rate);
+        }
+        let allowance = |t: f64| self.burst.allowance(rate, t);
+        // The allowance only grows, so bisect between 0 and a time known to be late enough
+        let mut late = total / rate;
+        while allowance(late) < total {
+            late *= 2.0;
+        }
+        let mut early = 0.0;
+        for _ in 0..64 {
+            let middle = (early + late) / 2.0;
+            if allowance(middle) < total {
+                early = middle;
+            } else {
+                late = middle;
+            }
+        }
+        Duration::from_secs_f64(late)
+    }
+
+    /// Counts a row of `bytes` bytes and returns how long to wait before writing it, if
+    /// it is due more than a few milliseconds from now.
+    pub fn pause(&mut self, bytes: usize) -> Option<Duration> {
+        self.sent += match self.rate {
+            Rate::RowsPerSecond(_) => 1.0,
+            Rate::BytesPerSecond(_) => bytes as f64,
+        };
+        let wait = self.due(self.sent).checked_sub(self.started.elapsed())?;
+        (wait >= MIN_PAUSE).then_some(wait)
+    }
+}
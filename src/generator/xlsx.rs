@@ -0,0 +1,222 @@
+use crate::data::generators::DataGenerator;
+use crate::data::timeseries::TimestampFormat;
+use crate::data::types::{ColumnConfig, CsvSchema, DataType};
+use crate::generator::rows::RowGenerator;
+use crate::utils::progress::Progress;
+use anyhow::{bail, Context, Result};
+use rust_xlsxwriter::{DocProperties, ExcelDateTime, Format, Workbook, Worksheet};
+use std::path::Path;
+
+/// Rows per worksheet in Excel, including the header.
+pub const EXCEL_MAX_ROWS: usize = 1_048_576;
+/// Columns per worksheet in Excel.
+pub const EXCEL_MAX_COLUMNS: usize = 16_384;
+/// Longest sheet name Excel accepts.
+const MAX_SHEET_NAME: usize = 31;
+/// Digits a double holds exactly; longer numbers are written as text.
+const MAX_EXACT_DIGITS: usize = 15;
+
+/// One generated table, written to one or more worksheets.
+pub struct XlsxSheet {
+    name: String,
+    rows: RowGenerator,
+    num_rows: usize,
+}
+
+impl XlsxSheet {
+    pub fn new(name: impl Into<String>, schema: CsvSchema, num_rows: usize) -> Self {
+        Self {
+            name: name.into(),
+            rows: RowGenerator::new(schema, DataGenerator::new()),
+            num_rows,
+        }
+    }
+
+    /// Makes the generated values reproducible.
+    pub fn with_seed(self, seed: u64) -> Self {
+        Self {
+            rows: RowGenerator::new(self.rows.schema().clone(), DataGenerator::with_seed(seed)),
+            ..self
+        }
+    }
+}
+
+/// How a column's values become cells.
+enum CellKind {
+    Text,
+    Number(Format),
+    DateTime(Format),
+}
+
+impl CellKind {
+    fn for_column(column: &ColumnConfig) -> Self {
+        let decimals = |places: usize| {
+            let format = if places == 0 { "0".to_string() } else { format!("0.{}", "0".repeat(places)) };
+            CellKind::Number(Format::new().set_num_format(format))
+        };
+        let exact = column.size_bytes <= MAX_EXACT_DIGITS;
+        match &column.data_type {
+            // Zero-padded, and Excel would strip the padding from a number
+            DataType::UniqueId => CellKind::Text,
+            DataType::Number if exact => decimals(0),
+            DataType::Range { min, max } if min.unsigned_abs().max(max.unsigned_abs()) < 10u64.pow(MAX_EXACT_DIGITS as u32) => {
+                decimals(0)
+            }
+            DataType::Decimal { scale } if exact => decimals(*scale),
+            DataType::Series(spec) if exact => decimals(spec.precision),
+            DataType::Date => CellKind::DateTime(Format::new().set_num_format("yyyy-mm-dd")),
+            DataType::Timestamp(spec) => match spec.format {
+                TimestampFormat::UnixSeconds | TimestampFormat::UnixMillis => decimals(0),
+                TimestampFormat::Iso8601Millis => CellKind::DateTime(Format::new().set_num_format("yyyy-mm-dd hh:mm:ss.000")),
+                TimestampFormat::Iso8601 | TimestampFormat::DateTime => {
+                    CellKind::DateTime(Format::new().set_num_format("yyyy-mm-dd hh:mm:ss"))
+                }
+            },
+            _ => CellKind::Text,
+        }
+    }
+}
+
+/// Writes generated tables to an XLSX workbook, one worksheet per table.
+///
+/// Cells are typed by column: IDs and long digit strings stay text so Excel keeps their
+/// zeros and digits, numbers are numbers with the column's decimal places, and dates and
+/// timestamps are Excel dates. Null values are blank cells. Worksheets stream their rows to
+/// temporary files, so memory stays flat however many rows are written. A table with more
+/// rows than a worksheet holds continues on `name (2)`, `name (3)` and so on, each with
+/// the header.
+pub struct XlsxWriter {
+    workbook: Workbook,
+    rows_per_sheet: usize,
+    sheet_names: Vec<String>,
+    progress: Progress,
+}
+
+impl Default for XlsxWriter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl XlsxWriter {
+    pub fn new() -> Self {
+        Self {
+            workbook: Workbook::new(),
+            rows_per_sheet: EXCEL_MAX_ROWS - 1,
+            sheet_names: Vec::new(),
+            progress: Progress::default(),
+        }
+    }
+
+    /// Starts a new worksheet after `rows` data rows; capped at Excel's limit.
+    pub fn with_rows_per_sheet(mut self, rows: usize) -> Self {
+        self.rows_per_sheet = rows.clamp(1, EXCEL_MAX_ROWS - 1);
+        self
+    }
+
+    /// Reports each sheet's progress as `progress` says instead of with a bar.
+    pub fn with_progress(mut self, progress: Progress) -> Self {
+        self.progress = progress;
+        self
+    }
+
+    /// Records `unix_seconds` as the workbook's creation time instead of the current time, so
+    /// the same sheets always produce the same file.
+    pub fn with_creation_time(mut self, unix_seconds: i64) -> Result<Self> {
+        let created = ExcelDateTime::from_timestamp(unix_seconds)?;
+        self.workbook.set_properties(&DocProperties::new().set_creation_datetime(&created));
+        Ok(self)
+    }
+
+    /// Names of the worksheets written so far.
+    pub fn sheet_names(&self) -> &[String] {
+        &self.sheet_names
+    }
+
+    /// Writes a table, returning the number of data rows.
+    pub fn write_sheet(&mut self, mut sheet: XlsxSheet) -> Result<usize> {
+        let columns = sheet.rows.schema().columns.clone();
+        if columns.len() > EXCEL_MAX_COLUMNS {
+            bail!("Excel worksheets hold at most {} columns, the schema has {}", EXCEL_MAX_COLUMNS, columns.len());
+        }
+        let kinds: Vec<CellKind> = columns.iter().map(CellKind::for_column).collect();
+        let header = sheet.rows.header();
+
+        let mut progress = self.progress.start(Some(sheet.num_rows as u64), None);
+        let mut written = 0;
+        let mut part = 1;
+        loop {
+            let name = self.sheet_name(&sheet.name, part);
+            let worksheet = self.workbook.add_worksheet_with_constant_memory();
+            worksheet.set_name(&name)?;
+            self.sheet_names.push(name);
+            for (col, name) in header.iter().enumerate() {
+                worksheet.write_string(0, col as u16, name)?;
+            }
+
+            let rows = self.rows_per_sheet.min(sheet.num_rows - written);
+            for (i, row) in sheet.rows.by_ref().take(rows).enumerate() {
+                for (col, (value, kind)) in row.iter().zip(&kinds).enumerate() {
+                    write_cell(worksheet, i as u32 + 1, col as u16, value, kind)
+                        .with_context(|| format!("Cannot write column {} of row {}", header[col], written + i + 1))?;
+                }
+                progress.inc(1, 0);
+            }
+            written += rows;
+            part += 1;
+            if written >= sheet.num_rows {
+                break;
+            }
+        }
+        progress.finish();
+
+        Ok(written)
+    }
+
+    pub fn save(mut self, path: impl AsRef<Path>) -> Result<()> {
+        self.workbook.save(path)?;
+        Ok(())
+    }
+
+    /// A valid, unused sheet name: Excel forbids `[]:*?/\`, names over 31 characters and
+    /// duplicates, ignoring case.
+    fn sheet_name(&self, base: &str, part: usize) -> String {
+        let clean: String = base.chars().map(|c| if "[]:*?/\\".contains(c) { '_' } else { c }).collect();
+        let clean = clean.trim_matches('\'');
+        let clean = if clean.is_empty() { "Sheet" } else { clean };
+        let mut attempt = part;
+        loop {
+            let suffix = if attempt == 1 { String::new() } else { format!(" ({})", attempt) };
+            let stem: String = clean.chars().take(MAX_SHEET_NAME - suffix.len()).collect();
+            let name = format!("{}{}", stem, suffix);
+            if !self.sheet_names.iter().any(|used| used.eq_ignore_ascii_case(&name)) {
+                return name;
+            }
+            attempt += 1;
+        }
+    }
+}
+
+fn write_cell(worksheet: &mut Worksheet, row: u32, col: u16, value: &str, kind: &CellKind) -> Result<()> {
+    if value.is_empty() {
+        return Ok(());
+    }
+    match kind {
+        CellKind::Number(format) => {
+            if let Ok(number) = value.parse::<f64>() {
+                worksheet.write_number_with_format(row, col, number, format)?;
+                return Ok(());
+            }
+        }
+        CellKind::DateTime(format) => {
+            // Years before 1900 have no Excel date; those stay text
+            if let Ok(datetime) = ExcelDateTime::parse_from_str(value) {
+                worksheet.write_datetime_with_format(row, col, &datetime, format)?;
+                return Ok(());
+            }
+        }
+        CellKind::Text => {}
+    }
+    worksheet.write_string(row, col, value)?;
+    Ok(())
+}
@@ -1,5 +1,20 @@
+//! Generates CSV files of a precise size and row count.
+//!
+//! The library entry point is [`CsvGen::builder`]; the modules below expose the planner,
+//! value generators and writers it is built on.
+
 pub mod cli;
 pub mod generator;
 pub mod data;
 pub mod config;
-pub mod utils;
\ No newline at end of file
+pub mod utils;
+pub mod builder;
+pub mod error;
+pub mod record;
+pub mod server;
+pub mod manifest;
+pub mod report;
+
+pub use builder::{CsvGen, CsvGenBuilder};
+pub use error::{Error, Result};
+pub use csv_gen_derive::CsvGen;
@@ -1,37 +1,449 @@
-use clap::Parser;
-use csv_gen::{cli::Args, data::schema::SchemaBuilder, generator::size_based::CsvGenerator, utils::file_ops};
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{SystemTime, UNIX_EPOCH};
+use csv_gen::{
+    cli::{Args, Command, GenerateArgs, GlobalArgs, InferArgs, MaskArgs, OutputFormat, PlanArgs, ReproduceArgs, RowRange, SchemaCommand, SchemaExportArgs, ServeArgs, VerifyArgs},
+    data::{inference, planner::{self, Plan, Solved}, types::{CsvSchema, SchemaSpec}, verify::Verifier},
+    generator::{fixed_width::FixedWidthGenerator, mask::CsvMasker, size_based::CsvGenerator, sql::SqlGenerator},
+    manifest::{self, Manifest},
+    report::{Report, ReportFormat},
+    server::{self, ServeOptions, Server},
+    utils::{file_ops, progress::Progress, units},
+};
+
+static QUIET: AtomicBool = AtomicBool::new(false);
+
+/// Prints a status message unless `--quiet` is given.
+macro_rules! status {
+    ($($arg:tt)*) => {
+        if !QUIET.load(Ordering::Relaxed) {
+            println!($($arg)*);
+        }
+    };
+}
 
 fn main() -> anyhow::Result<()> {
-    let args = Args::parse();
-    
+    let args = Args::try_parse_args_from(std::env::args_os()).unwrap_or_else(|e| e.exit());
+    if args.global.quiet {
+        QUIET.store(true, Ordering::Relaxed);
+    }
+
+    match args.command {
+        Some(Command::Generate(generate_args)) => generate(&generate_args, &args.global),
+        Some(Command::Plan(plan_args)) => show_plan(&plan_args, &args.global),
+        Some(Command::Infer(infer_args)) => infer(&infer_args),
+        Some(Command::Mask(mask_args)) => mask(&mask_args),
+        Some(Command::Serve(serve_args)) => serve(&serve_args),
+        Some(Command::Verify(verify_args)) => verify(&verify_args),
+        Some(Command::Reproduce(reproduce_args)) => reproduce(&reproduce_args, &args.global),
+        Some(Command::Schema(schema_args)) => match schema_args.command {
+            SchemaCommand::Validate(validate_args) => validate_schema(&validate_args.input),
+            SchemaCommand::Export(export_args) => export_schema(&export_args, &args.global),
+        },
+        None => generate(&args.generate, &args.global),
+    }
+}
+
+/// Plans the rows and columns for the global output format.
+fn make_plan(args: &PlanArgs, global: &GlobalArgs) -> anyhow::Result<Plan> {
     let target_size = args.parse_size()?;
-    let num_rows = args.rows;
-    
-    println!("Generating CSV: {} with {} rows", args.size, num_rows);
-    
-    let schema = SchemaBuilder::build_schema(
-        target_size,
-        num_rows,
-        args.min_columns,
-        args.max_columns,
-        args.email_columns,
-        args.domain_columns,
-    )?;
-    
-    println!("Schema: {} columns, target row size: {} bytes", 
-             schema.columns.len(), 
-             schema.target_row_size);
-    
-    file_ops::ensure_directory_exists(&args.output)?;
-    
-    let mut generator = CsvGenerator::new(schema);
-    let rows_generated = generator.generate(&args.output, num_rows)?;
+
+    let plan = match &args.schema {
+        Some(schema_path) => {
+            let mut spec = SchemaSpec::load_from_file(schema_path)?;
+            // Inferred schemas keep their own names unless a strategy is asked for
+            if let Some(headers) = &args.header_strategy {
+                spec.rename_columns(headers)?;
+            }
+            planner::plan_from_spec(&spec, target_size, args.rows)?
+        }
+        None => planner::plan_generated(target_size, args.rows, &args.column_options(&global.generator_config()?)?)?,
+    };
+    // No separators or header, so the same size holds more rows
+    if global.format == OutputFormat::FixedWidth {
+        Ok(planner::plan_fixed_width(plan)?)
+    } else {
+        Ok(plan)
+    }
+}
+
+fn generate(args: &GenerateArgs, global: &GlobalArgs) -> anyhow::Result<()> {
+    let plan = make_plan(&args.plan, global)?;
+    let num_rows = plan.num_rows;
+    // Reports measure accuracy against the size asked for
+    let target_size = plan.requested_size.unwrap_or(plan.target_size);
+
+    match plan.solved {
+        Solved::Nothing => status!("Generating CSV: {} with {} rows", units::format_size(plan.target_size as u64), num_rows),
+        Solved::Rows => status!("Generating CSV: {} with {} rows (row count derived from size)",
+                                units::format_size(plan.target_size as u64), num_rows),
+        Solved::Size => status!("Generating CSV: {} rows, expected size {} (derived from rows)",
+                                num_rows, units::format_size(plan.target_size as u64)),
+    }
+
+    let schema = plan.schema;
+    status!("Schema: {} columns, target row size: {} bytes", 
+            schema.columns.len(), 
+            schema.target_row_size);
+
+    let seed = global.seed.unwrap_or_else(rand::random);
+    let created_at = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs() as i64;
+    let (rows_generated, report) = write_output(args, global.format, &global.progress(), schema.clone(), num_rows, seed, created_at)?;
+    if args.feed.follow {
+        // The output may be a pipe, and a file that was followed has no fixed content
+        status!("Wrote {} rows to {} before the reader went away", rows_generated, args.output);
+        if let Some(report) = report {
+            write_report(args, &report)?;
+        }
+        return Ok(());
+    }
     
     let actual_size = file_ops::get_file_size(&args.output)?;
-    println!("Generated {} rows in {} ({} bytes)", 
-             rows_generated, 
-             args.output, 
-             actual_size);
+    status!("Generated {} rows in {} ({}, {} bytes)", 
+            rows_generated, 
+            args.output, 
+            units::format_size(actual_size as u64),
+            actual_size);
+    if let Some(report) = report {
+        // A range is only part of the planned size
+        let report = if args.row_range.is_some() { report } else { report.with_target(target_size as u64) };
+        write_report(args, &report)?;
+    }
+
+    if let Some(manifest_path) = args.manifest_path() {
+        let (sha256, columns) = match global.format {
+            OutputFormat::Csv => manifest::summarize_csv(&args.output, &schema)?,
+            _ => (manifest::sha256_file(&args.output)?, manifest::summarize_rows(&schema, seed, rows_generated)),
+        };
+        let manifest = Manifest {
+            version: manifest::VERSION.to_string(),
+            created_at,
+            args: std::env::args_os().map(|arg| arg.to_string_lossy().into_owned()).collect(),
+            seed,
+            format: global.format.as_str().to_string(),
+            output: args.output.clone(),
+            rows: rows_generated,
+            planned_rows: Some(num_rows),
+            row_range: args.row_range.map(|range| range.resolve(num_rows)).transpose()?,
+            bytes: actual_size as u64,
+            sha256,
+            schema,
+            columns,
+        };
+        file_ops::ensure_directory_exists(&manifest_path)?;
+        manifest.save_to_file(&manifest_path)?;
+        status!("Wrote manifest to {} (seed {})", manifest_path, seed);
+    }
     
     Ok(())
 }
+
+fn show_plan(args: &PlanArgs, global: &GlobalArgs) -> anyhow::Result<()> {
+    let plan = make_plan(args, global)?;
+    let derived = match plan.solved {
+        Solved::Nothing => "",
+        Solved::Rows => " (row count derived from size)",
+        Solved::Size => " (size derived from rows)",
+    };
+    println!("{} rows, {} ({} bytes){}", plan.num_rows, units::format_size(plan.target_size as u64), plan.target_size, derived);
+    println!("{} columns, target row size: {} bytes, header: {} bytes",
+             plan.schema.columns.len(),
+             plan.schema.target_row_size,
+             plan.schema.header_size);
+    for column in &plan.schema.columns {
+        println!("  {}: {:?}, {} bytes{}",
+                 column.name,
+                 column.data_type,
+                 column.size_bytes,
+                 if column.null_rate > 0.0 { format!(", {:.1}% null", column.null_rate * 100.0) } else { String::new() });
+    }
+    Ok(())
+}
+
+/// Prints the `--report`, or writes it to `--report-output`.
+fn write_report(args: &GenerateArgs, report: &Report) -> anyhow::Result<()> {
+    let content = match args.report {
+        Some(ReportFormat::Json) => report.to_json()? + "\n",
+        _ => report.to_table(),
+    };
+    match &args.report_output {
+        Some(path) => {
+            file_ops::ensure_directory_exists(path)?;
+            std::fs::write(path, content)?;
+            status!("Wrote report to {}", path);
+        }
+        None => print!("{}", content),
+    }
+    Ok(())
+}
+
+/// Writes `num_rows` rows of `schema` to `args.output` in `format`, with statistics of
+/// them if `--report` is given.
+fn write_output(args: &GenerateArgs, format: OutputFormat, progress: &Progress, schema: CsvSchema, num_rows: usize, seed: u64, created_at: i64) -> anyhow::Result<(usize, Option<Report>)> {
+    file_ops::ensure_directory_exists(&args.output)?;
+    if format != OutputFormat::Csv && args.duplicate_config().is_some() {
+        anyhow::bail!("Duplicate injection is only supported for CSV output");
+    }
+    if format != OutputFormat::Csv && (args.feed.rate.is_some() || args.feed.follow) {
+        anyhow::bail!("--rate and --follow are only supported for CSV output");
+    }
+    if format != OutputFormat::Csv && args.report.is_some() {
+        anyhow::bail!("--report is only supported for CSV output");
+    }
+    if format != OutputFormat::Csv && (args.row_range.is_some() || args.threads > 1) {
+        anyhow::bail!("--row-range and --threads are only supported for CSV output");
+    }
+
+    let rows = match format {
+        OutputFormat::Csv => {
+            let mut generator = CsvGenerator::new(schema)
+                .with_seed(seed)
+                .with_progress(progress.clone())
+                .with_threads(args.threads);
+            if let Some(duplicates) = args.duplicate_config() {
+                status!("Injecting duplicates; ground truth in {}", duplicates.ground_truth_path);
+                generator = generator.with_duplicates(duplicates);
+            }
+            if let Some(rate) = args.feed.rate {
+                generator = generator.with_rate(rate, args.feed.burst);
+            }
+            if args.report.is_some() {
+                generator = generator.with_report();
+            }
+            let rows = if args.feed.follow {
+                status!("Following: writing rows to {} until interrupted", args.output);
+                generator.follow(std::fs::File::create(&args.output)?)?
+            } else if let Some(range) = args.row_range {
+                generator.generate_range(&args.output, range.resolve(num_rows)?)?
+            } else {
+                generator.generate(&args.output, num_rows)?
+            };
+            return Ok((rows, generator.report().cloned()));
+        }
+        OutputFormat::Sql => {
+            let table = args.sql.table_name(&args.output);
+            SqlGenerator::new(schema, args.sql.dialect, table)
+                .with_seed(seed)
+                .with_statements(args.sql.statements())
+                .with_progress(progress.clone())
+                .generate(&args.output, num_rows)?
+        }
+        OutputFormat::FixedWidth => {
+            let mut generator = FixedWidthGenerator::new(schema).with_seed(seed).with_progress(progress.clone());
+            let layout_path = args.layout_path();
+            file_ops::ensure_directory_exists(&layout_path)?;
+            std::fs::write(&layout_path, generator.copybook(&format!("{}-record", args.sql.sheet_name(&args.output))))?;
+            status!("Wrote {}-byte record layout to {}", generator.record_len(), layout_path);
+            generator.generate(&args.output, num_rows)?
+        }
+        OutputFormat::Sqlite => write_sqlite(args, progress, schema, num_rows, seed)?,
+        OutputFormat::Xlsx => write_xlsx(args, progress, schema, num_rows, seed, created_at)?,
+    };
+    Ok((rows, None))
+}
+
+#[cfg(feature = "sqlite")]
+fn write_sqlite(args: &GenerateArgs, progress: &Progress, schema: CsvSchema, num_rows: usize, seed: u64) -> anyhow::Result<usize> {
+    use csv_gen::generator::sqlite::{SqliteSink, SqliteTable};
+
+    let table_name = args.sql.table_name(&args.output);
+    status!("Writing table {} to {}", table_name, args.output);
+    let mut table = SqliteTable::new(table_name, schema, num_rows).with_seed(seed);
+    for column in &args.sql.indexes {
+        table = table.with_index(column);
+    }
+    SqliteSink::open(&args.output)?.with_progress(progress.clone()).write_table(table)
+}
+
+#[cfg(not(feature = "sqlite"))]
+fn write_sqlite(_args: &GenerateArgs, _progress: &Progress, _schema: CsvSchema, _num_rows: usize, _seed: u64) -> anyhow::Result<usize> {
+    anyhow::bail!("SQLite output needs csvgen built with the sqlite feature")
+}
+
+#[cfg(feature = "xlsx")]
+fn write_xlsx(args: &GenerateArgs, progress: &Progress, schema: CsvSchema, num_rows: usize, seed: u64, created_at: i64) -> anyhow::Result<usize> {
+    use csv_gen::generator::xlsx::{XlsxSheet, XlsxWriter};
+
+    let mut writer = XlsxWriter::new().with_creation_time(created_at)?.with_progress(progress.clone());
+    if let Some(rows) = args.sql.sheet_rows {
+        writer = writer.with_rows_per_sheet(rows);
+    }
+    let rows = writer.write_sheet(XlsxSheet::new(args.sql.sheet_name(&args.output), schema, num_rows).with_seed(seed))?;
+    if writer.sheet_names().len() > 1 {
+        status!("Rows continue across sheets: {}", writer.sheet_names().join(", "));
+    }
+    writer.save(&args.output)?;
+    Ok(rows)
+}
+
+#[cfg(not(feature = "xlsx"))]
+fn write_xlsx(_args: &GenerateArgs, _progress: &Progress, _schema: CsvSchema, _num_rows: usize, _seed: u64, _created_at: i64) -> anyhow::Result<usize> {
+    anyhow::bail!("XLSX output needs csvgen built with the xlsx feature")
+}
+
+fn infer(args: &InferArgs) -> anyhow::Result<()> {
+    let spec = inference::infer_schema_from_file(&args.input, args.max_enum_values)?;
+
+    status!("Inferred {} columns from {} sample rows", spec.columns.len(), spec.sample_rows);
+    for column in &spec.columns {
+        status!("  {}: {:?}, {}-{} bytes, {:.1}% null{}",
+                column.name,
+                column.data_type,
+                column.min_len,
+                column.max_len,
+                column.null_rate * 100.0,
+                if column.unique { ", unique" } else { "" });
+    }
+
+    file_ops::ensure_directory_exists(&args.output)?;
+    spec.save_to_file(&args.output)?;
+    status!("Wrote schema to {}", args.output);
+
+    Ok(())
+}
+
+fn mask(args: &MaskArgs) -> anyhow::Result<()> {
+    file_ops::ensure_directory_exists(&args.output)?;
+
+    let mut masker = CsvMasker::new(args.columns.clone(), args.key.clone());
+    let rows_masked = masker.mask_file(&args.input, &args.output)?;
+
+    status!("Masked {} columns in {} rows of {} into {}",
+            args.columns.len(),
+            rows_masked,
+            args.input,
+            args.output);
+
+    Ok(())
+}
+
+fn serve(args: &ServeArgs) -> anyhow::Result<()> {
+    let options = ServeOptions {
+        schema_dir: args.schema_dir.clone().into(),
+        latency: server::parse_latency(&args.latency)?,
+        throttle: args.throttle.as_deref().map(server::parse_throttle).transpose()?,
+        max_rows: args.max_rows,
+        max_size: args.max_size,
+    };
+
+    let throttle = options.throttle;
+    let server = Server::bind((args.host.as_str(), args.port), options)?;
+    println!("Serving generated CSV on http://{}/generate?rows=1000", server.local_addr()?);
+    if let Some(throttle) = throttle {
+        status!("Response bodies are throttled to {}", units::format_rate(throttle as f64));
+    }
+    server.run();
+    Ok(())
+}
+
+fn verify(args: &VerifyArgs) -> anyhow::Result<()> {
+    let mut verifier = Verifier::new(SchemaSpec::load_from_file(&args.schema)?)
+        .with_size_tolerance(args.size_tolerance)
+        .with_max_violations(args.max_violations);
+    if let Some(rows) = args.rows {
+        verifier = verifier.with_rows(rows);
+    }
+    if let Some(size) = &args.size {
+        verifier = verifier.with_size(units::parse_size(size)?);
+    }
+    if let Some(limit) = args.unique_limit {
+        verifier = verifier.with_unique_limit(limit);
+    }
+
+    let report = verifier.verify_file(&args.input)?;
+    status!("Checked {} rows ({}) of {} against {}", report.rows, units::format_size(report.bytes), args.input, args.schema);
+    for violation in &report.violations {
+        println!("  {}", violation);
+    }
+    if report.total_violations > report.violations.len() {
+        println!("  ... and {} more", report.total_violations - report.violations.len());
+    }
+
+    if !report.passed() {
+        anyhow::bail!("{} violations in {}", report.total_violations, args.input);
+    }
+    status!("{} matches the schema", args.input);
+    Ok(())
+}
+
+fn reproduce(args: &ReproduceArgs, global: &GlobalArgs) -> anyhow::Result<()> {
+    let manifest = Manifest::load_from_file(&args.manifest)?;
+    if manifest.version != manifest::VERSION {
+        println!("Warning: {} was written by csvgen {}, this is {}", args.manifest, manifest.version, manifest::VERSION);
+    }
+    // Manifests written before commands existed record the generate options on their own
+    let Some((mut original, recorded)) = Args::try_parse_args_from(&manifest.args)?.into_generate() else {
+        anyhow::bail!("{} does not record a generate command", args.manifest);
+    };
+    // Without --output, check a copy under the same name (table and sidecar names come from it)
+    // in a directory beside the original, so a mismatch can't destroy the file being checked
+    let original_path = Path::new(&original.output).to_path_buf();
+    let scratch = match &args.output {
+        Some(output) => {
+            original.output = output.clone();
+            None
+        }
+        None => {
+            let name = original_path.file_name().ok_or_else(|| anyhow::anyhow!("{} is not a file", original.output))?;
+            let scratch = original_path.with_file_name(format!(".{}.reproduce", name.to_string_lossy()));
+            if scratch.exists() {
+                // Left by an earlier mismatch
+                std::fs::remove_dir_all(&scratch)?;
+            }
+            original.output = scratch.join(name).to_string_lossy().into_owned();
+            Some(scratch)
+        }
+    };
+    // Pacing doesn't change the content, and the statistics are the original run's
+    original.feed.rate = None;
+    original.report = None;
+    // A range is of the planned rows, not of the rows written
+    let planned_rows = manifest.planned_rows.unwrap_or(manifest.rows);
+    if let Some(range) = &manifest.row_range {
+        original.row_range = Some(RowRange { start: range.start, end: Some(range.end) });
+    }
+
+    status!("Reproducing {} rows of {} with seed {}", manifest.rows, original.output, manifest.seed);
+    write_output(&original, recorded.format, &global.progress(), manifest.schema, planned_rows, manifest.seed, manifest.created_at)?;
+
+    let sha256 = manifest::sha256_file(&original.output)?;
+    if sha256 != manifest.sha256 {
+        anyhow::bail!("Checksum mismatch for {}: expected {}, got {}", original.output, manifest.sha256, sha256);
+    }
+    if let Some(scratch) = scratch {
+        // The file and any sidecars named after it go back beside the original
+        for entry in std::fs::read_dir(&scratch)? {
+            let entry = entry?;
+            std::fs::rename(entry.path(), original_path.with_file_name(entry.file_name()))?;
+        }
+        std::fs::remove_dir(&scratch)?;
+        original.output = original_path.to_string_lossy().into_owned();
+    }
+    status!("Checksum matches: {} is identical ({})", original.output, sha256);
+    Ok(())
+}
+
+fn validate_schema(path: &str) -> anyhow::Result<()> {
+    let problems = SchemaSpec::load_from_file(path)?.validate();
+    for problem in &problems {
+        println!("  {}", problem);
+    }
+    if !problems.is_empty() {
+        anyhow::bail!("{} problems in {}", problems.len(), path);
+    }
+    status!("{} is a valid schema", path);
+    Ok(())
+}
+
+fn export_schema(args: &SchemaExportArgs, global: &GlobalArgs) -> anyhow::Result<()> {
+    let spec = SchemaSpec::from_schema(&make_plan(&args.plan, global)?.schema);
+    match &args.output {
+        Some(path) => {
+            file_ops::ensure_directory_exists(path)?;
+            spec.save_to_file(path)?;
+            status!("Wrote {} columns to {}", spec.columns.len(), path);
+        }
+        None => println!("{}", serde_json::to_string_pretty(&spec)?),
+    }
+    Ok(())
+}
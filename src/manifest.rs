@@ -0,0 +1,160 @@
+use crate::data::generators::DataGenerator;
+use crate::data::types::{ColumnConfig, CsvSchema};
+use crate::generator::rows::RowGenerator;
+use anyhow::Result;
+use csv::ReaderBuilder;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::fs::{self, File};
+use std::io::{self, Read};
+use std::ops::Range;
+
+/// Version of csvgen recorded in manifests.
+pub const VERSION: &str = env!("CARGO_PKG_VERSION");
+
+/// How an output file was made, written next to it so the file can be checked and rebuilt.
+///
+/// The schema is the planned one, not the arguments it was planned from, so a manifest
+/// reproduces its file even if a later version plans differently.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Manifest {
+    /// csvgen version that wrote the file
+    pub version: String,
+    /// Unix seconds when the file was written
+    pub created_at: i64,
+    /// Command line, program name first
+    pub args: Vec<String>,
+    pub seed: u64,
+    pub format: String,
+    pub output: String,
+    /// Rows written
+    pub rows: usize,
+    /// Rows the file was planned at; more than `rows` when only `row_range` was written
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub planned_rows: Option<usize>,
+    /// The planned rows written, from a `--row-range`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub row_range: Option<Range<usize>>,
+    pub bytes: u64,
+    /// Hex-encoded SHA-256 of the output file
+    pub sha256: String,
+    pub schema: CsvSchema,
+    pub columns: Vec<ColumnSummary>,
+}
+
+impl Manifest {
+    pub fn load_from_file(path: &str) -> Result<Self> {
+        let content = fs::read_to_string(path)?;
+        Ok(serde_json::from_str(&content)?)
+    }
+
+    pub fn save_to_file(&self, path: &str) -> Result<()> {
+        fs::write(path, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+}
+
+/// Summary statistics of one column's values.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ColumnSummary {
+    pub name: String,
+    /// Empty values
+    pub nulls: usize,
+    /// Non-empty values
+    pub values: usize,
+    pub min_len: usize,
+    pub max_len: usize,
+    pub mean_len: f64,
+    /// Smallest value of a numeric column
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub min: Option<f64>,
+    /// Largest value of a numeric column
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max: Option<f64>,
+    /// Sum of a numeric column's values
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sum: Option<f64>,
+    #[serde(skip)]
+    total_len: usize,
+    #[serde(skip)]
+    numeric: bool,
+}
+
+impl ColumnSummary {
+    pub fn new(column: &ColumnConfig) -> Self {
+        Self {
+            name: column.name.clone(),
+            numeric: column.data_type.is_numeric(),
+            ..Self::default()
+        }
+    }
+
+    pub fn observe(&mut self, value: &str) {
+        if value.is_empty() {
+            self.nulls += 1;
+            return;
+        }
+        self.min_len = if self.values == 0 { value.len() } else { self.min_len.min(value.len()) };
+        self.max_len = self.max_len.max(value.len());
+        self.values += 1;
+        self.total_len += value.len();
+        self.mean_len = self.total_len as f64 / self.values as f64;
+
+        if self.numeric
+            && let Ok(number) = value.parse::<f64>()
+        {
+            self.min = Some(self.min.map_or(number, |min| min.min(number)));
+            self.max = Some(self.max.map_or(number, |max| max.max(number)));
+            self.sum = Some(self.sum.unwrap_or(0.0) + number);
+        }
+    }
+}
+
+/// Hex-encoded SHA-256 of a file.
+pub fn sha256_file(path: &str) -> Result<String> {
+    let mut hasher = Sha256::new();
+    io::copy(&mut File::open(path)?, &mut hasher)?;
+    Ok(format!("{:x}", hasher.finalize()))
+}
+
+/// Reads a headed CSV once for both its SHA-256 and its column summaries.
+pub fn summarize_csv(path: &str, schema: &CsvSchema) -> Result<(String, Vec<ColumnSummary>)> {
+    let mut hashing = HashingReader { inner: File::open(path)?, hasher: Sha256::new() };
+    let mut summaries: Vec<ColumnSummary> = schema.columns.iter().map(ColumnSummary::new).collect();
+    {
+        let mut reader = ReaderBuilder::new().from_reader(&mut hashing);
+        for record in reader.records() {
+            for (summary, value) in summaries.iter_mut().zip(record?.iter()) {
+                summary.observe(value);
+            }
+        }
+    }
+    // The CSV reader stops at the last record; hash anything after it
+    io::copy(&mut hashing, &mut io::sink())?;
+    Ok((format!("{:x}", hashing.hasher.finalize()), summaries))
+}
+
+/// Column summaries of the first `num_rows` rows `schema` generates with `seed`: the rows of
+/// any output written with that seed, whatever its format.
+pub fn summarize_rows(schema: &CsvSchema, seed: u64, num_rows: usize) -> Vec<ColumnSummary> {
+    let mut summaries: Vec<ColumnSummary> = schema.columns.iter().map(ColumnSummary::new).collect();
+    for row in RowGenerator::new(schema.clone(), DataGenerator::with_seed(seed)).take(num_rows) {
+        for (summary, value) in summaries.iter_mut().zip(&row) {
+            summary.observe(value);
+        }
+    }
+    summaries
+}
+
+struct HashingReader<R> {
+    inner: R,
+    hasher: Sha256,
+}
+
+impl<R: Read> Read for HashingReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let read = self.inner.read(buf)?;
+        self.hasher.update(&buf[..read]);
+        Ok(read)
+    }
+}
@@ -0,0 +1,97 @@
+use crate::builder::CsvGen;
+use crate::data::types::{CsvSchema, SchemaSpec};
+use crate::error::{Error, Result};
+use std::fmt::Display;
+use std::str::FromStr;
+
+/// A struct whose instances can be generated, usually via `#[derive(CsvGen)]`.
+///
+/// Each field is one column, in declaration order. Field attributes pick the generated data:
+///
+/// - `#[csvgen(unique)]`: sequential IDs
+/// - `#[csvgen(email)]`, `#[csvgen(domain)]`, `#[csvgen(name)]`, `#[csvgen(date)]`
+/// - `#[csvgen(range = 1..100)]` or `1..=100`: uniform integers
+/// - `#[csvgen(pattern = "[A-Z]{3}-\\d{4}")]`: strings matching a regex
+/// - `#[csvgen(one_of = "a,b,c")]`: one of a fixed set of values
+/// - `#[csvgen(width = 20)]`, `#[csvgen(null_rate = 0.1)]` (nulls need an `Option` field),
+///   `#[csvgen(rename = "column")]`
+///
+/// Without attributes, integers span their type's range (up to `i64`), floats get two decimal
+/// places, `bool`s are `true` or `false`, and anything else is free text parsed with `FromStr`.
+///
+/// ```
+/// use csv_gen::CsvGen;
+/// use csv_gen::record::CsvRecord;
+///
+/// #[derive(CsvGen)]
+/// struct Person {
+///     #[csvgen(unique)]
+///     id: u64,
+///     #[csvgen(email)]
+///     email: String,
+///     #[csvgen(range = 18..100)]
+///     age: u8,
+///     #[csvgen(null_rate = 0.2)]
+///     nickname: Option<String>,
+/// }
+///
+/// let people = Person::fake_seeded(10, 7)?;
+/// assert_eq!(people[9].id, 10);
+/// assert!((18..100).contains(&people[0].age));
+/// # Ok::<(), csv_gen::Error>(())
+/// ```
+pub trait CsvRecord: Sized {
+    /// One column per field. Fails if a `pattern` attribute is not a valid regex.
+    fn schema_spec() -> Result<SchemaSpec>;
+
+    /// Builds an instance from a row whose values are in field order.
+    fn from_row(row: &[String]) -> Result<Self>;
+
+    /// The schema for `rows` rows at the columns' natural widths.
+    fn csv_schema(rows: usize) -> Result<CsvSchema> {
+        Ok(CsvGen::builder().schema(Self::schema_spec()?).rows(rows).build()?.schema().clone())
+    }
+
+    /// Generates `count` instances.
+    fn fake(count: usize) -> Result<Vec<Self>> {
+        CsvGen::builder().schema(Self::schema_spec()?).rows(count).build()?.records().collect()
+    }
+
+    /// Generates `count` instances reproducibly.
+    fn fake_seeded(count: usize, seed: u64) -> Result<Vec<Self>> {
+        CsvGen::builder().schema(Self::schema_spec()?).rows(count).seed(seed).build()?.records().collect()
+    }
+}
+
+impl CsvGen {
+    /// The planned rows as instances of `T`, whose fields must match the schema's columns.
+    pub fn records<T: CsvRecord>(&self) -> impl Iterator<Item = Result<T>> + use<T> {
+        self.iter().map(|row| T::from_row(&row))
+    }
+}
+
+/// Parses the value of column `index` into a field; used by `#[derive(CsvGen)]`.
+pub fn parse_field<T>(row: &[String], index: usize, name: &str) -> Result<T>
+where
+    T: FromStr,
+    T::Err: Display,
+{
+    let value = row
+        .get(index)
+        .ok_or_else(|| Error::InvalidSchema(format!("row has no value for field '{}'", name)))?;
+    value
+        .parse()
+        .map_err(|e| Error::InvalidSchema(format!("cannot parse '{}' for field '{}': {}", value, name, e)))
+}
+
+/// Like [`parse_field`], mapping empty values to `None`.
+pub fn parse_optional_field<T>(row: &[String], index: usize, name: &str) -> Result<Option<T>>
+where
+    T: FromStr,
+    T::Err: Display,
+{
+    match row.get(index) {
+        Some(value) if value.is_empty() => Ok(None),
+        _ => parse_field(row, index, name).map(Some),
+    }
+}
@@ -0,0 +1,225 @@
+//! Statistics of a written file, collected while its rows are generated.
+
+use crate::data::types::CsvSchema;
+use crate::manifest::ColumnSummary;
+use crate::utils::units;
+use anyhow::bail;
+use serde::Serialize;
+use std::collections::hash_map::DefaultHasher;
+use std::fmt::Write as _;
+use std::hash::{Hash, Hasher};
+use std::str::FromStr;
+use std::time::Instant;
+
+// 2^12 one-byte registers per column, for a standard error of about 1.6%
+const HLL_PRECISION: u32 = 12;
+
+/// Estimates the number of distinct values seen in a fixed 4 KiB, however many values there
+/// are.
+#[derive(Debug, Clone)]
+pub struct HyperLogLog {
+    registers: Vec<u8>,
+}
+
+impl Default for HyperLogLog {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl HyperLogLog {
+    pub fn new() -> Self {
+        Self { registers: vec![0; 1 << HLL_PRECISION] }
+    }
+
+    pub fn insert(&mut self, value: impl Hash) {
+        // SipHash with fixed keys, so the same values always give the same estimate
+        let mut hasher = DefaultHasher::new();
+        value.hash(&mut hasher);
+        let hash = hasher.finish();
+        let index = (hash >> (64 - HLL_PRECISION)) as usize;
+        let rank = ((hash << HLL_PRECISION).leading_zeros() + 1).min(64 - HLL_PRECISION + 1) as u8;
+        self.registers[index] = self.registers[index].max(rank);
+    }
+
+    pub fn estimate(&self) -> u64 {
+        let m = self.registers.len() as f64;
+        let alpha = 0.7213 / (1.0 + 1.079 / m);
+        let sum: f64 = self.registers.iter().map(|&rank| 2f64.powi(-i32::from(rank))).sum();
+        let raw = alpha * m * m / sum;
+        let empty = self.registers.iter().filter(|&&rank| rank == 0).count();
+        // Linear counting is more accurate while many registers are still empty
+        let estimate = if raw <= 2.5 * m && empty > 0 { m * (m / empty as f64).ln() } else { raw };
+        estimate.round() as u64
+    }
+}
+
+/// How `--report` shows the statistics.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReportFormat {
+    /// Aligned columns for reading
+    Table,
+    /// A [`Report`] as pretty-printed JSON
+    Json,
+}
+
+impl FromStr for ReportFormat {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "table" => Ok(ReportFormat::Table),
+            "json" => Ok(ReportFormat::Json),
+            _ => bail!("Unknown report format '{}'. Use table or json", s),
+        }
+    }
+}
+
+/// One column's values, as written.
+#[derive(Debug, Clone, Serialize)]
+pub struct ColumnReport {
+    #[serde(flatten)]
+    pub summary: ColumnSummary,
+    /// Estimated distinct non-empty values, within a few percent
+    pub distinct: u64,
+}
+
+/// What a write produced.
+#[derive(Debug, Clone, Serialize)]
+pub struct Report {
+    /// Data rows, duplicates included
+    pub rows: usize,
+    /// Bytes of the header line, newline included
+    pub header_bytes: u64,
+    pub data_bytes: u64,
+    pub total_bytes: u64,
+    /// Size the file was planned at, if known
+    pub target_bytes: Option<u64>,
+    /// How far the total is off the target, as a fraction of it: `0.01` is 1% over
+    pub size_error: Option<f64>,
+    pub elapsed_secs: f64,
+    pub rows_per_second: f64,
+    pub bytes_per_second: f64,
+    pub columns: Vec<ColumnReport>,
+}
+
+impl Report {
+    /// Measures the size error against `target_bytes`.
+    pub fn with_target(mut self, target_bytes: u64) -> Self {
+        self.target_bytes = Some(target_bytes);
+        self.size_error = (target_bytes > 0)
+            .then(|| (self.total_bytes as f64 - target_bytes as f64) / target_bytes as f64);
+        self
+    }
+
+    pub fn to_json(&self) -> anyhow::Result<String> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+
+    /// The totals, then a row per column.
+    pub fn to_table(&self) -> String {
+        let mut table = format!(
+            "{} rows in {:.2}s ({:.0} rows/s, {})\n",
+            self.rows,
+            self.elapsed_secs,
+            self.rows_per_second,
+            units::format_rate(self.bytes_per_second)
+        );
+        let _ = write!(
+            table,
+            "{} ({} bytes): {} header, {} data",
+            units::format_size(self.total_bytes),
+            self.total_bytes,
+            units::format_size(self.header_bytes),
+            units::format_size(self.data_bytes)
+        );
+        if let (Some(target), Some(error)) = (self.target_bytes, self.size_error) {
+            let _ = write!(table, "; target {} ({:+.2}%)", units::format_size(target), error * 100.0);
+        }
+        table.push_str("\n\n");
+
+        // Rounded to hide the float error a long sum of decimals builds up
+        let number = |value: Option<f64>| value.map_or_else(|| "-".to_string(), |v| format!("{}", (v * 1e6).round() / 1e6));
+        let mut rows = vec![["column", "distinct", "nulls", "min len", "mean len", "max len", "min", "max", "sum"].map(String::from)];
+        for column in &self.columns {
+            let summary = &column.summary;
+            rows.push([
+                summary.name.clone(),
+                column.distinct.to_string(),
+                summary.nulls.to_string(),
+                summary.min_len.to_string(),
+                format!("{:.1}", summary.mean_len),
+                summary.max_len.to_string(),
+                number(summary.min),
+                number(summary.max),
+                number(summary.sum),
+            ]);
+        }
+        let widths: Vec<usize> = (0..rows[0].len()).map(|i| rows.iter().map(|row| row[i].len()).max().unwrap_or(0)).collect();
+        for row in &rows {
+            let cells: Vec<String> = row
+                .iter()
+                .zip(&widths)
+                .enumerate()
+                // Names left-aligned, numbers right-aligned
+                .map(|(i, (cell, &width))| if i == 0 { format!("{:<width$}", cell) } else { format!("{:>width$}", cell) })
+                .collect();
+            table.push_str(cells.join("  ").trim_end());
+            table.push('\n');
+        }
+        table
+    }
+}
+
+/// Builds a [`Report`] from rows as they are written, in memory that doesn't grow with the
+/// row count.
+pub struct ReportCollector {
+    summaries: Vec<ColumnSummary>,
+    distinct: Vec<HyperLogLog>,
+    rows: usize,
+    started: Instant,
+}
+
+impl ReportCollector {
+    pub fn new(schema: &CsvSchema) -> Self {
+        Self {
+            summaries: schema.columns.iter().map(ColumnSummary::new).collect(),
+            distinct: vec![HyperLogLog::new(); schema.columns.len()],
+            rows: 0,
+            started: Instant::now(),
+        }
+    }
+
+    pub fn observe(&mut self, row: &[String]) {
+        self.rows += 1;
+        for ((summary, distinct), value) in self.summaries.iter_mut().zip(&mut self.distinct).zip(row) {
+            summary.observe(value);
+            if !value.is_empty() {
+                distinct.insert(value);
+            }
+        }
+    }
+
+    /// The report for a file of `total_bytes`, the first `header_bytes` of them the header.
+    pub fn finish(self, header_bytes: u64, total_bytes: u64) -> Report {
+        let elapsed_secs = self.started.elapsed().as_secs_f64();
+        let per_second = |count: f64| if elapsed_secs > 0.0 { count / elapsed_secs } else { 0.0 };
+        Report {
+            rows: self.rows,
+            header_bytes,
+            data_bytes: total_bytes.saturating_sub(header_bytes),
+            total_bytes,
+            target_bytes: None,
+            size_error: None,
+            elapsed_secs,
+            rows_per_second: per_second(self.rows as f64),
+            bytes_per_second: per_second(total_bytes as f64),
+            columns: self
+                .summaries
+                .into_iter()
+                .zip(&self.distinct)
+                .map(|(summary, distinct)| ColumnReport { summary, distinct: distinct.estimate() })
+                .collect(),
+        }
+    }
+}
@@ -0,0 +1,595 @@
+use crate::utils::units::{self, parse_byte_rate, parse_count, parse_size};
+use crate::data::headers::HeaderStrategy;
+use crate::data::planner::{self, ColumnOptions, Plan, SolverObjective};
+use crate::data::types::SchemaSpec;
+use crate::generator::size_based::CsvGenerator;
+use crate::utils::dates;
+use anyhow::{Context, Result, anyhow, bail};
+use std::collections::{HashMap, VecDeque};
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream, ToSocketAddrs};
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Longest request head the server reads before giving up on a client.
+const MAX_HEAD_BYTES: usize = 16 * 1024;
+
+/// Seeded datasets whose lengths are remembered; the oldest is forgotten first.
+const MAX_CACHED_DATASETS: usize = 256;
+/// A seeded dataset's offsets are remembered for every this many rows, so a range request
+/// generates at most this many rows it doesn't send.
+const CHECKPOINT_ROWS: usize = 1024;
+
+/// Largest row count a request may ask for unless configured otherwise.
+pub const DEFAULT_MAX_ROWS: usize = 10_000_000;
+/// Largest size in bytes a request may ask for unless configured otherwise.
+pub const DEFAULT_MAX_SIZE: usize = 1_000_000_000;
+
+/// Server-wide settings; `latency` and `throttle` can be overridden per request.
+#[derive(Debug, Clone)]
+pub struct ServeOptions {
+    /// Directory holding `<name>.json` schema files for `schema=<name>`
+    pub schema_dir: PathBuf,
+    /// Delay before each response
+    pub latency: Duration,
+    /// Body rate limit in bytes per second
+    pub throttle: Option<u64>,
+    /// Most rows a request may generate, given or derived
+    pub max_rows: usize,
+    /// Most bytes a request may generate, given or derived
+    pub max_size: usize,
+}
+
+impl Default for ServeOptions {
+    fn default() -> Self {
+        Self {
+            schema_dir: PathBuf::default(),
+            latency: Duration::ZERO,
+            throttle: None,
+            max_rows: DEFAULT_MAX_ROWS,
+            max_size: DEFAULT_MAX_SIZE,
+        }
+    }
+}
+
+/// Serves generated CSV over HTTP:
+///
+/// - `GET /generate?rows=1000&size=1MB&seed=42&schema=users` streams a chunked CSV. Optional
+///   parameters are `columns`, `latency` (e.g. `250ms`) and `throttle` (e.g. `64KB/s`).
+/// - With a `seed`, the same URL always returns the same bytes, so `Range` requests and `HEAD`
+///   (with a `Content-Length`) are supported. A range is generated from the row it starts in.
+/// - `GET /health` answers `ok`.
+///
+/// Each connection gets a thread and a single request. Requests over the `max_rows` or
+/// `max_size` of [`ServeOptions`] are refused.
+pub struct Server {
+    listener: TcpListener,
+    options: Arc<ServeOptions>,
+    datasets: Arc<Mutex<DatasetCache>>,
+}
+
+impl Server {
+    pub fn bind(addr: impl ToSocketAddrs, options: ServeOptions) -> Result<Self> {
+        Ok(Self {
+            listener: TcpListener::bind(addr)?,
+            options: Arc::new(options),
+            datasets: Arc::new(Mutex::new(DatasetCache::default())),
+        })
+    }
+
+    pub fn local_addr(&self) -> Result<SocketAddr> {
+        Ok(self.listener.local_addr()?)
+    }
+
+    /// Serves requests until the process exits.
+    pub fn run(self) {
+        for stream in self.listener.incoming() {
+            // Failed accepts (e.g. out of file descriptors) only lose that connection
+            let Ok(stream) = stream else { continue };
+            let options = Arc::clone(&self.options);
+            let datasets = Arc::clone(&self.datasets);
+            thread::spawn(move || {
+                // A client hanging up mid-response is not the server's problem
+                let _ = handle(stream, &options, &datasets);
+            });
+        }
+    }
+}
+
+struct Request {
+    method: String,
+    path: String,
+    query: HashMap<String, String>,
+    range: Option<String>,
+}
+
+impl Request {
+    fn read(stream: &TcpStream) -> Result<Self> {
+        let mut reader = BufReader::new(stream).take(MAX_HEAD_BYTES as u64);
+        let mut line = String::new();
+        reader.read_line(&mut line)?;
+        let mut parts = line.split_whitespace();
+        let (Some(method), Some(target)) = (parts.next(), parts.next()) else {
+            bail!("Malformed request line");
+        };
+        let (path, query) = target.split_once('?').unwrap_or((target, ""));
+        let mut request = Request {
+            method: method.to_string(),
+            path: path.to_string(),
+            query: parse_query(query),
+            range: None,
+        };
+
+        loop {
+            line.clear();
+            if reader.read_line(&mut line)? == 0 {
+                bail!("Request head too long or truncated");
+            }
+            let header = line.trim_end();
+            if header.is_empty() {
+                return Ok(request);
+            }
+            if let Some((name, value)) = header.split_once(':')
+                && name.eq_ignore_ascii_case("range")
+            {
+                request.range = Some(value.trim().to_string());
+            }
+        }
+    }
+}
+
+/// Parameters of a `/generate` request.
+struct Generate {
+    rows: Option<usize>,
+    size: Option<usize>,
+    seed: Option<u64>,
+    schema: Option<String>,
+    columns: Option<usize>,
+    latency: Duration,
+    throttle: Option<u64>,
+}
+
+impl Generate {
+    fn from_query(query: &HashMap<String, String>, options: &ServeOptions) -> Result<Self> {
+        let get = |name: &str| query.get(name).map(String::as_str);
+        let parse_number = |name: &str| -> Result<Option<u64>> {
+            get(name)
+                .map(|v| v.replace('_', "").parse().map_err(|_| anyhow!("Invalid {} '{}'", name, v)))
+                .transpose()
+        };
+        let latency = match get("latency") {
+            Some(value) => parse_latency(value)?,
+            None => options.latency,
+        };
+        let throttle = match get("throttle") {
+            Some(value) => Some(parse_throttle(value)?),
+            None => options.throttle,
+        };
+        Ok(Self {
+            rows: get("rows").map(parse_count).transpose()?,
+            size: get("size").map(parse_size).transpose()?,
+            seed: parse_number("seed")?,
+            schema: get("schema").map(String::from),
+            columns: parse_number("columns")?.map(|n| n as usize),
+            latency,
+            throttle,
+        })
+    }
+
+    fn plan(&self, options: &ServeOptions) -> Result<Plan> {
+        if self.rows == Some(0) {
+            bail!("rows must be positive");
+        }
+        // Checked before planning too, so an oversized request costs nothing
+        check_limits(self.rows, self.size, options)?;
+        let plan = match &self.schema {
+            Some(name) => {
+                if name.is_empty() || !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-') {
+                    bail!("Invalid schema name '{}'", name);
+                }
+                let path = options.schema_dir.join(format!("{}.json", name));
+                let spec = SchemaSpec::load_from_file(&path.to_string_lossy())
+                    .with_context(|| format!("Unknown schema '{}'", name))?;
+                planner::plan_from_spec(&spec, self.size, self.rows)?
+            }
+            None => {
+                let (min_columns, max_columns) = self.columns.map_or((2, 100), |n| (n, n));
+                let options = ColumnOptions {
+                    min_columns,
+                    max_columns,
+                    email_columns: 0,
+                    domain_columns: 0,
+                    fixed_columns: Vec::new(),
+                    header_strategy: HeaderStrategy::default(),
+                    objective: SolverObjective::default(),
+                };
+                planner::plan_generated(self.size, self.rows, &options)?
+            }
+        };
+        check_limits(Some(plan.num_rows), Some(plan.target_size), options)?;
+        Ok(plan)
+    }
+
+    /// Identifies the generated bytes of a seeded request.
+    fn dataset_key(&self) -> String {
+        format!("{:?}/{:?}/{:?}/{:?}/{:?}", self.rows, self.size, self.seed, self.schema, self.columns)
+    }
+}
+
+fn check_limits(rows: Option<usize>, size: Option<usize>, options: &ServeOptions) -> Result<()> {
+    if let Some(rows) = rows
+        && rows > options.max_rows
+    {
+        bail!("{} rows is over this server's limit of {}", rows, options.max_rows);
+    }
+    if let Some(size) = size
+        && size > options.max_size
+    {
+        bail!("{} is over this server's limit of {}", units::format_size(size as u64), units::format_size(options.max_size as u64));
+    }
+    Ok(())
+}
+
+/// Where the rows of a seeded dataset fall in its body.
+struct Dataset {
+    length: u64,
+    /// Offset of every [`CHECKPOINT_ROWS`]th row; the first is 0, where the header starts
+    offsets: Vec<u64>,
+}
+
+impl Dataset {
+    /// Generates the dataset once, counting its bytes.
+    fn measure(plan: &Plan, seed: u64) -> Result<Self> {
+        let mut generator = CsvGenerator::new(plan.schema.clone()).without_progress().with_seed(seed);
+        let mut counter = Counter(0);
+        let mut offsets = Vec::with_capacity(plan.num_rows.div_ceil(CHECKPOINT_ROWS));
+        for start in (0..plan.num_rows).step_by(CHECKPOINT_ROWS) {
+            offsets.push(counter.0);
+            generator.write_range(&mut counter, start..(start + CHECKPOINT_ROWS).min(plan.num_rows))?;
+        }
+        Ok(Self { length: counter.0, offsets })
+    }
+
+    /// The row to generate from for a range starting at byte `start`, and how many of its
+    /// bytes come before the range.
+    fn locate(&self, start: u64) -> (usize, u64) {
+        let checkpoint = self.offsets.partition_point(|&offset| offset <= start).saturating_sub(1);
+        (checkpoint * CHECKPOINT_ROWS, start - self.offsets.get(checkpoint).copied().unwrap_or(0))
+    }
+}
+
+/// Seeded datasets, keyed by their parameters. Holds at most [`MAX_CACHED_DATASETS`], as the
+/// keys come from clients.
+#[derive(Default)]
+struct DatasetCache {
+    datasets: HashMap<String, Arc<Dataset>>,
+    // Keys from oldest to newest
+    order: VecDeque<String>,
+}
+
+impl DatasetCache {
+    fn get(&self, key: &str) -> Option<Arc<Dataset>> {
+        self.datasets.get(key).cloned()
+    }
+
+    fn insert(&mut self, key: String, dataset: Arc<Dataset>) {
+        if self.datasets.insert(key.clone(), dataset).is_none() {
+            self.order.push_back(key);
+        }
+        while self.order.len() > MAX_CACHED_DATASETS {
+            if let Some(oldest) = self.order.pop_front() {
+                self.datasets.remove(&oldest);
+            }
+        }
+    }
+}
+
+fn handle(mut stream: TcpStream, options: &ServeOptions, datasets: &Mutex<DatasetCache>) -> Result<()> {
+    let request = match Request::read(&stream) {
+        Ok(request) => request,
+        Err(e) => return respond_text(&mut stream, 400, &e.to_string()),
+    };
+    let head_only = match request.method.as_str() {
+        "GET" => false,
+        "HEAD" => true,
+        _ => return respond_text(&mut stream, 405, "Only GET and HEAD are supported"),
+    };
+    match request.path.as_str() {
+        "/health" => respond_text(&mut stream, 200, "ok"),
+        "/generate" => {
+            let generate = match Generate::from_query(&request.query, options) {
+                Ok(generate) => generate,
+                Err(e) => return respond_text(&mut stream, 400, &e.to_string()),
+            };
+            let plan = match generate.plan(options) {
+                Ok(plan) => plan,
+                Err(e) => return respond_text(&mut stream, 400, &format!("{:#}", e)),
+            };
+            thread::sleep(generate.latency);
+            serve_csv(&mut stream, &generate, &plan, request.range.as_deref(), head_only, datasets)
+        }
+        _ => respond_text(&mut stream, 404, "Not found; try /generate?rows=1000"),
+    }
+}
+
+fn serve_csv(
+    stream: &mut TcpStream,
+    generate: &Generate,
+    plan: &Plan,
+    range: Option<&str>,
+    head_only: bool,
+    datasets: &Mutex<DatasetCache>,
+) -> Result<()> {
+    let Some(seed) = generate.seed else {
+        // Unseeded data differs on every request, so ranges cannot be honoured
+        write_head(stream, 200, &[("Content-Type", "text/csv".to_string()), ("Transfer-Encoding", "chunked".to_string())])?;
+        if head_only {
+            return Ok(());
+        }
+        let mut body = Chunked(Throttled::new(&mut *stream, generate.throttle));
+        write_csv(plan, None, 0, &mut body)?;
+        return body.finish();
+    };
+
+    let dataset = seeded_dataset(generate, plan, seed, datasets)?;
+    let total = dataset.length;
+    let mut headers = vec![("Content-Type", "text/csv".to_string()), ("Accept-Ranges", "bytes".to_string())];
+    let Some(range) = range else {
+        headers.push(("Content-Length", total.to_string()));
+        write_head(stream, 200, &headers)?;
+        if !head_only {
+            write_csv(plan, Some(seed), 0, Throttled::new(&mut *stream, generate.throttle))?;
+        }
+        return Ok(());
+    };
+
+    let Some((start, end)) = parse_range(range, total) else {
+        headers.push(("Content-Range", format!("bytes */{}", total)));
+        headers.push(("Content-Length", "0".to_string()));
+        return write_head(stream, 416, &headers);
+    };
+    headers.push(("Content-Range", format!("bytes {}-{}/{}", start, end, total)));
+    headers.push(("Content-Length", (end - start + 1).to_string()));
+    write_head(stream, 206, &headers)?;
+    if head_only {
+        return Ok(());
+    }
+
+    // Start at the nearest checkpoint, so only the rows before it in its block are skipped
+    let (first_row, skip) = dataset.locate(start);
+    let mut body = Ranged {
+        inner: Throttled::new(&mut *stream, generate.throttle),
+        skip,
+        remaining: end - start + 1,
+    };
+    match write_csv(plan, Some(seed), first_row, &mut body) {
+        // The writer stops generation once the range is sent
+        Err(_) if body.remaining == 0 => Ok(()),
+        result => result.map(|_| ()),
+    }
+}
+
+/// A seeded dataset's layout, generating it once to find out.
+fn seeded_dataset(generate: &Generate, plan: &Plan, seed: u64, datasets: &Mutex<DatasetCache>) -> Result<Arc<Dataset>> {
+    let key = generate.dataset_key();
+    if let Some(dataset) = datasets.lock().map_err(|_| anyhow!("Dataset cache poisoned"))?.get(&key) {
+        return Ok(dataset);
+    }
+    let dataset = Arc::new(Dataset::measure(plan, seed)?);
+    datasets.lock().map_err(|_| anyhow!("Dataset cache poisoned"))?.insert(key, Arc::clone(&dataset));
+    Ok(dataset)
+}
+
+/// Writes the planned rows from `first_row` on, with the header if that is row 0.
+fn write_csv<W: Write>(plan: &Plan, seed: Option<u64>, first_row: usize, output: W) -> Result<usize> {
+    let mut generator = CsvGenerator::new(plan.schema.clone()).without_progress();
+    if let Some(seed) = seed {
+        generator = generator.with_seed(seed);
+    }
+    generator.write_range(output, first_row..plan.num_rows)
+}
+
+/// Resolves a single `bytes=` range to inclusive offsets, or `None` if it cannot be satisfied.
+fn parse_range(range: &str, total: u64) -> Option<(u64, u64)> {
+    let spec = range.strip_prefix("bytes=")?.trim();
+    if spec.contains(',') {
+        return None;
+    }
+    let (start, end) = spec.split_once('-')?;
+    let (start, end) = if start.is_empty() {
+        let suffix: u64 = end.parse().ok()?;
+        (total.checked_sub(suffix.min(total))?, total.checked_sub(1)?)
+    } else {
+        let start: u64 = start.parse().ok()?;
+        let end = if end.is_empty() { total.saturating_sub(1) } else { end.parse::<u64>().ok()?.min(total.saturating_sub(1)) };
+        (start, end)
+    };
+    (start <= end && start < total).then_some((start, end))
+}
+
+/// Parses a delay such as `250ms` or `2s`.
+pub fn parse_latency(value: &str) -> Result<Duration> {
+    let millis = dates::parse_duration_ms(value)
+        .filter(|&ms| ms >= 0)
+        .ok_or_else(|| anyhow!("Invalid latency '{}'. Use e.g. 250ms or 2s", value))?;
+    Ok(Duration::from_millis(millis as u64))
+}
+
+/// Parses a rate such as `64KB/s` (or just `64KB`) into bytes per second.
+pub fn parse_throttle(value: &str) -> Result<u64> {
+    let bytes = parse_byte_rate(value)?;
+    if bytes == 0 {
+        bail!("Throttle must be positive");
+    }
+    Ok(bytes)
+}
+
+fn parse_query(query: &str) -> HashMap<String, String> {
+    query
+        .split('&')
+        .filter(|pair| !pair.is_empty())
+        .map(|pair| {
+            let (name, value) = pair.split_once('=').unwrap_or((pair, ""));
+            (percent_decode(name), percent_decode(value))
+        })
+        .collect()
+}
+
+fn percent_decode(value: &str) -> String {
+    let bytes = value.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'+' => decoded.push(b' '),
+            b'%' if let Some(byte) = value.get(i + 1..i + 3).and_then(|hex| u8::from_str_radix(hex, 16).ok()) => {
+                decoded.push(byte);
+                i += 2;
+            }
+            byte => decoded.push(byte),
+        }
+        i += 1;
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+fn write_head(stream: &mut TcpStream, status: u16, headers: &[(&str, String)]) -> Result<()> {
+    let mut head = format!("HTTP/1.1 {} {}\r\nConnection: close\r\n", status, reason(status));
+    for (name, value) in headers {
+        head.push_str(&format!("{}: {}\r\n", name, value));
+    }
+    head.push_str("\r\n");
+    stream.write_all(head.as_bytes())?;
+    Ok(())
+}
+
+fn respond_text(stream: &mut TcpStream, status: u16, body: &str) -> Result<()> {
+    let body = format!("{}\n", body);
+    write_head(
+        stream,
+        status,
+        &[("Content-Type", "text/plain".to_string()), ("Content-Length", body.len().to_string())],
+    )?;
+    stream.write_all(body.as_bytes())?;
+    Ok(())
+}
+
+fn reason(status: u16) -> &'static str {
+    match status {
+        200 => "OK",
+        206 => "Partial Content",
+        400 => "Bad Request",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        416 => "Range Not Satisfiable",
+        _ => "",
+    }
+}
+
+/// Writes each buffer as one HTTP chunk.
+struct Chunked<W: Write>(W);
+
+impl<W: Write> Chunked<W> {
+    fn finish(mut self) -> Result<()> {
+        self.0.write_all(b"0\r\n\r\n")?;
+        self.0.flush()?;
+        Ok(())
+    }
+}
+
+impl<W: Write> Write for Chunked<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if !buf.is_empty() {
+            write!(self.0, "{:x}\r\n", buf.len())?;
+            self.0.write_all(buf)?;
+            self.0.write_all(b"\r\n")?;
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.0.flush()
+    }
+}
+
+/// Passes through bytes `skip..skip + remaining`, then fails to stop the generator.
+struct Ranged<W: Write> {
+    inner: W,
+    skip: u64,
+    remaining: u64,
+}
+
+impl<W: Write> Write for Ranged<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if self.remaining == 0 {
+            return Err(io::Error::other("range complete"));
+        }
+        let skipped = self.skip.min(buf.len() as u64) as usize;
+        self.skip -= skipped as u64;
+        let rest = &buf[skipped..];
+        let taken = self.remaining.min(rest.len() as u64) as usize;
+        self.inner.write_all(&rest[..taken])?;
+        self.remaining -= taken as u64;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+/// Limits writes to a number of bytes per second.
+struct Throttled<W: Write> {
+    inner: W,
+    bytes_per_second: Option<u64>,
+    started: Instant,
+    written: u64,
+}
+
+impl<W: Write> Throttled<W> {
+    fn new(inner: W, bytes_per_second: Option<u64>) -> Self {
+        Self {
+            inner,
+            bytes_per_second,
+            started: Instant::now(),
+            written: 0,
+        }
+    }
+}
+
+impl<W: Write> Write for Throttled<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let Some(rate) = self.bytes_per_second else {
+            return self.inner.write(buf);
+        };
+        // Small slices, so a slow rate still trickles instead of stalling
+        let slice = buf.len().min((rate as usize / 10).max(1));
+        let written = self.inner.write(&buf[..slice])?;
+        self.written += written as u64;
+        let due = Duration::from_secs_f64(self.written as f64 / rate as f64);
+        if let Some(wait) = due.checked_sub(self.started.elapsed()) {
+            thread::sleep(wait);
+        }
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+/// Counts bytes instead of writing them.
+struct Counter(u64);
+
+impl Write for Counter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0 += buf.len() as u64;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
@@ -0,0 +1,123 @@
+/// Converts days since 1970-01-01 into a (year, month, day) civil date.
+pub fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    // Howard Hinnant's days_from_civil inverse, valid for the whole proleptic Gregorian calendar
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
+
+/// Converts a civil date into days since 1970-01-01.
+pub fn days_from_civil(year: i64, month: u32, day: u32) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let yoe = year.rem_euclid(400);
+    let month = i64::from(month);
+    let doy = (153 * (if month > 2 { month - 3 } else { month + 9 }) + 2) / 5 + i64::from(day) - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    era * 146_097 + doe - 719_468
+}
+
+/// Parses an ISO `YYYY-MM-DD` date, checking that the day exists.
+pub fn parse_iso_date(value: &str) -> Option<(i64, u32, u32)> {
+    let bytes = value.as_bytes();
+    if bytes.len() != 10 || bytes[4] != b'-' || bytes[7] != b'-' {
+        return None;
+    }
+    let year: i64 = value[0..4].parse().ok()?;
+    let month: u32 = value[5..7].parse().ok()?;
+    let day: u32 = value[8..10].parse().ok()?;
+    if !(1..=12).contains(&month) || day == 0 {
+        return None;
+    }
+    // Round-trip through the day count to reject dates like 2023-02-30
+    let parsed = civil_from_days(days_from_civil(year, month, day));
+    (parsed == (year, month, day)).then_some(parsed)
+}
+
+pub fn format_iso_date(days: i64) -> String {
+    let (year, month, day) = civil_from_days(days);
+    format!("{:04}-{:02}-{:02}", year, month, day)
+}
+
+const MS_PER_SECOND: i64 = 1000;
+const MS_PER_DAY: i64 = 86_400 * MS_PER_SECOND;
+/// 0000-01-01T00:00:00.000Z, the earliest time with a four-digit year
+pub const MIN_TIMESTAMP_MS: i64 = -62_167_219_200_000;
+/// 9999-12-31T23:59:59.999Z, the latest time with a four-digit year
+pub const MAX_TIMESTAMP_MS: i64 = 253_402_300_799_999;
+
+/// Splits milliseconds since the epoch into days and (hour, minute, second, millisecond).
+pub fn split_millis(millis: i64) -> (i64, (u32, u32, u32, u32)) {
+    let days = millis.div_euclid(MS_PER_DAY);
+    let rest = millis.rem_euclid(MS_PER_DAY);
+    let seconds = rest / MS_PER_SECOND;
+    (
+        days,
+        (
+            (seconds / 3600) as u32,
+            (seconds / 60 % 60) as u32,
+            (seconds % 60) as u32,
+            (rest % MS_PER_SECOND) as u32,
+        ),
+    )
+}
+
+/// Parses a start time given as `YYYY-MM-DD`, `YYYY-MM-DDTHH:MM:SS[Z]` (a space may replace
+/// the `T`) or Unix seconds, returning milliseconds since the epoch. Unix seconds must fall
+/// within years 0-9999.
+pub fn parse_timestamp_ms(value: &str) -> Option<i64> {
+    if let Ok(seconds) = value.parse::<i64>() {
+        return seconds
+            .checked_mul(MS_PER_SECOND)
+            .filter(|millis| (MIN_TIMESTAMP_MS..=MAX_TIMESTAMP_MS).contains(millis));
+    }
+
+    let value = value.strip_suffix('Z').unwrap_or(value);
+    let (date, time) = match value.split_once(['T', ' ']) {
+        Some((date, time)) => (date, Some(time)),
+        None => (value, None),
+    };
+    let (year, month, day) = parse_iso_date(date)?;
+    let mut millis = days_from_civil(year, month, day) * MS_PER_DAY;
+
+    if let Some(time) = time {
+        let parts: Vec<&str> = time.split(':').collect();
+        if parts.len() != 3 || parts.iter().any(|p| p.len() != 2) {
+            return None;
+        }
+        let hour: i64 = parts[0].parse().ok()?;
+        let minute: i64 = parts[1].parse().ok()?;
+        let second: i64 = parts[2].parse().ok()?;
+        if hour > 23 || minute > 59 || second > 59 {
+            return None;
+        }
+        millis += ((hour * 60 + minute) * 60 + second) * MS_PER_SECOND;
+    }
+    Some(millis)
+}
+
+/// Parses a duration such as `500ms`, `1s`, `5m`, `2h` or `1d` into milliseconds. Durations
+/// longer than the ten thousand years a timestamp can span are rejected.
+pub fn parse_duration_ms(value: &str) -> Option<i64> {
+    let value = value.trim();
+    let split = value.find(|c: char| !c.is_ascii_digit() && c != '.')?;
+    let (number, unit) = value.split_at(split);
+    let number: f64 = number.parse().ok()?;
+    let unit_ms = match unit {
+        "ms" => 1.0,
+        "s" => 1000.0,
+        "m" => 60_000.0,
+        "h" => 3_600_000.0,
+        "d" => 86_400_000.0,
+        _ => return None,
+    };
+    let millis = (number * unit_ms).round();
+    (millis <= (MAX_TIMESTAMP_MS - MIN_TIMESTAMP_MS) as f64).then_some(millis as i64)
+}
@@ -1,2 +1,4 @@
 pub mod progress;
-pub mod file_ops;
\ No newline at end of file
+pub mod file_ops;
+pub mod dates;
+pub mod units;
@@ -1,12 +1,206 @@
+use crate::utils::units;
+use anyhow::bail;
 use indicatif::{ProgressBar, ProgressStyle};
+use serde::Serialize;
+use std::fmt;
+use std::str::FromStr;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
 
-pub fn create_progress_bar(total: u64) -> ProgressBar {
+// How often the bar's rows, bytes and throughput are redrawn
+const BAR_REFRESH: Duration = Duration::from_millis(100);
+// How often JSON lines and callbacks receive an event, besides the final one
+const EVENT_INTERVAL: Duration = Duration::from_secs(1);
+
+/// How far a write has got, as passed to progress callbacks and printed by `--progress json`.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct ProgressEvent {
+    /// Data rows written so far
+    pub rows: u64,
+    /// Rows to write, if the write has an end
+    pub total_rows: Option<u64>,
+    /// Bytes written so far; 0 for formats whose size is only known once they are saved
+    pub bytes: u64,
+    /// Expected bytes, if known
+    pub total_bytes: Option<u64>,
+    pub elapsed_secs: f64,
+    pub rows_per_second: f64,
+    pub bytes_per_second: f64,
+    /// Seconds left at the pace so far, from the bytes if the expected size is known and
+    /// otherwise from the rows
+    pub eta_secs: Option<f64>,
+    /// Whether this is the last event of the write
+    pub done: bool,
+}
+
+/// Where a generator reports its progress.
+#[derive(Clone, Default)]
+pub enum Progress {
+    /// An interactive bar on the terminal with rows, bytes, throughput and ETA
+    #[default]
+    Bar,
+    /// Nothing
+    Hidden,
+    /// A [`ProgressEvent`] per line on stderr as JSON, every second and when done
+    Json,
+    /// [`ProgressEvent`]s passed to a function, every second and when done
+    Callback(Arc<dyn Fn(&ProgressEvent) + Send + Sync>),
+}
+
+impl fmt::Debug for Progress {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Progress::Bar => write!(f, "Bar"),
+            Progress::Hidden => write!(f, "Hidden"),
+            Progress::Json => write!(f, "Json"),
+            Progress::Callback(_) => write!(f, "Callback"),
+        }
+    }
+}
+
+impl FromStr for Progress {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "bar" => Ok(Progress::Bar),
+            "json" => Ok(Progress::Json),
+            "none" => Ok(Progress::Hidden),
+            _ => bail!("Unknown progress mode '{}'. Use bar, json or none", s),
+        }
+    }
+}
+
+impl Progress {
+    pub fn callback(callback: impl Fn(&ProgressEvent) + Send + Sync + 'static) -> Self {
+        Progress::Callback(Arc::new(callback))
+    }
+
+    /// Starts reporting a write of `total_rows` rows and about `total_bytes` bytes; `None`
+    /// for either if it isn't known.
+    pub fn start(&self, total_rows: Option<u64>, total_bytes: Option<u64>) -> ProgressTracker {
+        let bar = match self {
+            Progress::Bar => Some(create_progress_bar(total_rows, total_bytes)),
+            _ => None,
+        };
+        let now = Instant::now();
+        ProgressTracker {
+            progress: self.clone(),
+            bar,
+            started: now,
+            last_refresh: now,
+            last_event: now,
+            rows: 0,
+            bytes: 0,
+            total_rows,
+            total_bytes,
+        }
+    }
+}
+
+/// Counts the rows and bytes of one write and reports them as its [`Progress`] says.
+pub struct ProgressTracker {
+    progress: Progress,
+    bar: Option<ProgressBar>,
+    started: Instant,
+    last_refresh: Instant,
+    last_event: Instant,
+    rows: u64,
+    bytes: u64,
+    total_rows: Option<u64>,
+    total_bytes: Option<u64>,
+}
+
+impl ProgressTracker {
+    /// Adds `rows` written rows and `bytes` written bytes.
+    pub fn inc(&mut self, rows: u64, bytes: u64) {
+        self.rows += rows;
+        self.bytes += bytes;
+        if let Some(bar) = &self.bar {
+            bar.set_position(if self.total_bytes.is_some() { self.bytes } else { self.rows });
+            if self.last_refresh.elapsed() >= BAR_REFRESH {
+                bar.set_message(self.summary());
+                self.last_refresh = Instant::now();
+            }
+        } else if self.last_event.elapsed() >= EVENT_INTERVAL {
+            self.report(false);
+            self.last_event = Instant::now();
+        }
+    }
+
+    /// Reports the final counts, leaving a bar on screen.
+    pub fn finish(self) {
+        match &self.bar {
+            Some(bar) => bar.finish_with_message(self.summary()),
+            None => self.report(true),
+        }
+    }
+
+    pub fn event(&self, done: bool) -> ProgressEvent {
+        let elapsed_secs = self.started.elapsed().as_secs_f64();
+        let per_second = |count: u64| if elapsed_secs > 0.0 { count as f64 / elapsed_secs } else { 0.0 };
+        let (done_so_far, total) = match self.total_bytes {
+            Some(total) => (self.bytes, Some(total)),
+            None => (self.rows, self.total_rows),
+        };
+        let eta_secs = total.filter(|_| done_so_far > 0).map(|total| {
+            let left = total.saturating_sub(done_so_far) as f64;
+            left * elapsed_secs / done_so_far as f64
+        });
+        ProgressEvent {
+            rows: self.rows,
+            total_rows: self.total_rows,
+            bytes: self.bytes,
+            total_bytes: self.total_bytes,
+            elapsed_secs,
+            rows_per_second: per_second(self.rows),
+            bytes_per_second: per_second(self.bytes),
+            eta_secs: if done { Some(0.0) } else { eta_secs },
+            done,
+        }
+    }
+
+    fn report(&self, done: bool) {
+        match &self.progress {
+            Progress::Json => {
+                if let Ok(line) = serde_json::to_string(&self.event(done)) {
+                    eprintln!("{}", line);
+                }
+            }
+            Progress::Callback(callback) => callback(&self.event(done)),
+            Progress::Bar | Progress::Hidden => {}
+        }
+    }
+
+    /// Rows, then bytes and throughput when the bytes are counted.
+    fn summary(&self) -> String {
+        let event = self.event(false);
+        if self.bytes == 0 {
+            return format!("{} rows", self.rows);
+        }
+        format!("{} rows, {}, {}", self.rows, units::format_size(self.bytes), units::format_rate(event.bytes_per_second))
+    }
+}
+
+/// A bar over the expected bytes, or the rows if the size isn't known, or a spinner if
+/// neither is.
+fn create_progress_bar(total_rows: Option<u64>, total_bytes: Option<u64>) -> ProgressBar {
+    let Some(total) = total_bytes.or(total_rows) else {
+        let pb = ProgressBar::new_spinner();
+        pb.set_style(
+            ProgressStyle::default_spinner()
+                .template("{spinner:.green} [{elapsed_precise}] {msg}")
+                .unwrap_or_else(|_| ProgressStyle::default_spinner()),
+        );
+        pb.enable_steady_tick(BAR_REFRESH);
+        return pb;
+    };
     let pb = ProgressBar::new(total);
     pb.set_style(
         ProgressStyle::default_bar()
-            .template("{spinner:.green} [{elapsed_precise}] [{bar:40.cyan/blue}] {pos}/{len} ({eta})")
-            .unwrap()
+            .template("{spinner:.green} [{elapsed_precise}] [{bar:40.cyan/blue}] {msg} ({eta})")
+            .unwrap_or_else(|_| ProgressStyle::default_bar())
             .progress_chars("#>-"),
     );
     pb
-}
\ No newline at end of file
+}
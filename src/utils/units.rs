@@ -0,0 +1,125 @@
+//! Human-readable sizes, row counts and rates.
+//!
+//! Unit policy: SI prefixes are powers of 1000 and IEC prefixes powers of 1024, as their
+//! standards define them. `KB`, `MB`, `GB` and `TB` (or just `K`, `M`, `G`, `T`) are 1000,
+//! 1000², 1000³ and 1000⁴ bytes; `KiB`, `MiB`, `GiB` and `TiB` are 1024, 1024², 1024³ and
+//! 1024⁴ bytes. A bare number is bytes. Units are case-insensitive, may follow a space, and
+//! numbers may have a fraction and underscores between digits (`1_500KB`, `2.5 GiB`).
+//!
+//! Row counts take the same SI suffixes without the `B`: `10M` is 10,000,000 rows and
+//! `2.5k` is 2,500. They must come out whole.
+//!
+//! Output uses SI units, so a size printed by csvgen reads back as the same number of bytes
+//! to three significant figures.
+
+use anyhow::{Result, anyhow, bail};
+
+const SI_UNITS: [&str; 5] = ["B", "KB", "MB", "GB", "TB"];
+
+/// Parses a size such as `500KB`, `1.5MiB`, `2 GB` or `100` into bytes, rounding to the
+/// nearest byte.
+pub fn parse_size(value: &str) -> Result<usize> {
+    let (number, unit) = split_unit(value)?;
+    let multiplier: u128 = match unit.to_ascii_lowercase().as_str() {
+        "" | "b" => 1,
+        "k" | "kb" => 1000,
+        "m" | "mb" => 1000u128.pow(2),
+        "g" | "gb" => 1000u128.pow(3),
+        "t" | "tb" => 1000u128.pow(4),
+        "kib" => 1 << 10,
+        "mib" => 1 << 20,
+        "gib" => 1 << 30,
+        "tib" => 1 << 40,
+        _ => bail!("Invalid size '{}'. Use B, KB, MB, GB, TB (powers of 1000) or KiB, MiB, GiB, TiB (powers of 1024)", value),
+    };
+    let bytes = number.scale(multiplier, true).ok_or_else(|| anyhow!("Size '{}' is too large", value))?;
+    usize::try_from(bytes).map_err(|_| anyhow!("Size '{}' is too large", value))
+}
+
+/// Parses a row count such as `5000`, `10M`, `2.5k` or `1_000_000`.
+pub fn parse_count(value: &str) -> Result<usize> {
+    let (number, unit) = split_unit(value)?;
+    let multiplier: u128 = match unit.to_ascii_lowercase().as_str() {
+        "" => 1,
+        "k" => 1000,
+        "m" => 1000u128.pow(2),
+        "g" => 1000u128.pow(3),
+        _ => bail!("Invalid count '{}'. Use a number with an optional k, M or G suffix", value),
+    };
+    let count = number
+        .scale(multiplier, false)
+        .ok_or_else(|| anyhow!("Count '{}' is not a whole number", value))?;
+    usize::try_from(count).map_err(|_| anyhow!("Count '{}' is too large", value))
+}
+
+/// Parses a byte rate such as `64KB/s` (or just `64KB`) into bytes per second.
+pub fn parse_byte_rate(value: &str) -> Result<u64> {
+    let bytes = parse_size(value.trim().trim_end_matches("/s"))?;
+    Ok(bytes as u64)
+}
+
+/// A size in SI units to three significant figures, e.g. `1.05 MB` or `512 B`.
+pub fn format_size(bytes: u64) -> String {
+    let mut value = bytes as f64;
+    let mut unit = 0;
+    // Step up before rounding would show 1000 of a unit
+    while value >= 999.5 && unit < SI_UNITS.len() - 1 {
+        value /= 1000.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{} B", bytes)
+    } else if value < 9.995 {
+        format!("{:.2} {}", value, SI_UNITS[unit])
+    } else if value < 99.95 {
+        format!("{:.1} {}", value, SI_UNITS[unit])
+    } else {
+        format!("{:.0} {}", value, SI_UNITS[unit])
+    }
+}
+
+/// A byte rate in SI units, e.g. `2.50 MB/s`.
+pub fn format_rate(bytes_per_second: f64) -> String {
+    format!("{}/s", format_size(bytes_per_second.max(0.0).round() as u64))
+}
+
+/// A decimal number kept exact: `digits` divided by 10 to the power `scale`.
+struct Decimal {
+    digits: u128,
+    scale: u32,
+}
+
+impl Decimal {
+    /// The number times `multiplier`, rounded to the nearest integer or, if `round` is false,
+    /// `None` unless it is whole. `None` as well on overflow.
+    fn scale(&self, multiplier: u128, round: bool) -> Option<u128> {
+        let divisor = 10u128.checked_pow(self.scale)?;
+        let product = self.digits.checked_mul(multiplier)?;
+        if round {
+            Some((product + divisor / 2) / divisor)
+        } else {
+            (product % divisor == 0).then_some(product / divisor)
+        }
+    }
+}
+
+/// Splits `value` into its number and the unit after it.
+fn split_unit(value: &str) -> Result<(Decimal, &str)> {
+    let value = value.trim();
+    let end = value.find(|c: char| !(c.is_ascii_digit() || c == '.' || c == '_')).unwrap_or(value.len());
+    let (number, unit) = value.split_at(end);
+    let invalid = || anyhow!("Invalid number '{}'", value);
+
+    // Underscores may only separate digits
+    if number.split('_').any(|group| group.is_empty() || group.starts_with('.') || group.ends_with('.')) {
+        return Err(invalid());
+    }
+    let number = number.replace('_', "");
+    let (whole, fraction) = number.split_once('.').unwrap_or((&number, ""));
+    if whole.is_empty() || fraction.contains('.') {
+        return Err(invalid());
+    }
+    let digits = format!("{}{}", whole, fraction).parse::<u128>().map_err(|_| invalid())?;
+    let scale = u32::try_from(fraction.len()).map_err(|_| invalid())?;
+    Ok((Decimal { digits, scale }, unit.trim_start()))
+}
@@ -0,0 +1,76 @@
+#![cfg(feature = "async")]
+
+use csv_gen::CsvGen;
+use csv_gen::data::types::DataType;
+use futures::StreamExt;
+
+fn seeded_csv() -> csv_gen::Result<CsvGen> {
+    CsvGen::builder()
+        .rows(2000)
+        .column("id", DataType::UniqueId)
+        .column("email", DataType::Email)
+        .column("notes", DataType::String)
+        .seed(9)
+        .build()
+}
+
+#[tokio::test]
+async fn test_stream_matches_blocking_writer() -> anyhow::Result<()> {
+    let csv = seeded_csv()?;
+    let mut expected = Vec::new();
+    csv.write_to(&mut expected)?;
+
+    let mut streamed = Vec::new();
+    assert_eq!(csv.write_to_async(&mut streamed).await?, 2000);
+    assert_eq!(streamed, expected);
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_stream_yields_bounded_chunks() -> anyhow::Result<()> {
+    let csv = seeded_csv()?;
+    let chunks: Vec<Vec<u8>> = csv
+        .stream()
+        .with_chunk_size(4096)
+        .map(|chunk| chunk.expect("encoding into memory cannot fail"))
+        .collect()
+        .await;
+
+    assert!(chunks.len() > 10, "Expected many chunks, got {}", chunks.len());
+    let longest_row = 200;
+    assert!(chunks.iter().all(|c| c.len() < 4096 + longest_row));
+    assert!(chunks.iter().all(|c| c.ends_with(b"\n")), "Rows should not be split across chunks");
+
+    let mut expected = Vec::new();
+    csv.write_to(&mut expected)?;
+    assert_eq!(chunks.concat(), expected);
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_stream_is_driven_by_the_consumer() -> anyhow::Result<()> {
+    let csv = seeded_csv()?;
+    let mut stream = csv.stream().with_chunk_size(1024);
+    stream.next().await.transpose()?;
+    let after_one = stream.rows_written();
+    assert!(after_one > 0 && after_one < 100, "Only one chunk should be generated, got {} rows", after_one);
+
+    let (mut tx, mut rx) = futures::channel::mpsc::channel(1);
+    let producer = async move {
+        while let Some(chunk) = stream.next().await {
+            if futures::SinkExt::send(&mut tx, chunk).await.is_err() {
+                break;
+            }
+        }
+    };
+    let consumer = async move {
+        let mut rows = 0;
+        while let Some(chunk) = rx.next().await {
+            rows += chunk?.iter().filter(|&&b| b == b'\n').count();
+        }
+        anyhow::Ok(rows)
+    };
+    let ((), rows) = futures::join!(producer, consumer);
+    assert_eq!(rows? + after_one + 1, 2001);
+    Ok(())
+}
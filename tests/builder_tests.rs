@@ -0,0 +1,83 @@
+use csv_gen::data::headers::HeaderStrategy;
+use csv_gen::data::pattern::Pattern;
+use csv_gen::data::planner::{Solved, SolverObjective};
+use csv_gen::data::types::{ColumnSpec, DataType, FixedColumn};
+use csv_gen::{CsvGen, Error};
+
+#[test]
+fn test_builder_with_generated_columns() -> anyhow::Result<()> {
+    let csv = CsvGen::builder().size(32 * 1024).rows(200).columns(5).seed(7).build()?;
+    assert_eq!(csv.schema().columns.len(), 5);
+    assert_eq!(csv.solved(), Solved::Nothing);
+
+    let mut first = Vec::new();
+    let mut second = Vec::new();
+    assert_eq!(csv.write_to(&mut first)?, 200);
+    csv.write_to(&mut second)?;
+    assert_eq!(first, second, "Seeded output should be reproducible");
+
+    let error = first.len().abs_diff(32 * 1024) as f64 / (32.0 * 1024.0);
+    assert!(error < 0.01, "Size error too large: {}", first.len());
+    Ok(())
+}
+
+#[test]
+fn test_builder_with_explicit_columns() -> anyhow::Result<()> {
+    let csv = CsvGen::builder()
+        .rows(100)
+        .column("id", DataType::UniqueId)
+        .column_spec(ColumnSpec::new("amount", DataType::Number).with_width(25))
+        .column_spec(ColumnSpec::new("notes", DataType::String).with_null_rate(0.5))
+        .build()?;
+    assert_eq!(csv.solved(), Solved::Size);
+
+    let mut output = Vec::new();
+    csv.write_to(&mut output)?;
+    let content = String::from_utf8(output)?;
+    assert_eq!(content.lines().next(), Some("id,amount,notes"));
+    assert_eq!(content.lines().count(), 101);
+    Ok(())
+}
+
+#[test]
+fn test_builder_error_kinds() {
+    let result = CsvGen::builder().columns(4).build();
+    assert!(matches!(result, Err(Error::ImpossibleConstraints(_))));
+
+    let result = CsvGen::builder().size(100).columns(50).build();
+    assert!(matches!(result, Err(Error::ImpossibleConstraints(_))));
+
+    let result = CsvGen::builder()
+        .rows(10)
+        .column("a", DataType::String)
+        .column("a", DataType::Number)
+        .build();
+    assert!(matches!(result, Err(Error::InvalidSchema(_))));
+
+    let result = CsvGen::builder().rows(10).column("a,b", DataType::String).build();
+    assert!(matches!(result, Err(Error::InvalidSchema(_))));
+
+    let csv = CsvGen::builder().rows(10).build().expect("valid constraints");
+    let result = csv.write_to_file("test_output/missing_dir/out.csv");
+    assert!(matches!(result, Err(Error::Io(_))));
+}
+
+#[test]
+fn test_builder_rejects_overflowing_inputs() -> anyhow::Result<()> {
+    let impossible = |result: Result<CsvGen, Error>| matches!(result, Err(Error::ImpossibleConstraints(_)));
+
+    assert!(impossible(CsvGen::builder().size(usize::MAX).rows(1).build()));
+    assert!(impossible(CsvGen::builder().rows(usize::MAX).build()));
+    assert!(impossible(CsvGen::builder().rows(10).objective(SolverObjective::CellWidth(usize::MAX)).build()));
+    let huge = FixedColumn::pattern(1, &Pattern::new("a{99999999999}")?);
+    assert!(impossible(CsvGen::builder().size(100_000).rows(100).fixed_column(huge).build()));
+
+    // Planner failures that aren't about the size keep their own kind
+    let short_list = HeaderStrategy::List(vec!["a".to_string(), "b".to_string()]);
+    let result = CsvGen::builder().rows(10).columns(4).header_strategy(short_list).build();
+    assert!(matches!(result, Err(Error::InvalidSchema(_))));
+
+    let record = csv::Reader::from_reader("a,b\n1\n".as_bytes()).records().next().expect("a record");
+    assert!(matches!(Error::from(record.unwrap_err()), Error::Csv(_)));
+    Ok(())
+}
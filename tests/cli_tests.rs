@@ -0,0 +1,72 @@
+use csv_gen::cli::{Args, Command, OutputFormat, SchemaCommand};
+use csv_gen::data::planner;
+use csv_gen::data::types::{ColumnSpec, DataType, SchemaSpec};
+use csv_gen::CsvGen;
+
+fn parse(line: &str) -> Result<Args, clap::Error> {
+    Args::try_parse_args_from(line.split_whitespace())
+}
+
+#[test]
+fn test_flat_arguments_still_generate() -> anyhow::Result<()> {
+    let (flat, flat_global) = parse("csvgen --size 1MB --rows 5000 --seed 7")?.into_generate().unwrap();
+    let (explicit, global) = parse("csvgen --seed 7 generate --size 1MB --rows 5000 -q --format sql")?.into_generate().unwrap();
+
+    assert_eq!(flat.plan.size.as_deref(), Some("1MB"));
+    assert_eq!(flat.plan.rows, explicit.plan.rows);
+    assert_eq!(flat.output, explicit.output);
+    assert_eq!(flat_global.seed, Some(7));
+    assert_eq!(global.seed, Some(7), "Global options may come before the command");
+    assert!(global.quiet && global.format == OutputFormat::Sql, "... or after it");
+
+    assert!(parse("csvgen --size 1MB infer sample.csv").is_err(), "Generate options must not be dropped");
+    assert!(parse("csvgen generate").is_err(), "A size or row count is still required");
+    assert!(parse("csvgen infer sample.csv")?.into_generate().is_none());
+    Ok(())
+}
+
+#[test]
+fn test_plan_and_schema_commands() -> anyhow::Result<()> {
+    let args = parse("csvgen --config generator.json plan --rows 10 --columns 4")?;
+    assert_eq!(args.global.config.as_deref(), Some("generator.json"));
+    assert!(matches!(args.command, Some(Command::Plan(ref plan)) if plan.rows == Some(10) && plan.columns == Some(4)));
+
+    let args = parse("csvgen schema validate schema.json")?;
+    let Some(Command::Schema(schema)) = args.command else { panic!("Expected a schema command") };
+    assert!(matches!(schema.command, SchemaCommand::Validate(ref validate) if validate.input == "schema.json"));
+
+    let args = parse("csvgen schema export --size 10KB out.json")?;
+    let Some(Command::Schema(schema)) = args.command else { panic!("Expected a schema command") };
+    let SchemaCommand::Export(export) = schema.command else { panic!("Expected schema export") };
+    assert_eq!(export.output.as_deref(), Some("out.json"));
+    assert_eq!(export.plan.size.as_deref(), Some("10KB"));
+    Ok(())
+}
+
+#[test]
+fn test_schema_validation_and_export() -> anyhow::Result<()> {
+    let csv = CsvGen::builder()
+        .rows(500)
+        .column("id", DataType::UniqueId)
+        .column("amount", DataType::Decimal { scale: 2 })
+        .column_spec(ColumnSpec::new("note", DataType::String).with_width(20).with_null_rate(0.1))
+        .build()?;
+    let exported = SchemaSpec::from_schema(csv.schema());
+    assert!(exported.validate().is_empty(), "{:?}", exported.validate());
+    assert_eq!(exported.columns[2].null_rate, 0.1);
+
+    // The export scales like any other schema file
+    let plan = planner::plan_from_spec(&exported, Some(50_000), None)?;
+    assert_eq!(plan.schema.columns.len(), 3);
+    assert!(plan.num_rows > 500);
+
+    let mut broken = exported.clone();
+    broken.columns[1].name = "id".to_string();
+    broken.columns[2].null_rate = 1.5;
+    broken.columns.push(ColumnSpec::new("level", DataType::Range { min: 5, max: 1 }));
+    broken.columns.push(ColumnSpec::new("kind", DataType::Enum(Vec::new())));
+    let problems = broken.validate();
+    assert_eq!(problems.len(), 4, "{:?}", problems);
+    assert!(problems[0].contains("repeated"));
+    Ok(())
+}
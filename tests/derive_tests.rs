@@ -0,0 +1,86 @@
+use csv_gen::CsvGen;
+use csv_gen::data::types::DataType;
+use csv_gen::record::CsvRecord;
+use std::collections::HashSet;
+
+#[derive(Debug, CsvGen)]
+struct Order {
+    #[csvgen(unique)]
+    id: u64,
+    #[csvgen(email)]
+    customer: String,
+    #[csvgen(range = 1..=5)]
+    quantity: u8,
+    #[csvgen(range = -10..10)]
+    adjustment: i32,
+    #[csvgen(pattern = "SKU-[0-9]{4}")]
+    sku: String,
+    #[csvgen(one_of = "new, shipped, returned", rename = "order_status")]
+    status: String,
+    price: f64,
+    gift: bool,
+    #[csvgen(null_rate = 0.5)]
+    note: Option<String>,
+}
+
+#[test]
+fn test_derived_schema_follows_fields() -> anyhow::Result<()> {
+    let spec = Order::schema_spec()?;
+    let names: Vec<&str> = spec.columns.iter().map(|c| c.name.as_str()).collect();
+    assert_eq!(
+        names,
+        ["id", "customer", "quantity", "adjustment", "sku", "order_status", "price", "gift", "note"]
+    );
+    assert!(matches!(spec.columns[0].data_type, DataType::UniqueId));
+    assert!(matches!(spec.columns[2].data_type, DataType::Range { min: 1, max: 5 }));
+    assert!(matches!(spec.columns[3].data_type, DataType::Range { min: -10, max: 9 }));
+    assert!(matches!(spec.columns[6].data_type, DataType::Decimal { scale: 2 }));
+    assert_eq!(spec.columns[8].null_rate, 0.5);
+
+    let schema = Order::csv_schema(100)?;
+    assert_eq!(schema.columns.len(), 9);
+    Ok(())
+}
+
+#[test]
+fn test_fake_instances_respect_attributes() -> anyhow::Result<()> {
+    let orders = Order::fake_seeded(500, 11)?;
+    assert_eq!(orders.len(), 500);
+
+    let ids: HashSet<u64> = orders.iter().map(|o| o.id).collect();
+    assert_eq!(ids.len(), 500);
+    for order in &orders {
+        assert!(order.customer.contains('@'), "Not an email: {}", order.customer);
+        assert!((1..=5).contains(&order.quantity));
+        assert!((-10..10).contains(&order.adjustment));
+        assert!(order.sku.starts_with("SKU-") && order.sku.len() == 8, "Bad SKU: {}", order.sku);
+        assert!(["new", "shipped", "returned"].contains(&order.status.as_str()));
+        assert!(order.price >= 0.0);
+    }
+    assert!(orders.iter().any(|o| o.gift) && orders.iter().any(|o| !o.gift));
+    let nulls = orders.iter().filter(|o| o.note.is_none()).count();
+    assert!((150..350).contains(&nulls), "Expected about half the notes to be null, got {}", nulls);
+
+    let again = Order::fake_seeded(500, 11)?;
+    assert_eq!(format!("{:?}", orders), format!("{:?}", again));
+    Ok(())
+}
+
+#[test]
+fn test_csv_and_instances_share_a_definition() -> anyhow::Result<()> {
+    let csv = CsvGen::builder().schema(Order::schema_spec()?).rows(50).seed(3).build()?;
+
+    let mut output = Vec::new();
+    csv.write_to(&mut output)?;
+    let mut reader = csv::Reader::from_reader(output.as_slice());
+    assert_eq!(reader.headers()?.get(5), Some("order_status"));
+
+    let rows: Vec<Vec<String>> = reader
+        .records()
+        .map(|r| r.map(|r| r.iter().map(String::from).collect()))
+        .collect::<Result<_, _>>()?;
+    let from_file: Vec<Order> = rows.iter().map(|row| Order::from_row(row)).collect::<Result<_, _>>()?;
+    let from_iter: Vec<Order> = csv.records().collect::<Result<_, _>>()?;
+    assert_eq!(format!("{:?}", from_file), format!("{:?}", from_iter));
+    Ok(())
+}
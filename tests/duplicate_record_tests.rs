@@ -0,0 +1,77 @@
+use csv_gen::data::schema::SchemaBuilder;
+use csv_gen::generator::duplicates::DuplicateConfig;
+use csv_gen::generator::size_based::CsvGenerator;
+use std::collections::HashMap;
+use std::fs;
+
+fn read_rows(path: &str) -> anyhow::Result<Vec<Vec<String>>> {
+    let content = fs::read_to_string(path)?;
+    Ok(content
+        .lines()
+        .map(|line| line.split(',').map(str::to_string).collect())
+        .collect())
+}
+
+#[test]
+fn test_duplicates_with_ground_truth() -> anyhow::Result<()> {
+    let num_rows = 300;
+    let output_path = "test_duplicate_records.csv";
+    let truth_path = "test_duplicate_records_truth.csv";
+
+    let schema = SchemaBuilder::build_schema(30_000, num_rows, 4, 6, 1, 1)?;
+    let mut generator = CsvGenerator::new(schema).with_duplicates(DuplicateConfig {
+        duplicate_rate: 0.2,
+        near_duplicate_rate: 0.2,
+        ground_truth_path: truth_path.to_string(),
+    });
+    generator.generate(output_path, num_rows)?;
+
+    let rows = read_rows(output_path)?;
+    let truth = read_rows(truth_path)?;
+    assert_eq!(truth[0], ["duplicate_id", "original_id", "kind"]);
+
+    // IDs stay unique and sequential
+    let by_id: HashMap<&str, &Vec<String>> = rows[1..].iter().map(|r| (r[0].as_str(), r)).collect();
+    assert_eq!(by_id.len(), num_rows);
+
+    let mut exact = 0;
+    let mut near = 0;
+    for entry in &truth[1..] {
+        let duplicate = by_id[entry[0].as_str()];
+        let original = by_id[entry[1].as_str()];
+        assert!(entry[1] < entry[0], "Original {} should precede duplicate {}", entry[1], entry[0]);
+        match entry[2].as_str() {
+            "exact" => {
+                assert_eq!(duplicate[1..], original[1..]);
+                exact += 1;
+            }
+            "near" => {
+                assert_ne!(duplicate[1..], original[1..]);
+                near += 1;
+            }
+            kind => panic!("Unexpected kind {}", kind),
+        }
+    }
+    assert!(exact > 20 && near > 20, "Expected duplicates of both kinds, got {} exact and {} near", exact, near);
+
+    // Originals are never recorded as duplicates
+    let duplicate_ids: Vec<&str> = truth[1..].iter().map(|e| e[0].as_str()).collect();
+    assert!(truth[1..].iter().all(|e| !duplicate_ids.contains(&e[1].as_str())));
+
+    fs::remove_file(output_path)?;
+    fs::remove_file(truth_path)?;
+    Ok(())
+}
+
+#[test]
+fn test_invalid_duplicate_rates() -> anyhow::Result<()> {
+    let schema = SchemaBuilder::build_schema(1000, 10, 2, 5, 0, 0)?;
+    let mut generator = CsvGenerator::new(schema).with_duplicates(DuplicateConfig {
+        duplicate_rate: 0.7,
+        near_duplicate_rate: 0.7,
+        ground_truth_path: "test_invalid_rates_truth.csv".to_string(),
+    });
+    assert!(generator.generate("test_invalid_rates.csv", 10).is_err());
+    assert!(!std::path::Path::new("test_invalid_rates.csv").exists());
+    Ok(())
+}
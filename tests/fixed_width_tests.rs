@@ -0,0 +1,94 @@
+use csv_gen::CsvGen;
+use csv_gen::data::headers::HeaderStrategy;
+use csv_gen::data::planner::{self, ColumnOptions, SolverObjective};
+use csv_gen::data::types::{ColumnSpec, CsvSchema, DataType};
+use csv_gen::generator::fixed_width::{Alignment, FixedWidthGenerator};
+
+fn schema() -> anyhow::Result<CsvSchema> {
+    let csv = CsvGen::builder()
+        .rows(100)
+        .column("id", DataType::UniqueId)
+        .column("city", DataType::Enum(vec!["Oslo".to_string(), "Reykjavik".to_string()]))
+        .column("balance", DataType::Decimal { scale: 2 })
+        .column("delta", DataType::Range { min: -50, max: 50 })
+        .column_spec(ColumnSpec::new("note", DataType::String).with_width(6).with_null_rate(0.5))
+        .build()?;
+    Ok(csv.schema().clone())
+}
+
+#[test]
+fn test_records_are_padded_and_aligned() -> anyhow::Result<()> {
+    let mut generator = FixedWidthGenerator::new(schema()?).with_seed(3);
+    let fields = generator.fields().to_vec();
+    assert_eq!(fields[0].start, 1);
+    assert!(fields.windows(2).all(|pair| pair[1].start == pair[0].start + pair[0].length));
+    assert_eq!(fields[1].length, "Reykjavik".len());
+    assert_eq!(fields[1].alignment, Alignment::Left);
+    assert_eq!(fields[2].alignment, Alignment::Right);
+
+    let mut output = Vec::new();
+    generator.write(&mut output, 100)?;
+    let text = String::from_utf8(output)?;
+    assert_eq!(text.len(), 100 * generator.record_len(), "Every record has the same length");
+
+    let city = |line: &str| line[fields[1].start - 1..][..fields[1].length].to_string();
+    let delta = |line: &str| line[fields[3].start - 1..][..fields[3].length].to_string();
+    let note = |line: &str| line[fields[4].start - 1..][..fields[4].length].to_string();
+    let lines: Vec<&str> = text.lines().collect();
+    assert!(lines.iter().any(|line| city(line) == "Oslo     "), "Text pads on the right");
+    assert!(lines.iter().all(|line| !delta(line).ends_with(' ')), "Numbers pad on the left");
+    assert!(lines.iter().any(|line| delta(line).starts_with(' ')));
+    assert!(lines.iter().any(|line| note(line).trim().is_empty()), "Nulls are blank fields");
+    assert!(!text.contains(','));
+    Ok(())
+}
+
+#[test]
+fn test_copybook_layout() -> anyhow::Result<()> {
+    let generator = FixedWidthGenerator::new(schema()?);
+    let copybook = generator.copybook("customers-record");
+    let balance = &generator.fields()[2];
+
+    assert!(copybook.contains("       01  CUSTOMERS-RECORD.\n"));
+    assert!(copybook.contains(&format!("{}-byte records", generator.record_len() - 1)));
+    assert!(copybook.contains("05  ID "));
+    assert!(copybook.contains("PIC X(9).\n"), "City: {}", copybook);
+    assert!(copybook.contains(&format!("PIC Z({})9.99.\n", balance.length - 4)));
+    assert!(copybook.contains("PIC --9.\n"), "Signed range");
+    let offsets = copybook.lines().find(|line| line.contains("BALANCE") && line.starts_with("      *")).unwrap();
+    assert!(offsets.contains(&format!(" {} ", balance.start)) && offsets.ends_with("RIGHT"));
+    Ok(())
+}
+
+#[test]
+fn test_size_targeting_without_separators() -> anyhow::Result<()> {
+    let options = ColumnOptions {
+        min_columns: 6,
+        max_columns: 6,
+        email_columns: 0,
+        domain_columns: 0,
+        fixed_columns: Vec::new(),
+        header_strategy: HeaderStrategy::default(),
+        objective: SolverObjective::FewestColumns,
+    };
+    let csv_plan = planner::plan_generated(Some(100_000), None, &options)?;
+    let plan = planner::plan_fixed_width(csv_plan.clone())?;
+    assert!(plan.num_rows > csv_plan.num_rows, "No separators or header means more rows");
+
+    let mut output = Vec::new();
+    FixedWidthGenerator::new(plan.schema.clone()).write(&mut output, plan.num_rows)?;
+    assert!(output.len() <= 100_000 && output.len() > 100_000 - plan.schema.target_row_size);
+
+    let csv_plan = planner::plan_generated(Some(100_000), Some(1000), &options)?;
+    let both = planner::plan_fixed_width(csv_plan.clone())?;
+    let growth: Vec<usize> = csv_plan.schema.columns.iter().zip(&both.schema.columns)
+        .filter(|(column, _)| matches!(column.data_type, DataType::String))
+        .map(|(before, after)| after.size_bytes - before.size_bytes)
+        .collect();
+    let (least, most) = (growth.iter().min().unwrap(), growth.iter().max().unwrap());
+    assert!(most - least <= 1, "Text columns share the spare bytes evenly: {:?}", growth);
+    let mut output = Vec::new();
+    FixedWidthGenerator::new(both.schema).write(&mut output, 1000)?;
+    assert_eq!(output.len(), 100_000);
+    Ok(())
+}
@@ -0,0 +1,97 @@
+use csv_gen::data::headers::{sql_identifier, spreadsheet_name, HeaderStrategy};
+use csv_gen::data::planner::{ColumnOptions, SolverObjective};
+use csv_gen::data::schema::SchemaBuilder;
+use csv_gen::generator::size_based::CsvGenerator;
+use std::collections::HashSet;
+use std::fs;
+
+fn assert_fits(names: &[String], widths: &[usize]) {
+    let unique: HashSet<&String> = names.iter().collect();
+    assert_eq!(unique.len(), names.len(), "Duplicate header in {:?}", names);
+    for (name, &width) in names.iter().zip(widths) {
+        assert!(!name.is_empty() && name.len() <= width, "'{}' does not fit {} bytes", name, width);
+        assert!(!name.contains([',', '"', ';', '#']), "'{}' needs quoting or is unsafe", name);
+    }
+}
+
+#[test]
+fn test_strategies_fit_planned_widths() -> anyhow::Result<()> {
+    let widths = [5, 1, 1, 1, 2, 3, 12, 1, 1, 30];
+    let columns: Vec<(String, usize)> = widths
+        .iter()
+        .enumerate()
+        .map(|(i, &w)| (if i == 0 { "id".to_string() } else { format!("col{}", i) }, w))
+        .collect();
+
+    for strategy in ["padded", "sql", "spreadsheet", "prefix", "prefix:field_", "words"] {
+        let names = strategy.parse::<HeaderStrategy>()?.name_columns(&columns)?;
+        assert_fits(&names, &widths);
+    }
+
+    let names = "list:customer,a,a,b,c,d,e,f,g,h".parse::<HeaderStrategy>()?.name_columns(&columns)?;
+    assert_fits(&names, &widths);
+    assert_eq!(names[0], "custo");
+    assert!("list:a,b".parse::<HeaderStrategy>()?.name_columns(&columns).is_err());
+    assert!("list:a,\"b\"".parse::<HeaderStrategy>().is_err());
+    Ok(())
+}
+
+#[test]
+fn test_one_byte_columns_never_use_punctuation() -> anyhow::Result<()> {
+    let columns: Vec<(String, usize)> = (0..62).map(|i| (format!("col{}", i), 1)).collect();
+    let names = HeaderStrategy::Padded.name_columns(&columns)?;
+    assert_fits(&names, &[1; 62]);
+    assert!(names.iter().all(|n| n.bytes().all(|b| b.is_ascii_alphanumeric())));
+
+    // More one-byte columns than one-byte names is an error, not a panic
+    let columns: Vec<(String, usize)> = (0..63).map(|i| (format!("col{}", i), 1)).collect();
+    assert!(HeaderStrategy::Padded.name_columns(&columns).is_err());
+    Ok(())
+}
+
+#[test]
+fn test_sql_identifiers_and_spreadsheet_letters() {
+    assert_eq!(sql_identifier("Order Date"), "order_date");
+    assert_eq!(sql_identifier("2nd-value"), "_2nd_value");
+    assert_eq!(sql_identifier("select"), "select_");
+    assert_eq!(spreadsheet_name(0), "A");
+    assert_eq!(spreadsheet_name(25), "Z");
+    assert_eq!(spreadsheet_name(26), "AA");
+    assert_eq!(spreadsheet_name(701), "ZZ");
+    assert_eq!(spreadsheet_name(702), "AAA");
+}
+
+#[test]
+fn test_header_strategy_keeps_size_exact() -> anyhow::Result<()> {
+    let target_size = 50 * 1024;
+    let num_rows = 400;
+    let headers: HeaderStrategy = "sql".parse()?;
+    let options = ColumnOptions {
+        min_columns: 8,
+        max_columns: 8,
+        email_columns: 0,
+        domain_columns: 0,
+        fixed_columns: Vec::new(),
+        header_strategy: headers,
+        objective: SolverObjective::default(),
+    };
+    let schema = SchemaBuilder::build_schema_with_options(target_size, num_rows, &options)?;
+    let header_size = schema.header_size;
+
+    let output_path = "test_output/header_strategy.csv";
+    fs::create_dir_all("test_output")?;
+    let mut generator = CsvGenerator::new(schema);
+    generator.generate(output_path, num_rows)?;
+
+    let content = fs::read_to_string(output_path)?;
+    let header_line = content.lines().next().unwrap();
+    assert_eq!(header_line.len(), header_size);
+    assert!(header_line.split(',').all(|name| name.bytes().all(|b| b.is_ascii_lowercase() || b.is_ascii_digit() || b == b'_')));
+
+    let actual_size = content.len();
+    let error = actual_size.abs_diff(target_size) as f64 / target_size as f64;
+    assert!(error < 0.01, "Size error too large: {} vs {}", actual_size, target_size);
+
+    fs::remove_file(output_path)?;
+    Ok(())
+}
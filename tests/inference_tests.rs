@@ -0,0 +1,111 @@
+use csv_gen::data::inference::{infer_schema, DEFAULT_MAX_ENUM_VALUES};
+use csv_gen::data::schema::SchemaBuilder;
+use csv_gen::data::types::{ColumnSpec, DataType, SchemaSpec};
+use csv_gen::generator::size_based::CsvGenerator;
+use std::fs;
+
+const SAMPLE: &str = "\
+id,email,site,price,joined,status,notes,score
+1,alice@example.com,example.com,12.50,2023-01-05,active,likes apples,3
+2,bob@test.org,test.org,3.99,2023-02-11,inactive,,7
+3,carol.w@mail.net,mail.net,100.00,2022-12-30,active,prefers email,3
+4,dan@x.io,x.io,7.25,2021-07-04,active,called twice,9
+5,eve@corp.com,corp.com,0.10,2020-03-15,inactive,,3
+6,fay@corp.com,corp.com,9.00,2020-03-16,active,vip since 2019,1
+";
+
+#[test]
+fn test_infer_column_types() -> anyhow::Result<()> {
+    let spec = infer_schema(SAMPLE.as_bytes(), DEFAULT_MAX_ENUM_VALUES)?;
+    assert_eq!(spec.sample_rows, 6);
+
+    let types: Vec<&DataType> = spec.columns.iter().map(|c| &c.data_type).collect();
+    assert!(matches!(types[0], DataType::UniqueId));
+    assert!(matches!(types[1], DataType::Email));
+    assert!(matches!(types[2], DataType::Domain));
+    assert!(matches!(types[3], DataType::Decimal { scale: 2 }));
+    assert!(matches!(types[4], DataType::Date));
+    assert!(matches!(types[5], DataType::Enum(values) if values == &["active", "inactive"]));
+    assert!(matches!(types[6], DataType::String));
+    assert!(matches!(types[7], DataType::Number));
+    Ok(())
+}
+
+#[test]
+fn test_infer_lengths_nulls_and_uniqueness() -> anyhow::Result<()> {
+    let spec = infer_schema(SAMPLE.as_bytes(), DEFAULT_MAX_ENUM_VALUES)?;
+
+    let notes = &spec.columns[6];
+    assert_eq!(notes.min_len, 12);
+    assert_eq!(notes.max_len, 14);
+    assert!((notes.null_rate - 2.0 / 6.0).abs() < 1e-9);
+    assert!(notes.unique);
+
+    let site = &spec.columns[2];
+    assert!(!site.unique);
+    assert_eq!(site.null_rate, 0.0);
+    Ok(())
+}
+
+#[test]
+fn test_unique_integers_are_ids_only_when_they_look_like_ids() -> anyhow::Result<()> {
+    let sample = "\
+code,quantity,order_id,rank
+10,250,907,3
+11,14,12,1
+12,3,455,2
+";
+    let spec = infer_schema(sample.as_bytes(), 2)?;
+    let types: Vec<&DataType> = spec.columns.iter().map(|c| &c.data_type).collect();
+    assert!(matches!(types[0], DataType::UniqueId), "Ascending");
+    assert!(matches!(types[1], DataType::Range { min: 3, max: 250 }), "Distinct counts keep their bounds");
+    assert!(matches!(types[2], DataType::UniqueId), "Named like an ID");
+    assert!(matches!(types[3], DataType::Range { min: 1, max: 3 }));
+    assert!(spec.columns.iter().all(|c| c.unique));
+    Ok(())
+}
+
+#[test]
+fn test_scale_inferred_schema() -> anyhow::Result<()> {
+    let spec = infer_schema(SAMPLE.as_bytes(), DEFAULT_MAX_ENUM_VALUES)?;
+
+    // Round-trip through a schema file like `csvgen infer` does
+    let schema_path = "test_inferred_schema.json";
+    spec.save_to_file(schema_path)?;
+    let spec = SchemaSpec::load_from_file(schema_path)?;
+    fs::remove_file(schema_path)?;
+
+    let target_size = 100 * 1024;
+    let num_rows = 1000;
+    let schema = SchemaBuilder::build_schema_from_spec(&spec, target_size, num_rows)?;
+    let mut generator = CsvGenerator::new(schema);
+
+    let output_path = "test_scaled_schema.csv";
+    generator.generate(output_path, num_rows)?;
+
+    let content = fs::read_to_string(output_path)?;
+    let header_line = content.lines().next().unwrap();
+    assert_eq!(header_line, "id,email,site,price,joined,status,notes,score");
+    assert_eq!(content.lines().count(), num_rows + 1);
+
+    // Nulls make the size vary from run to run, so allow 5%
+    let actual_size = fs::metadata(output_path)?.len() as usize;
+    let tolerance = target_size as f64 * 0.05;
+    assert!((actual_size as f64 - target_size as f64).abs() <= tolerance);
+
+    fs::remove_file(output_path)?;
+    Ok(())
+}
+
+#[test]
+fn test_size_without_free_text_must_be_reachable() {
+    let spec = SchemaSpec {
+        columns: vec![ColumnSpec::new("id", DataType::UniqueId), ColumnSpec::new("email", DataType::Email)],
+        sample_rows: 0,
+    };
+    assert!(SchemaBuilder::build_schema_from_spec(&spec, 1024 * 1024, 100).is_err(), "Too small to grow");
+    assert!(SchemaBuilder::build_schema_from_spec(&spec, 100, 100).is_err(), "Too wide to shrink");
+
+    let natural_size = "id,email\n".len() + (100.0 * SchemaBuilder::natural_spec_row_size(&spec, 100)) as usize;
+    assert!(SchemaBuilder::build_schema_from_spec(&spec, natural_size, 100).is_ok());
+}
@@ -0,0 +1,149 @@
+use csv_gen::CsvGen;
+use csv_gen::data::types::{ColumnSpec, CsvSchema, DataType};
+use csv_gen::generator::duplicates::DuplicateConfig;
+use csv_gen::generator::size_based::CsvGenerator;
+use csv_gen::manifest::{self, Manifest};
+use std::fs;
+use std::process::Command;
+
+fn schema() -> anyhow::Result<CsvSchema> {
+    let csv = CsvGen::builder()
+        .rows(500)
+        .column("id", DataType::UniqueId)
+        .column("age", DataType::Range { min: 18, max: 65 })
+        .column_spec(ColumnSpec::new("note", DataType::String).with_width(8).with_null_rate(0.2))
+        .build()?;
+    Ok(csv.schema().clone())
+}
+
+#[test]
+fn test_summaries_and_checksum() -> anyhow::Result<()> {
+    let output_path = "test_manifest_summary.csv";
+    CsvGenerator::new(schema()?).with_seed(11).generate(output_path, 500)?;
+
+    let (sha256, columns) = manifest::summarize_csv(output_path, &schema()?)?;
+    assert_eq!(sha256, manifest::sha256_file(output_path)?);
+    assert_eq!(sha256.len(), 64);
+
+    assert_eq!(columns[0].values, 500);
+    assert_eq!((columns[0].min, columns[0].max), (Some(1.0), Some(500.0)));
+    let age = &columns[1];
+    assert!(age.min.unwrap() >= 18.0 && age.max.unwrap() <= 65.0);
+    let note = &columns[2];
+    assert_eq!(note.nulls + note.values, 500);
+    assert!(note.nulls > 50 && note.nulls < 150, "About 20% nulls, got {}", note.nulls);
+    assert_eq!((note.min_len, note.max_len, note.mean_len), (8, 8, 8.0));
+    assert!(note.min.is_none(), "Text columns have no numeric range");
+
+    // Replaying the seed gives the same statistics without reading the file
+    let replayed = manifest::summarize_rows(&schema()?, 11, 500);
+    assert_eq!(replayed[2].nulls, note.nulls);
+    assert_eq!(replayed[1].max, age.max);
+
+    fs::remove_file(output_path)?;
+    Ok(())
+}
+
+#[test]
+fn test_seeded_duplicates_reproduce() -> anyhow::Result<()> {
+    let write = |path: &str| -> anyhow::Result<String> {
+        let duplicates = DuplicateConfig {
+            duplicate_rate: 0.1,
+            near_duplicate_rate: 0.1,
+            ground_truth_path: format!("{}.duplicates.csv", path),
+        };
+        CsvGenerator::new(schema()?).with_seed(5).with_duplicates(duplicates).generate(path, 500)?;
+        manifest::sha256_file(path)
+    };
+    assert_eq!(write("test_manifest_a.csv")?, write("test_manifest_b.csv")?);
+
+    for path in ["test_manifest_a.csv", "test_manifest_b.csv"] {
+        fs::remove_file(path)?;
+        fs::remove_file(format!("{}.duplicates.csv", path))?;
+    }
+    Ok(())
+}
+
+#[test]
+fn test_ranged_manifest_reproduces() -> anyhow::Result<()> {
+    let dir = "test_output/ranged_manifest";
+    fs::create_dir_all(dir)?;
+    let csvgen = |args: &[&str]| -> anyhow::Result<()> {
+        let status = Command::new(env!("CARGO_BIN_EXE_csv_gen")).args(args).status()?;
+        anyhow::ensure!(status.success(), "csvgen {:?} failed", args);
+        Ok(())
+    };
+    let part = format!("{}/part.csv", dir);
+    csvgen(&["-q", "--seed", "5", "generate", "--rows", "5000", "--size", "200KB", "--row-range", "1000..2000", "-o", &part])?;
+
+    let manifest = Manifest::load_from_file(&format!("{}.manifest.json", part))?;
+    assert_eq!(manifest.rows, 1000);
+    assert_eq!(manifest.planned_rows, Some(5000));
+    assert_eq!(manifest.row_range, Some(1000..2000));
+
+    let copy = format!("{}/copy.csv", dir);
+    csvgen(&["-q", "reproduce", &format!("{}.manifest.json", part), "-o", &copy])?;
+    assert_eq!(fs::read(&copy)?, fs::read(&part)?);
+
+    fs::remove_dir_all(dir)?;
+    Ok(())
+}
+
+#[test]
+fn test_reproduce_never_overwrites_on_mismatch() -> anyhow::Result<()> {
+    let dir = "test_output/reproduce_in_place";
+    fs::create_dir_all(dir)?;
+    let csvgen = |args: &[&str]| -> anyhow::Result<bool> {
+        Ok(Command::new(env!("CARGO_BIN_EXE_csv_gen")).args(args).status()?.success())
+    };
+    let path = format!("{}/data.sql", dir);
+    let manifest_path = format!("{}.manifest.json", path);
+    assert!(csvgen(&["-q", "--seed", "9", "--format", "sql", "generate", "--rows", "200", "--size", "20KB", "-o", &path])?);
+    let original = fs::read(&path)?;
+
+    let mut manifest = Manifest::load_from_file(&manifest_path)?;
+    let sha256 = std::mem::replace(&mut manifest.sha256, "00".repeat(32));
+    manifest.save_to_file(&manifest_path)?;
+    assert!(!csvgen(&["-q", "reproduce", &manifest_path])?);
+    assert_eq!(fs::read(&path)?, original, "A mismatch leaves the original alone");
+
+    // A matching copy takes the original's place, with the table still named after it
+    manifest.sha256 = sha256;
+    manifest.save_to_file(&manifest_path)?;
+    fs::remove_file(&path)?;
+    assert!(csvgen(&["-q", "reproduce", &manifest_path])?);
+    assert_eq!(fs::read(&path)?, original);
+    assert_eq!(fs::read_dir(dir)?.count(), 2, "No scratch copy is left behind");
+
+    fs::remove_dir_all(dir)?;
+    Ok(())
+}
+
+#[test]
+fn test_manifest_round_trip() -> anyhow::Result<()> {
+    let path = "test_manifest.json";
+    let manifest = Manifest {
+        version: manifest::VERSION.to_string(),
+        created_at: 1_700_000_000,
+        args: vec!["csvgen".to_string(), "--rows".to_string(), "500".to_string()],
+        seed: u64::MAX,
+        format: "csv".to_string(),
+        output: "output.csv".to_string(),
+        rows: 500,
+        planned_rows: Some(500),
+        row_range: None,
+        bytes: 12_345,
+        sha256: "00".repeat(32),
+        schema: schema()?,
+        columns: manifest::summarize_rows(&schema()?, 1, 10),
+    };
+    manifest.save_to_file(path)?;
+    let loaded = Manifest::load_from_file(path)?;
+    assert_eq!(loaded.seed, u64::MAX);
+    assert_eq!(loaded.schema.columns.len(), 3);
+    assert!(matches!(loaded.schema.columns[1].data_type, DataType::Range { min: 18, max: 65 }));
+    assert_eq!(loaded.columns[0].values, 10);
+
+    fs::remove_file(path)?;
+    Ok(())
+}
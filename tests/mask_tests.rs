@@ -0,0 +1,109 @@
+use csv_gen::generator::mask::{CsvMasker, MaskKind, MaskRule};
+
+const INPUT: &str = "\
+id,name,email,site,phone
+1,Alice Smith,alice@example.com,example.com,+1-555-0100
+2,Bob Brown,bob@corp.com,corp.com,+1-555-0199
+3,Carol White,carol.w@mail.net,corp.com,
+4,Bob Brown,bob@corp.com,mail.net,+44-20-7946-0958
+";
+
+fn rules() -> anyhow::Result<Vec<MaskRule>> {
+    ["name=name", "email=email", "site=domain", "phone"]
+        .iter()
+        .map(|r| r.parse())
+        .collect()
+}
+
+fn mask(masker: &mut CsvMasker) -> anyhow::Result<Vec<Vec<String>>> {
+    let mut output = Vec::new();
+    masker.mask(INPUT.as_bytes(), &mut output)?;
+    let mut reader = csv::Reader::from_reader(output.as_slice());
+    let mut rows = vec![reader.headers()?.iter().map(str::to_string).collect()];
+    for record in reader.records() {
+        rows.push(record?.iter().map(str::to_string).collect());
+    }
+    Ok(rows)
+}
+
+fn input_rows() -> Vec<Vec<String>> {
+    INPUT
+        .lines()
+        .map(|line| line.split(',').map(str::to_string).collect())
+        .collect()
+}
+
+#[test]
+fn test_mask_preserves_structure_and_lengths() -> anyhow::Result<()> {
+    let original = input_rows();
+    let masked = mask(&mut CsvMasker::new(rules()?, None))?;
+
+    assert_eq!(masked.len(), original.len());
+    assert_eq!(masked[0], original[0]);
+
+    for (orig, row) in original.iter().zip(&masked).skip(1) {
+        // The id column is not masked
+        assert_eq!(row[0], orig[0]);
+        for i in 1..orig.len() {
+            assert_eq!(row[i].len(), orig[i].len(), "Length changed: {} -> {}", orig[i], row[i]);
+        }
+        assert!(row[2].contains('@'), "Masked email lost its @: {}", row[2]);
+        assert!(row[3].contains('.') && !row[3].contains('@'), "Bad masked domain: {}", row[3]);
+        // Format masking keeps punctuation and digit positions
+        for (a, b) in orig[4].chars().zip(row[4].chars()) {
+            assert_eq!(a.is_ascii_digit(), b.is_ascii_digit());
+            if !a.is_ascii_alphanumeric() {
+                assert_eq!(a, b);
+            }
+        }
+    }
+    Ok(())
+}
+
+#[test]
+fn test_keyed_masking_is_deterministic() -> anyhow::Result<()> {
+    let first = mask(&mut CsvMasker::new(rules()?, Some("secret".to_string())))?;
+    let second = mask(&mut CsvMasker::new(rules()?, Some("secret".to_string())))?;
+    assert_eq!(first, second);
+
+    // Equal inputs map to equal outputs within the file as well
+    assert_eq!(first[2][1], first[4][1]);
+    assert_eq!(first[2][2], first[4][2]);
+    assert_eq!(first[2][3], first[3][3]);
+
+    let other_key = mask(&mut CsvMasker::new(rules()?, Some("other".to_string())))?;
+    assert_ne!(first, other_key);
+    Ok(())
+}
+
+#[test]
+fn test_mask_rule_parsing() -> anyhow::Result<()> {
+    let rule: MaskRule = "contact=email".parse()?;
+    assert_eq!(rule.column, "contact");
+    assert_eq!(rule.kind, MaskKind::Email);
+
+    let rule: MaskRule = "ssn".parse()?;
+    assert_eq!(rule.kind, MaskKind::Format);
+
+    assert!("col=bogus".parse::<MaskRule>().is_err());
+
+    let mut masker = CsvMasker::new(vec!["missing".parse()?], None);
+    assert!(masker.mask(INPUT.as_bytes(), Vec::new()).is_err());
+    Ok(())
+}
+
+#[test]
+fn test_keyed_names_and_emails_stay_distinct_and_valid() {
+    let mut masker = CsvMasker::new(Vec::new(), Some("secret".to_string()));
+    let names: std::collections::HashSet<String> = (0..2000)
+        .map(|i| masker.mask_value(MaskKind::Name, &format!("Person {:05}", i)))
+        .collect();
+    assert_eq!(names.len(), 2000, "Distinct names collided");
+
+    for value in ["a@b", "ab@c.io", "x@y.com", "alice@example.com"] {
+        let masked = masker.mask_value(MaskKind::Email, value);
+        let (local, domain) = masked.split_once('@').expect("An @");
+        assert!(!local.is_empty() && domain.contains('.'), "Invalid email: {}", masked);
+        assert_eq!(masked.len(), value.len().max(6));
+    }
+}
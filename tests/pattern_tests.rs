@@ -0,0 +1,147 @@
+use csv_gen::data::pattern::Pattern;
+use csv_gen::data::schema::SchemaBuilder;
+use csv_gen::data::types::DataType;
+use csv_gen::generator::size_based::CsvGenerator;
+use std::fs;
+
+#[test]
+fn test_fixed_length_pattern() -> anyhow::Result<()> {
+    let pattern = Pattern::new(r"[A-Z]{3}-\d{6}")?;
+    assert_eq!(pattern.min_len(), 10);
+    assert_eq!(pattern.max_len(), 10);
+
+    let mut rng = rand::thread_rng();
+    for _ in 0..100 {
+        let value = pattern.generate(&mut rng);
+        let (letters, digits) = value.split_once('-').unwrap();
+        assert_eq!(letters.len(), 3);
+        assert!(letters.chars().all(|c| c.is_ascii_uppercase()), "Bad letters: {}", value);
+        assert_eq!(digits.len(), 6);
+        assert!(digits.chars().all(|c| c.is_ascii_digit()), "Bad digits: {}", value);
+    }
+    Ok(())
+}
+
+#[test]
+fn test_alternation_groups_and_ranges() -> anyhow::Result<()> {
+    let pattern = Pattern::new("(?:ab|cde){1,3}x?")?;
+    assert_eq!(pattern.min_len(), 2);
+    assert_eq!(pattern.max_len(), 10);
+
+    let mut rng = rand::thread_rng();
+    for _ in 0..100 {
+        let value = pattern.generate(&mut rng);
+        let body = value.strip_suffix('x').unwrap_or(&value);
+        assert!(!body.is_empty());
+        assert!(body.replace("ab", "").replace("cde", "").is_empty(), "Bad value: {}", value);
+        assert!(value.len() >= 2 && value.len() <= 10);
+    }
+
+    // A range across ',' and '"' leaves them out, so the field is never quoted
+    let printable = Pattern::new("[ -~]{20}")?;
+    for _ in 0..100 {
+        let value = printable.generate(&mut rng);
+        assert!(!value.contains([',', '"']), "Would be quoted: {}", value);
+    }
+    Ok(())
+}
+
+#[test]
+fn test_matches_generated_values() -> anyhow::Result<()> {
+    let pattern = Pattern::with_max_len(r"(?:ab|cde)+-\d{2}x?", 12)?;
+    let mut rng = rand::thread_rng();
+    for _ in 0..100 {
+        let value = pattern.generate(&mut rng);
+        assert!(pattern.matches(&value), "Should match its own value: {}", value);
+    }
+    assert!(pattern.matches("cdeab-42x"));
+    assert!(!pattern.matches("cdeab-42xx"));
+    assert!(!pattern.matches("ab-4"));
+    assert!(!pattern.matches("abababababab-42"), "Longer than the cap");
+    Ok(())
+}
+
+#[test]
+fn test_unbounded_pattern_requires_cap() -> anyhow::Result<()> {
+    assert!(Pattern::new(r"SKU_\w+").is_err());
+    assert!(Pattern::with_max_len(r"SKU_\w+", 3).is_err());
+
+    let pattern = Pattern::with_max_len(r"SKU_\w+", 12)?;
+    assert_eq!(pattern.min_len(), 5);
+    assert_eq!(pattern.max_len(), 12);
+
+    let mut rng = rand::thread_rng();
+    for _ in 0..100 {
+        let value = pattern.generate(&mut rng);
+        assert!(value.starts_with("SKU_"));
+        assert!(value.len() >= 5 && value.len() <= 12, "Out of range: {}", value);
+    }
+    Ok(())
+}
+
+#[test]
+fn test_invalid_patterns() {
+    assert!(Pattern::new("(abc").is_err());
+    assert!(Pattern::new("abc)").is_err());
+    assert!(Pattern::new("[a-z").is_err());
+    assert!(Pattern::new("*a").is_err());
+    assert!(Pattern::new("a{3,1}").is_err());
+    assert!(Pattern::new("[z-a]").is_err());
+    assert!(Pattern::new("(a{4294967296}){4294967296}").is_err(), "Length overflows");
+    assert!(Pattern::new("(?:a{4294967296}){4294967296}b*{0}").is_err());
+    // Values the CSV writer would quote
+    for source in ["a,b", r#"say "hi""#, r"a\nb", r"a\tb", "[a,b]", r"[\n-z]"] {
+        assert!(Pattern::new(source).is_err(), "Accepted {}", source);
+    }
+}
+
+#[test]
+fn test_pattern_columns_in_schema() -> anyhow::Result<()> {
+    let target_size = 1024;
+    let num_rows = 10;
+    let patterns = vec![Pattern::new(r"[A-Z]{3}-\d{6}")?, Pattern::new("SKU_[a-f0-9]{8}")?];
+
+    let schema = SchemaBuilder::build_schema_with_patterns(target_size, num_rows, 4, 10, 0, 0, &patterns)?;
+    let pattern_columns: Vec<_> = schema
+        .columns
+        .iter()
+        .filter(|c| matches!(c.data_type, DataType::Pattern(_)))
+        .collect();
+    assert_eq!(pattern_columns.len(), 2);
+    assert_eq!(pattern_columns[0].size_bytes, 10);
+    assert_eq!(pattern_columns[1].size_bytes, 12);
+
+    let mut generator = CsvGenerator::new(schema);
+    let output_path = "test_pattern_columns.csv";
+    generator.generate(output_path, num_rows)?;
+
+    let content = fs::read_to_string(output_path)?;
+    let headers: Vec<&str> = content.lines().next().unwrap().split(',').collect();
+    for line in content.lines().skip(1) {
+        let values: Vec<&str> = line.split(',').collect();
+        for (i, header) in headers.iter().enumerate() {
+            if header.starts_with("pattern_2") {
+                assert!(values[i].starts_with("SKU_"), "Pattern column mismatch: {}", values[i]);
+            }
+        }
+    }
+
+    // Fixed-length patterns keep the size targeting exact
+    let actual_size = fs::metadata(output_path)?.len() as usize;
+    let tolerance = target_size as f64 * 0.02;
+    assert!((actual_size as f64 - target_size as f64).abs() <= tolerance);
+
+    fs::remove_file(output_path)?;
+    Ok(())
+}
+
+#[test]
+fn test_pattern_wider_than_rows_is_impossible() -> anyhow::Result<()> {
+    let too_wide = [Pattern::new("a{5000}")?];
+    assert!(SchemaBuilder::build_schema_with_patterns(100_000, 100, 1, 10, 0, 0, &too_wide).is_err());
+
+    // Rejected before any header is padded to its width
+    let huge = [Pattern::new("a{99999999999}")?];
+    assert!(SchemaBuilder::build_schema_with_patterns(100_000, 100, 1, 10, 0, 0, &huge).is_err());
+    Ok(())
+}
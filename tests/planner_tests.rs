@@ -0,0 +1,99 @@
+use csv_gen::data::headers::HeaderStrategy;
+use csv_gen::data::inference::{infer_schema, DEFAULT_MAX_ENUM_VALUES};
+use csv_gen::data::planner::{plan_from_spec, plan_generated, ColumnOptions, Solved, SolverObjective};
+use csv_gen::generator::size_based::CsvGenerator;
+use std::fs;
+
+fn options(min_columns: usize, max_columns: usize) -> ColumnOptions {
+    ColumnOptions {
+        min_columns,
+        max_columns,
+        email_columns: 0,
+        domain_columns: 0,
+        fixed_columns: Vec::new(),
+        header_strategy: HeaderStrategy::default(),
+        objective: SolverObjective::default(),
+    }
+}
+
+#[test]
+fn test_rows_only_derives_size() -> anyhow::Result<()> {
+    let plan = plan_generated(None, Some(500), &options(2, 100))?;
+    assert_eq!(plan.solved, Solved::Size);
+    assert_eq!(plan.num_rows, 500);
+
+    let output_path = "test_output/planner_rows_only.csv";
+    fs::create_dir_all("test_output")?;
+    let mut generator = CsvGenerator::new(plan.schema);
+    generator.generate(output_path, plan.num_rows)?;
+
+    let actual_size = fs::metadata(output_path)?.len() as usize;
+    assert_eq!(actual_size, plan.target_size, "Reported size should match the file");
+
+    fs::remove_file(output_path)?;
+    Ok(())
+}
+
+#[test]
+fn test_size_only_derives_rows() -> anyhow::Result<()> {
+    let target_size = 100 * 1024;
+    let plan = plan_generated(Some(target_size), None, &options(6, 6))?;
+    assert_eq!(plan.solved, Solved::Rows);
+    assert_eq!(plan.schema.columns.len(), 6);
+    assert!(plan.num_rows > 0);
+
+    let output_path = "test_output/planner_size_only.csv";
+    fs::create_dir_all("test_output")?;
+    let mut generator = CsvGenerator::new(plan.schema);
+    let rows = generator.generate(output_path, plan.num_rows)?;
+    assert_eq!(rows, plan.num_rows);
+
+    let actual_size = fs::metadata(output_path)?.len() as usize;
+    let error = actual_size.abs_diff(target_size) as f64 / target_size as f64;
+    assert!(error < 0.01, "Size error too large: {} vs {}", actual_size, target_size);
+
+    fs::remove_file(output_path)?;
+    Ok(())
+}
+
+#[test]
+fn test_planner_rejects_impossible_constraints() {
+    assert!(plan_generated(None, None, &options(2, 100)).is_err());
+    assert!(plan_generated(Some(100), None, &options(10, 10)).is_err());
+    assert!(plan_generated(None, Some(10), &options(5, 3)).is_err());
+
+    // Sizes and row counts that overflow or need rows too long to lay out
+    assert!(plan_generated(None, Some(usize::MAX), &options(2, 100)).is_err());
+    assert!(plan_generated(Some(usize::MAX), Some(1), &options(2, 100)).is_err());
+    let wide = ColumnOptions {
+        objective: SolverObjective::CellWidth(usize::MAX),
+        ..options(2, 100)
+    };
+    assert!(plan_generated(None, Some(10), &wide).is_err());
+}
+
+#[test]
+fn test_plan_reports_the_size_it_reaches() -> anyhow::Result<()> {
+    // A lone ID column can't grow to the requested size
+    let plan = plan_generated(Some(10_000), Some(10), &options(1, 1))?;
+    assert_eq!(plan.requested_size, Some(10_000));
+    assert_eq!(plan.target_size, plan.schema.header_size + 1 + 10 * plan.schema.target_row_size);
+    assert!(plan.target_size < 100);
+    Ok(())
+}
+
+#[test]
+fn test_spec_plan_from_rows_only() -> anyhow::Result<()> {
+    let sample = "id,name,notes\n1,alice,likes apples\n2,bob,prefers email\n3,carol,\n";
+    let spec = infer_schema(sample.as_bytes(), DEFAULT_MAX_ENUM_VALUES)?;
+
+    let plan = plan_from_spec(&spec, None, Some(1000))?;
+    assert_eq!(plan.solved, Solved::Size);
+    let names: Vec<&str> = plan.schema.columns.iter().map(|c| c.name.as_str()).collect();
+    assert_eq!(names, ["id", "name", "notes"]);
+
+    let plan = plan_from_spec(&spec, Some(plan.target_size), None)?;
+    assert_eq!(plan.solved, Solved::Rows);
+    assert!(plan.num_rows.abs_diff(1000) < 50, "Expected about 1000 rows, got {}", plan.num_rows);
+    Ok(())
+}
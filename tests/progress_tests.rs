@@ -0,0 +1,75 @@
+use csv_gen::CsvGen;
+use csv_gen::data::types::DataType;
+use csv_gen::generator::fixed_width::FixedWidthGenerator;
+use csv_gen::utils::progress::{Progress, ProgressEvent};
+use std::sync::{Arc, Mutex};
+
+fn recorder() -> (Progress, Arc<Mutex<Vec<ProgressEvent>>>) {
+    let events = Arc::new(Mutex::new(Vec::new()));
+    let received = Arc::clone(&events);
+    let progress = Progress::callback(move |event| received.lock().unwrap().push(*event));
+    (progress, events)
+}
+
+#[test]
+fn test_callback_receives_the_final_counts() -> anyhow::Result<()> {
+    let events = Arc::new(Mutex::new(Vec::new()));
+    let received = Arc::clone(&events);
+    let csv = CsvGen::builder()
+        .rows(500)
+        .column("id", DataType::UniqueId)
+        .column("name", DataType::Name)
+        .seed(7)
+        .on_progress(move |event| received.lock().unwrap().push(*event))
+        .build()?;
+
+    let mut output = Vec::new();
+    csv.write_to(&mut output)?;
+
+    let events = events.lock().unwrap();
+    let last = events.last().expect("A final event");
+    assert!(last.done);
+    assert_eq!(last.rows, 500);
+    assert_eq!(last.total_rows, Some(500));
+    assert_eq!(last.bytes, output.len() as u64, "Counts the header and every row");
+    assert_eq!(last.eta_secs, Some(0.0));
+    assert_eq!(events.iter().filter(|event| event.done).count(), 1);
+    Ok(())
+}
+
+#[test]
+fn test_fixed_width_reports_bytes_against_the_record_size() -> anyhow::Result<()> {
+    let csv = CsvGen::builder().rows(100).column("id", DataType::UniqueId).seed(1).build()?;
+    let (progress, events) = recorder();
+    let mut generator = FixedWidthGenerator::new(csv.schema().clone()).with_progress(progress);
+    let mut output = Vec::new();
+    generator.write(&mut output, 100)?;
+
+    let events = events.lock().unwrap();
+    let last = events.last().expect("A final event");
+    assert_eq!(last.total_bytes, Some(output.len() as u64));
+    assert_eq!(last.bytes, output.len() as u64);
+    Ok(())
+}
+
+#[test]
+fn test_modes_parse_and_events_serialize() -> anyhow::Result<()> {
+    assert!(matches!("bar".parse::<Progress>()?, Progress::Bar));
+    assert!(matches!("JSON".parse::<Progress>()?, Progress::Json));
+    assert!(matches!("none".parse::<Progress>()?, Progress::Hidden));
+    assert!("verbose".parse::<Progress>().is_err());
+
+    let (progress, events) = recorder();
+    let mut tracker = progress.start(Some(10), None);
+    tracker.inc(4, 100);
+    let event = tracker.event(false);
+    assert_eq!((event.rows, event.bytes, event.total_bytes), (4, 100, None));
+    tracker.finish();
+    assert_eq!(events.lock().unwrap().len(), 1, "Only the final event within the first second");
+
+    let json: serde_json::Value = serde_json::to_value(event)?;
+    for field in ["rows", "total_rows", "bytes", "total_bytes", "elapsed_secs", "rows_per_second", "bytes_per_second", "eta_secs", "done"] {
+        assert!(json.get(field).is_some(), "Missing {}", field);
+    }
+    Ok(())
+}
@@ -0,0 +1,98 @@
+use csv_gen::{CsvGen, Error};
+use csv_gen::cli::RowRange;
+use csv_gen::data::DataGenerator;
+use csv_gen::data::timeseries::{SeriesKind, SeriesSpec, TimestampFormat, TimestampSpec};
+use csv_gen::data::types::{ColumnSpec, DataType};
+use csv_gen::generator::rows::RowGenerator;
+use std::ops::Range;
+
+/// Every kind of column, including the ones that carry state from row to row.
+fn csv(threads: usize) -> anyhow::Result<CsvGen> {
+    let timestamps = TimestampSpec {
+        start_ms: 1_700_000_000_000,
+        interval_ms: 1000,
+        jitter_ms: 200,
+        gap_rate: 0.05,
+        gap_ms: 60_000,
+        out_of_order_rate: 0.01,
+        format: TimestampFormat::Iso8601Millis,
+    };
+    let walk = SeriesSpec { kind: SeriesKind::RandomWalk { start: 100.0, step: 1.0 }, precision: 2 };
+    let seasonal = SeriesSpec { kind: SeriesKind::Seasonal { base: 50.0, amplitude: 5.0, period: 24.0, noise: 1.0 }, precision: 1 };
+    Ok(CsvGen::builder()
+        .rows(5000)
+        .column("id", DataType::UniqueId)
+        .column("at", DataType::Timestamp(timestamps))
+        .column_spec(ColumnSpec::new("walk", DataType::Series(walk)).with_width(9))
+        .column_spec(ColumnSpec::new("seasonal", DataType::Series(seasonal)).with_width(6))
+        .column_spec(ColumnSpec::new("email", DataType::Email).with_null_rate(0.1))
+        .column("name", DataType::Name)
+        .seed(21)
+        .threads(threads)
+        .build()?)
+}
+
+#[test]
+fn test_any_row_matches_the_sequential_run() -> anyhow::Result<()> {
+    let csv = csv(1)?;
+    let sequential: Vec<Vec<String>> = csv.iter().collect();
+
+    let mut rows = RowGenerator::new(csv.schema().clone(), DataGenerator::with_seed(21));
+    for index in [4321, 17, 0, 4999, 2500, 2501] {
+        assert_eq!(rows.row(index), sequential[index as usize], "Row {}", index);
+    }
+    assert_eq!(sequential[4321][0], "4322", "IDs are the row number plus one");
+
+    let range: Vec<Vec<String>> = csv.iter_range(3000..3100).collect();
+    assert_eq!(range, sequential[3000..3100]);
+    let mut skipping = csv.iter();
+    assert_eq!(skipping.nth(1234).as_ref(), Some(&sequential[1234]));
+    assert_eq!(skipping.next().as_ref(), Some(&sequential[1235]));
+    Ok(())
+}
+
+#[test]
+fn test_ranges_and_threads_write_the_same_bytes() -> anyhow::Result<()> {
+    let sequential = csv(1)?;
+    let mut full = Vec::new();
+    sequential.write_to(&mut full)?;
+
+    let mut pieces = Vec::new();
+    for range in [0..1200, 1200..1201, 1201..4000, 4000..5000] {
+        sequential.write_range_to(&mut pieces, range)?;
+    }
+    assert_eq!(pieces, full, "Pieces concatenate into the full file");
+
+    for threads in [2, 3, 8] {
+        let mut parallel = Vec::new();
+        csv(threads)?.write_to(&mut parallel)?;
+        assert!(parallel == full, "{} threads changed the output", threads);
+    }
+
+    // Each piece can itself be written in parallel
+    let mut piece = Vec::new();
+    csv(4)?.write_range_to(&mut piece, 2345..4567)?;
+    let offset = full.split(|&b| b == b'\n').take(2346).map(|line| line.len() + 1).sum::<usize>();
+    assert_eq!(piece, full[offset..offset + piece.len()]);
+    assert_eq!(piece.iter().filter(|&&b| b == b'\n').count(), 4567 - 2345);
+
+    // Ranges outside the plan are argument errors, not I/O failures
+    for range in [5000..5100, 4900..5001, Range { start: 30, end: 20 }] {
+        let error = sequential.write_range_to(Vec::new(), range.clone()).expect_err("Outside the plan");
+        assert!(matches!(error, Error::ImpossibleConstraints(_)), "{:?}: {}", range, error);
+    }
+    assert_eq!(sequential.iter_range(4990..6000).count(), 10, "Iterating clips to the plan");
+    Ok(())
+}
+
+#[test]
+fn test_row_range_arguments() -> anyhow::Result<()> {
+    assert_eq!("1M..2M".parse::<RowRange>()?, RowRange { start: 1_000_000, end: Some(2_000_000) });
+    assert_eq!("2.5k..".parse::<RowRange>()?, RowRange { start: 2500, end: None });
+    assert!("1000".parse::<RowRange>().is_err());
+
+    assert_eq!("10..".parse::<RowRange>()?.resolve(100)?, 10..100);
+    assert!("10..200".parse::<RowRange>()?.resolve(100).is_err(), "Past the planned rows");
+    assert!("50..50".parse::<RowRange>()?.resolve(100).is_err(), "Empty");
+    Ok(())
+}
@@ -0,0 +1,75 @@
+use csv_gen::CsvGen;
+use csv_gen::data::types::{ColumnSpec, DataType};
+use csv_gen::generator::size_based::CsvGenerator;
+use csv_gen::report::{HyperLogLog, ReportFormat};
+
+#[test]
+fn test_distinct_counts_stay_within_a_few_percent() {
+    let mut sketch = HyperLogLog::new();
+    for i in 0..100_000 {
+        sketch.insert(format!("value-{}", i));
+        // Repeats don't count twice
+        sketch.insert(format!("value-{}", i / 2));
+    }
+    let estimate = sketch.estimate() as f64;
+    assert!((estimate - 100_000.0).abs() / 100_000.0 < 0.03, "Estimated {}", estimate);
+
+    let mut small = HyperLogLog::new();
+    for value in ["a", "b", "c", "a", "b", "a"] {
+        small.insert(value);
+    }
+    assert_eq!(small.estimate(), 3);
+}
+
+#[test]
+fn test_report_matches_the_written_file() -> anyhow::Result<()> {
+    let csv = CsvGen::builder()
+        .rows(1000)
+        .column("id", DataType::UniqueId)
+        .column_spec(ColumnSpec::new("status", DataType::Enum(vec!["open".into(), "closed".into()])).with_null_rate(0.2))
+        .column("score", DataType::Range { min: 1, max: 9 })
+        .build()?;
+    let mut generator = CsvGenerator::new(csv.schema().clone()).with_seed(5).without_progress().with_report();
+    let mut output = Vec::new();
+    generator.write(&mut output, 1000)?;
+    let report = generator.report().expect("A report").clone().with_target(output.len() as u64 + 100);
+
+    assert_eq!(report.rows, 1000);
+    assert_eq!(report.total_bytes, output.len() as u64);
+    assert_eq!(report.header_bytes, "id,status,score\n".len() as u64);
+    assert_eq!(report.header_bytes + report.data_bytes, report.total_bytes);
+    assert!(report.size_error.expect("A size error") < 0.0, "Under the target");
+
+    let [id, status, score] = &report.columns[..] else { panic!("Three columns") };
+    assert_eq!((id.summary.min, id.summary.max, id.summary.sum), (Some(1.0), Some(1000.0), Some(500_500.0)));
+    assert!((950..=1050).contains(&id.distinct));
+    assert_eq!(status.distinct, 2);
+    assert_eq!(status.summary.nulls + status.summary.values, 1000);
+    assert!(status.summary.nulls > 100 && status.summary.sum.is_none());
+    assert!(score.summary.min >= Some(1.0) && score.summary.max <= Some(9.0));
+    Ok(())
+}
+
+#[test]
+fn test_report_formats() -> anyhow::Result<()> {
+    assert_eq!("table".parse::<ReportFormat>()?, ReportFormat::Table);
+    assert_eq!("JSON".parse::<ReportFormat>()?, ReportFormat::Json);
+    assert!("csv".parse::<ReportFormat>().is_err());
+
+    let csv = CsvGen::builder().rows(50).column("id", DataType::UniqueId).column("name", DataType::Name).build()?;
+    let mut generator = CsvGenerator::new(csv.schema().clone()).without_progress().with_report();
+    generator.write(Vec::new(), 50)?;
+    let report = generator.report().expect("A report").clone().with_target(2000);
+
+    let table = report.to_table();
+    assert!(table.starts_with("50 rows in "));
+    assert!(table.contains("target 2.00 KB"));
+    assert!(table.lines().any(|line| line.starts_with("name ") && line.ends_with('-')), "No sum for text:\n{}", table);
+
+    let json: serde_json::Value = serde_json::from_str(&report.to_json()?)?;
+    assert_eq!(json["rows"], 50);
+    assert_eq!(json["target_bytes"], 2000);
+    assert_eq!(json["columns"][0]["name"], "id");
+    assert!(json["columns"][0]["distinct"].is_u64());
+    Ok(())
+}
@@ -0,0 +1,64 @@
+use csv_gen::data::schema::SchemaBuilder;
+use csv_gen::data::types::DataType;
+use csv_gen::data::DataGenerator;
+use csv_gen::generator::rows::RowGenerator;
+use csv_gen::CsvGen;
+
+#[test]
+fn test_rows_follow_schema() -> anyhow::Result<()> {
+    let schema = SchemaBuilder::build_schema(20_000, 100, 5, 5, 0, 0)?;
+    let widths: Vec<usize> = schema.columns.iter().map(|c| c.size_bytes).collect();
+    let rows = RowGenerator::new(schema, DataGenerator::with_seed(3));
+
+    for (i, row) in rows.take(50).enumerate() {
+        assert_eq!(row.len(), widths.len());
+        assert_eq!(row[0].parse::<usize>()?, i + 1);
+        for (value, width) in row.iter().zip(&widths) {
+            assert_eq!(value.len(), *width);
+        }
+    }
+    Ok(())
+}
+
+#[test]
+fn test_iter_matches_written_output() -> anyhow::Result<()> {
+    let csv = CsvGen::builder().size(16 * 1024).rows(100).columns(4).seed(11).build()?;
+
+    let mut output = Vec::new();
+    csv.write_to(&mut output)?;
+    let mut reader = csv::Reader::from_reader(output.as_slice());
+    let written: Vec<Vec<String>> = reader
+        .records()
+        .map(|record| record.map(|r| r.iter().map(str::to_string).collect()))
+        .collect::<Result<_, _>>()?;
+
+    let generated: Vec<Vec<String>> = csv.iter().collect();
+    assert_eq!(generated, written);
+    Ok(())
+}
+
+#[test]
+fn test_collect_rows_into_structs() -> anyhow::Result<()> {
+    struct Person {
+        id: u64,
+        email: String,
+    }
+
+    let csv = CsvGen::builder()
+        .rows(20)
+        .column("id", DataType::UniqueId)
+        .column("email", DataType::Email)
+        .build()?;
+    let people: Vec<Person> = csv
+        .iter()
+        .map(|row| Person {
+            id: row[0].parse().unwrap_or_default(),
+            email: row[1].clone(),
+        })
+        .collect();
+
+    assert_eq!(people.len(), 20);
+    assert_eq!(people[19].id, 20);
+    assert!(people.iter().all(|p| p.email.contains('@')));
+    Ok(())
+}
@@ -0,0 +1,160 @@
+use csv_gen::data::types::{ColumnSpec, DataType, SchemaSpec};
+use csv_gen::server::{ServeOptions, Server};
+use std::collections::HashMap;
+use std::fs;
+use std::io::{Read, Write};
+use std::net::{SocketAddr, TcpStream};
+use std::thread;
+use std::time::{Duration, Instant};
+
+struct Response {
+    status: u16,
+    headers: HashMap<String, String>,
+    body: Vec<u8>,
+}
+
+fn start(options: ServeOptions) -> anyhow::Result<SocketAddr> {
+    let server = Server::bind("127.0.0.1:0", options)?;
+    let addr = server.local_addr()?;
+    thread::spawn(move || server.run());
+    Ok(addr)
+}
+
+fn request(addr: SocketAddr, method: &str, target: &str, headers: &[&str]) -> anyhow::Result<Response> {
+    let mut stream = TcpStream::connect(addr)?;
+    let mut head = format!("{} {} HTTP/1.1\r\nHost: localhost\r\n", method, target);
+    for header in headers {
+        head.push_str(header);
+        head.push_str("\r\n");
+    }
+    head.push_str("\r\n");
+    stream.write_all(head.as_bytes())?;
+
+    let mut raw = Vec::new();
+    stream.read_to_end(&mut raw)?;
+    let split = raw.windows(4).position(|w| w == b"\r\n\r\n").expect("response head");
+    let head = String::from_utf8(raw[..split].to_vec())?;
+    let mut lines = head.lines();
+    let status = lines.next().unwrap_or_default().split(' ').nth(1).unwrap_or_default().parse()?;
+    let headers: HashMap<String, String> = lines
+        .filter_map(|line| line.split_once(": "))
+        .map(|(name, value)| (name.to_lowercase(), value.to_string()))
+        .collect();
+
+    let mut body = raw[split + 4..].to_vec();
+    if headers.get("transfer-encoding").map(String::as_str) == Some("chunked") {
+        body = dechunk(&body);
+    }
+    Ok(Response { status, headers, body })
+}
+
+fn dechunk(mut raw: &[u8]) -> Vec<u8> {
+    let mut body = Vec::new();
+    loop {
+        let line_end = raw.windows(2).position(|w| w == b"\r\n").expect("chunk size");
+        let size = usize::from_str_radix(std::str::from_utf8(&raw[..line_end]).unwrap(), 16).unwrap();
+        if size == 0 {
+            return body;
+        }
+        body.extend_from_slice(&raw[line_end + 2..line_end + 2 + size]);
+        raw = &raw[line_end + 4 + size..];
+    }
+}
+
+#[test]
+fn test_generate_streams_chunked_csv() -> anyhow::Result<()> {
+    let addr = start(ServeOptions::default())?;
+
+    let response = request(addr, "GET", "/generate?rows=500&columns=4", &[])?;
+    assert_eq!(response.status, 200);
+    assert_eq!(response.headers["transfer-encoding"], "chunked");
+    let mut reader = csv::Reader::from_reader(response.body.as_slice());
+    assert_eq!(reader.headers()?.len(), 4);
+    assert_eq!(reader.records().count(), 500);
+
+    let seeded = "/generate?size=20KB&seed=42";
+    let first = request(addr, "GET", seeded, &[])?;
+    let second = request(addr, "GET", seeded, &[])?;
+    assert_eq!(first.headers["content-length"], first.body.len().to_string());
+    assert_eq!(first.body, second.body, "Seeded responses should be identical");
+    assert!(first.body.len().abs_diff(20_000) < 200);
+
+    assert_eq!(request(addr, "GET", "/generate", &[])?.status, 400);
+    assert_eq!(request(addr, "GET", "/generate?rows=ten", &[])?.status, 400);
+    assert_eq!(request(addr, "GET", "/nowhere", &[])?.status, 404);
+    assert_eq!(request(addr, "POST", "/generate?rows=1", &[])?.status, 405);
+    Ok(())
+}
+
+#[test]
+fn test_range_requests_on_seeded_data() -> anyhow::Result<()> {
+    let addr = start(ServeOptions::default())?;
+    let target = "/generate?rows=2000&seed=7";
+    let full = request(addr, "GET", target, &[])?.body;
+
+    let head = request(addr, "HEAD", target, &[])?;
+    assert_eq!(head.headers["content-length"], full.len().to_string());
+    assert!(head.body.is_empty());
+
+    let middle = request(addr, "GET", target, &["Range: bytes=1000-1999"])?;
+    assert_eq!(middle.status, 206);
+    assert_eq!(middle.headers["content-range"], format!("bytes 1000-1999/{}", full.len()));
+    assert_eq!(middle.body, full[1000..2000]);
+
+    let tail = request(addr, "GET", target, &["Range: bytes=-100"])?;
+    assert_eq!(tail.body, full[full.len() - 100..]);
+
+    let open = request(addr, "GET", target, &["Range: bytes=5000-"])?;
+    assert_eq!(open.body, full[5000..]);
+
+    // Past the first block of rows, generation starts partway through
+    let late = full.len() * 3 / 4;
+    let deep = request(addr, "GET", target, &[&format!("Range: bytes={}-{}", late, late + 99)])?;
+    assert_eq!(deep.body, full[late..late + 100]);
+    let last = request(addr, "GET", target, &["Range: bytes=-1"])?;
+    assert_eq!(last.body, b"\n");
+
+    let beyond = request(addr, "GET", target, &[&format!("Range: bytes={}-", full.len())])?;
+    assert_eq!(beyond.status, 416);
+    Ok(())
+}
+
+#[test]
+fn test_named_schemas_latency_and_throttle() -> anyhow::Result<()> {
+    let schema_dir = "test_output/serve_schemas";
+    fs::create_dir_all(schema_dir)?;
+    let spec = SchemaSpec {
+        columns: vec![ColumnSpec::new("id", DataType::UniqueId), ColumnSpec::new("email", DataType::Email)],
+        sample_rows: 0,
+    };
+    spec.save_to_file(&format!("{}/users.json", schema_dir))?;
+
+    let addr = start(ServeOptions {
+        schema_dir: schema_dir.into(),
+        latency: Duration::from_millis(200),
+        max_rows: 1000,
+        ..ServeOptions::default()
+    })?;
+
+    let started = Instant::now();
+    let users = request(addr, "GET", "/generate?rows=10&schema=users", &[])?;
+    assert!(started.elapsed() >= Duration::from_millis(200));
+    assert!(users.body.starts_with(b"id,email\n"));
+    assert_eq!(request(addr, "GET", "/generate?rows=10&schema=missing", &[])?.status, 400);
+    assert_eq!(request(addr, "GET", "/generate?rows=10&schema=..%2Fusers", &[])?.status, 400);
+
+    // Over the limits, given or derived
+    let limited = |target: &str| request(addr, "GET", target, &[]).map(|response| response.status);
+    assert_eq!(limited("/generate?rows=1001&latency=0ms")?, 400);
+    assert_eq!(limited("/generate?size=1MB&schema=users&latency=0ms")?, 400);
+    assert_eq!(limited("/generate?rows=10&size=2GB&latency=0ms")?, 400);
+    assert_eq!(limited("/generate?rows=1000&schema=users&latency=0ms")?, 200);
+
+    let started = Instant::now();
+    let throttled = request(addr, "GET", "/generate?size=8KiB&latency=0ms&throttle=16KiB/s", &[])?;
+    assert!(throttled.body.len() > 8000);
+    assert!(started.elapsed() >= Duration::from_millis(400), "Took {:?}", started.elapsed());
+
+    fs::remove_dir_all(schema_dir)?;
+    Ok(())
+}
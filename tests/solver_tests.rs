@@ -0,0 +1,82 @@
+use csv_gen::data::headers::HeaderStrategy;
+use csv_gen::data::planner::{ColumnOptions, SolverObjective};
+use csv_gen::data::schema::SchemaBuilder;
+use std::collections::HashSet;
+use std::time::{Duration, Instant};
+
+fn options(min_columns: usize, max_columns: usize, objective: SolverObjective) -> ColumnOptions {
+    ColumnOptions {
+        min_columns,
+        max_columns,
+        email_columns: 0,
+        domain_columns: 0,
+        fixed_columns: Vec::new(),
+        header_strategy: HeaderStrategy::default(),
+        objective,
+    }
+}
+
+fn planned_size(schema: &csv_gen::data::CsvSchema, num_rows: usize) -> usize {
+    schema.header_size + 1 + num_rows * schema.target_row_size
+}
+
+#[test]
+fn test_plans_100k_columns_quickly() -> anyhow::Result<()> {
+    let target_size = 2 * 1024 * 1024 * 1024;
+    let num_rows = 1000;
+
+    // Every objective only names the headers of the count it settles on
+    let objectives = [
+        (100_000, SolverObjective::FewestColumns),
+        (2, SolverObjective::MostColumns),
+        (2, SolverObjective::CellWidth(10)),
+    ];
+    for (min_columns, objective) in objectives {
+        let start = Instant::now();
+        let schema = SchemaBuilder::build_schema_with_options(target_size, num_rows, &options(min_columns, 100_000, objective))?;
+        let elapsed = start.elapsed();
+
+        assert_eq!(schema.columns.len(), 100_000);
+        let names: HashSet<&str> = schema.columns.iter().map(|c| c.name.as_str()).collect();
+        assert_eq!(names.len(), 100_000);
+        let error = planned_size(&schema, num_rows).abs_diff(target_size) as f64 / target_size as f64;
+        assert!(error < 0.001, "Size error too large");
+        // Generous bound so unoptimized test builds pass too
+        assert!(elapsed < Duration::from_secs(3), "Planning for {:?} took {:?}", objective, elapsed);
+    }
+    Ok(())
+}
+
+#[test]
+fn test_objectives_choose_column_counts() -> anyhow::Result<()> {
+    let target_size = 1024 * 1024;
+    let num_rows = 1000;
+
+    let fewest = SchemaBuilder::build_schema_with_options(target_size, num_rows, &options(2, 100_000, SolverObjective::FewestColumns))?;
+    assert_eq!(fewest.columns.len(), 2);
+
+    let most = SchemaBuilder::build_schema_with_options(target_size, num_rows, &options(2, 100_000, SolverObjective::MostColumns))?;
+    let widest = SchemaBuilder::build_schema_with_options(target_size, num_rows, &options(2, most.columns.len() + 1, SolverObjective::FewestColumns))?;
+    assert!(most.columns.len() > 100, "Expected many columns, got {}", most.columns.len());
+    assert!(most.columns[1..].iter().all(|c| c.size_bytes >= 2));
+    assert!(widest.columns.len() <= most.columns.len() + 1);
+
+    let cells = SchemaBuilder::build_schema_with_options(target_size, num_rows, &options(2, 100_000, SolverObjective::CellWidth(20)))?;
+    let regular: Vec<usize> = cells.columns[1..].iter().map(|c| c.size_bytes).collect();
+    let average = regular.iter().sum::<usize>() as f64 / regular.len() as f64;
+    assert!((18.0..=22.0).contains(&average), "Average cell width {}", average);
+
+    for schema in [&fewest, &most, &cells] {
+        let error = planned_size(schema, num_rows).abs_diff(target_size);
+        assert!(error < 1000, "Size error {} for {} columns", error, schema.columns.len());
+    }
+    Ok(())
+}
+
+#[test]
+fn test_objective_parsing() {
+    assert_eq!("most".parse::<SolverObjective>().unwrap(), SolverObjective::MostColumns);
+    assert_eq!("width:16".parse::<SolverObjective>().unwrap(), SolverObjective::CellWidth(16));
+    assert!("width:0".parse::<SolverObjective>().is_err());
+    assert!("widest".parse::<SolverObjective>().is_err());
+}
@@ -0,0 +1,92 @@
+use csv_gen::CsvGen;
+use csv_gen::data::types::{ColumnSpec, CsvSchema, DataType};
+use csv_gen::generator::sql::{SqlDialect, SqlGenerator, SqlStatements};
+
+fn schema() -> anyhow::Result<CsvSchema> {
+    let tricky = vec!["O'Brien".to_string(), "back\\slash".to_string(), "tab\there".to_string()];
+    let csv = CsvGen::builder()
+        .rows(250)
+        .column("id", DataType::UniqueId)
+        .column("surname", DataType::Enum(tricky))
+        .column("price", DataType::Decimal { scale: 2 })
+        .column("day", DataType::Date)
+        .column_spec(ColumnSpec::new("note", DataType::String).with_width(30).with_null_rate(0.3))
+        .build()?;
+    Ok(csv.schema().clone())
+}
+
+fn script(dialect: SqlDialect, statements: SqlStatements) -> anyhow::Result<String> {
+    let mut output = Vec::new();
+    SqlGenerator::new(schema()?, dialect, "people")
+        .with_seed(5)
+        .with_statements(statements)
+        .write(&mut output, 250)?;
+    Ok(String::from_utf8(output)?)
+}
+
+#[test]
+fn test_create_table_per_dialect() -> anyhow::Result<()> {
+    let postgres = SqlGenerator::new(schema()?, SqlDialect::Postgres, "people").create_table();
+    assert!(postgres.starts_with("CREATE TABLE \"people\" (\n"));
+    assert!(postgres.contains("\"id\" BIGINT NOT NULL PRIMARY KEY"));
+    assert!(postgres.contains("\"surname\" VARCHAR(10) NOT NULL"));
+    assert!(postgres.contains("\"price\" NUMERIC(8, 2) NOT NULL"));
+    assert!(postgres.contains("\"day\" DATE NOT NULL"));
+    let note = postgres.lines().find(|line| line.contains("\"note\"")).unwrap();
+    assert!(note.contains("VARCHAR(") && !note.contains("NOT NULL"), "Nullable column: {}", note);
+
+    let mysql = SqlGenerator::new(schema()?, SqlDialect::MySql, "people").create_table();
+    assert!(mysql.contains("`price` DECIMAL(8, 2) NOT NULL"));
+
+    let sqlite = SqlGenerator::new(schema()?, SqlDialect::Sqlite, "people").create_table();
+    assert!(sqlite.contains("\"id\" INTEGER NOT NULL PRIMARY KEY"));
+    assert!(sqlite.contains("\"day\" TEXT NOT NULL"));
+    Ok(())
+}
+
+#[test]
+fn test_wide_decimals_stay_within_dialect_limits() -> anyhow::Result<()> {
+    let csv = CsvGen::builder()
+        .rows(10)
+        .column("id", DataType::UniqueId)
+        .column_spec(ColumnSpec::new("amount", DataType::Decimal { scale: 2 }).with_width(1500))
+        .build()?;
+    let postgres = SqlGenerator::new(csv.schema().clone(), SqlDialect::Postgres, "t").create_table();
+    assert!(postgres.contains("\"amount\" NUMERIC NOT NULL"), "{}", postgres);
+    let mysql = SqlGenerator::new(csv.schema().clone(), SqlDialect::MySql, "t").create_table();
+    assert!(mysql.contains("`amount` DECIMAL(65, 2) NOT NULL"), "{}", mysql);
+    Ok(())
+}
+
+#[test]
+fn test_batched_inserts_escape_literals() -> anyhow::Result<()> {
+    let postgres = script(SqlDialect::Postgres, SqlStatements::Insert { batch_size: 100 })?;
+    assert_eq!(postgres.matches("INSERT INTO \"people\" (\"id\", \"surname\", \"price\", \"day\", \"note\") VALUES").count(), 3);
+    assert_eq!(postgres.matches(";\n").count(), 4, "CREATE TABLE plus three INSERTs");
+    assert!(postgres.contains("(1, '"), "IDs should be unpadded numbers");
+    assert!(postgres.contains("'O''Brien'"));
+    assert!(postgres.contains("'back\\slash'"));
+    assert!(postgres.contains(", NULL)"));
+
+    let mysql = script(SqlDialect::MySql, SqlStatements::Insert { batch_size: 1000 })?;
+    assert_eq!(mysql.matches("INSERT INTO `people`").count(), 1);
+    assert!(mysql.contains("'back\\\\slash'"));
+    assert!(mysql.contains("'O''Brien'"));
+    Ok(())
+}
+
+#[test]
+fn test_copy_from_stdin() -> anyhow::Result<()> {
+    let copy = script(SqlDialect::Postgres, SqlStatements::Copy)?;
+    let block = copy.split_once("COPY \"people\" (\"id\", \"surname\", \"price\", \"day\", \"note\") FROM STDIN;\n").unwrap().1;
+    let lines: Vec<&str> = block.lines().collect();
+    assert_eq!(lines.len(), 251);
+    assert_eq!(lines[250], "\\.");
+    assert!(lines[..250].iter().all(|line| line.split('\t').count() == 5));
+    assert!(block.contains("tab\\there"));
+    assert!(block.contains("back\\\\slash"));
+    assert!(block.contains("\t\\N\n"));
+
+    assert!(script(SqlDialect::MySql, SqlStatements::Copy).is_err());
+    Ok(())
+}
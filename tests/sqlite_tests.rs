@@ -0,0 +1,82 @@
+#![cfg(feature = "sqlite")]
+
+use csv_gen::CsvGen;
+use csv_gen::data::types::{ColumnSpec, CsvSchema, DataType};
+use csv_gen::generator::sqlite::{SqliteSink, SqliteTable};
+use rusqlite::Connection;
+use std::fs;
+
+fn schema(columns: Vec<ColumnSpec>) -> anyhow::Result<CsvSchema> {
+    let mut builder = CsvGen::builder().rows(1000);
+    for column in columns {
+        builder = builder.column_spec(column);
+    }
+    Ok(builder.build()?.schema().clone())
+}
+
+#[test]
+fn test_tables_indexes_and_types() -> anyhow::Result<()> {
+    let path = "test_output/sqlite_sink.db";
+    fs::create_dir_all("test_output")?;
+    let _ = fs::remove_file(path);
+
+    let users = schema(vec![
+        ColumnSpec::new("id", DataType::UniqueId),
+        ColumnSpec::new("email", DataType::Email),
+        ColumnSpec::new("age", DataType::Range { min: 18, max: 99 }),
+        ColumnSpec::new("nickname", DataType::Name).with_null_rate(0.5),
+    ])?;
+    let orders = schema(vec![
+        ColumnSpec::new("id", DataType::UniqueId),
+        ColumnSpec::new("user_id", DataType::Range { min: 1, max: 1000 }),
+        ColumnSpec::new("total", DataType::Decimal { scale: 2 }),
+    ])?;
+
+    let mut sink = SqliteSink::open(path)?.with_transaction_rows(300);
+    assert_eq!(sink.write_table(SqliteTable::new("users", users.clone(), 1000).with_seed(1).with_index("email"))?, 1000);
+    assert_eq!(sink.write_table(SqliteTable::new("orders", orders, 2500).with_index("user_id"))?, 2500);
+    drop(sink);
+
+    let db = Connection::open(path)?;
+    assert_eq!(count_rows(&db, "users")?, 1000);
+    assert_eq!(count_rows(&db, "orders")?, 2500);
+
+    let (max_id, typeof_age, typeof_total): (i64, String, String) = db.query_row(
+        "SELECT MAX(u.id), typeof(u.age), (SELECT typeof(total) FROM orders LIMIT 1) FROM users u",
+        [],
+        |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+    )?;
+    assert_eq!(max_id, 1000);
+    assert_eq!(typeof_age, "integer");
+    assert_eq!(typeof_total, "real");
+
+    let nulls: i64 = db.query_row("SELECT COUNT(*) FROM users WHERE nickname IS NULL", [], |row| row.get(0))?;
+    assert!((350..650).contains(&nulls), "Expected about half null, got {}", nulls);
+
+    let indexes: i64 = db.query_row(
+        "SELECT COUNT(*) FROM sqlite_master WHERE type = 'index' AND name IN ('idx_users_email', 'idx_orders_user_id')",
+        [],
+        |row| row.get(0),
+    )?;
+    assert_eq!(indexes, 2);
+    let duplicate = db.execute("INSERT INTO users (id, email, age) VALUES (1, 'x@y.z', 20)", []);
+    assert!(duplicate.is_err(), "id should be the primary key");
+    drop(db);
+
+    // Rewriting a table replaces it and leaves the others alone
+    let mut sink = SqliteSink::open(path)?;
+    sink.write_table(SqliteTable::new("users", users.clone(), 10))?;
+    assert!(sink.write_table(SqliteTable::new("bad", users, 10).with_index("missing")).is_err());
+    drop(sink);
+    let db = Connection::open(path)?;
+    assert_eq!(count_rows(&db, "users")?, 10);
+    assert_eq!(count_rows(&db, "orders")?, 2500);
+
+    drop(db);
+    fs::remove_file(path)?;
+    Ok(())
+}
+
+fn count_rows(db: &Connection, table: &str) -> rusqlite::Result<i64> {
+    db.query_row(&format!("SELECT COUNT(*) FROM {}", table), [], |row| row.get(0))
+}
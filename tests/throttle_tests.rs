@@ -0,0 +1,91 @@
+use csv_gen::CsvGen;
+use csv_gen::data::types::{CsvSchema, DataType};
+use csv_gen::generator::size_based::CsvGenerator;
+use csv_gen::generator::throttle::{Burst, Rate, Throttle};
+use std::io::{self, Write};
+use std::time::{Duration, Instant};
+
+fn schema() -> anyhow::Result<CsvSchema> {
+    let csv = CsvGen::builder()
+        .rows(100)
+        .column("id", DataType::UniqueId)
+        .column("amount", DataType::Decimal { scale: 2 })
+        .build()?;
+    Ok(csv.schema().clone())
+}
+
+/// Takes `capacity` bytes, then fails like a pipe whose reader has gone away.
+struct Pipe {
+    received: Vec<u8>,
+    capacity: usize,
+}
+
+impl Write for Pipe {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if self.received.len() >= self.capacity {
+            return Err(io::ErrorKind::BrokenPipe.into());
+        }
+        let taken = buf.len().min(self.capacity - self.received.len());
+        self.received.extend_from_slice(&buf[..taken]);
+        Ok(taken)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+#[test]
+fn test_rates_and_burst_patterns() -> anyhow::Result<()> {
+    assert_eq!("500rows/s".parse::<Rate>()?, Rate::RowsPerSecond(500.0));
+    assert_eq!("2.5k rows/s".parse::<Rate>()?, Rate::RowsPerSecond(2500.0));
+    assert_eq!("2MB/s".parse::<Rate>()?, Rate::BytesPerSecond(2_000_000.0));
+    assert!("500rows".parse::<Rate>().is_err() && "0rows/s".parse::<Rate>().is_err());
+
+    assert_eq!("sine:period=10".parse::<Burst>()?, Burst::Sine { period: 10.0, amplitude: 0.5 });
+    assert!("sine:amplitude=2".parse::<Burst>().is_err());
+    assert!("spikes:every=1,length=2".parse::<Burst>().is_err());
+
+    // Whole periods average out to the base rate; spikes add their extra rows on top
+    let sine = Burst::Sine { period: 10.0, amplitude: 0.5 };
+    assert!((sine.allowance(100.0, 20.0) - 2000.0).abs() < 1e-6);
+    assert!(sine.allowance(100.0, 5.0) > 500.0, "The first half period runs fast");
+    let spikes = Burst::Spikes { every: 10.0, length: 1.0, factor: 5.0 };
+    assert_eq!(spikes.allowance(100.0, 1.0), 500.0);
+    assert_eq!(spikes.allowance(100.0, 10.0), 1400.0);
+
+    let throttle = Throttle::new(Rate::RowsPerSecond(100.0), spikes);
+    assert_eq!(throttle.due(500.0).as_millis(), 1000);
+    assert_eq!(throttle.due(600.0).as_millis(), 2000, "Back to the base rate after the spike");
+    Ok(())
+}
+
+#[test]
+fn test_rate_paces_the_rows() -> anyhow::Result<()> {
+    let started = Instant::now();
+    let mut output = Vec::new();
+    let rows = CsvGenerator::new(schema()?)
+        .without_progress()
+        .with_rate(Rate::RowsPerSecond(1000.0), Burst::Steady)
+        .write(&mut output, 200)?;
+    let elapsed = started.elapsed();
+
+    assert_eq!(rows, 200);
+    assert_eq!(output.iter().filter(|&&b| b == b'\n').count(), 201);
+    assert!(elapsed >= Duration::from_millis(180), "Took {:?}", elapsed);
+    assert!(elapsed < Duration::from_secs(2), "Took {:?}", elapsed);
+    Ok(())
+}
+
+#[test]
+fn test_follow_stops_when_the_reader_goes_away() -> anyhow::Result<()> {
+    let mut pipe = Pipe { received: Vec::new(), capacity: 100_000 };
+    let rows = CsvGenerator::new(schema()?).without_progress().follow(&mut pipe)?;
+    assert!(rows > 1000, "Keeps going past the planned 100 rows");
+    assert_eq!(pipe.received.len(), 100_000);
+
+    // A fixed row count that can't all be written is still an error
+    let mut pipe = Pipe { received: Vec::new(), capacity: 1000 };
+    assert!(CsvGenerator::new(schema()?).without_progress().write(&mut pipe, 1000).is_err());
+    Ok(())
+}
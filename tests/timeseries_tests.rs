@@ -0,0 +1,141 @@
+use csv_gen::data::schema::SchemaBuilder;
+use csv_gen::data::timeseries::{SeriesKind, SeriesSpec, TimestampFormat, TimestampSpec};
+use csv_gen::data::types::FixedColumn;
+use csv_gen::generator::size_based::CsvGenerator;
+use csv_gen::utils::dates;
+use std::fs;
+
+fn timestamp_spec(format: TimestampFormat) -> TimestampSpec {
+    TimestampSpec {
+        start_ms: dates::parse_timestamp_ms("2024-02-28T23:59:58Z").unwrap(),
+        interval_ms: 1000,
+        jitter_ms: 0,
+        gap_rate: 0.0,
+        gap_ms: 0,
+        out_of_order_rate: 0.0,
+        format,
+    }
+}
+
+fn generate_columns(fixed_columns: &[FixedColumn], num_rows: usize, output_path: &str) -> anyhow::Result<Vec<Vec<String>>> {
+    let schema = SchemaBuilder::build_schema_with_fixed_columns(4096.max(num_rows * 32), num_rows, 2, 10, 0, 0, fixed_columns)?;
+    let mut generator = CsvGenerator::new(schema);
+    generator.generate(output_path, num_rows)?;
+
+    let content = fs::read_to_string(output_path)?;
+    fs::remove_file(output_path)?;
+    Ok(content
+        .lines()
+        .map(|line| line.split(',').map(str::to_string).collect())
+        .collect())
+}
+
+#[test]
+fn test_timestamp_formats_have_exact_widths() {
+    let millis = dates::parse_timestamp_ms("2024-02-29T13:05:09Z").unwrap() + 42;
+    let cases = [
+        (TimestampFormat::Iso8601, "2024-02-29T13:05:09Z"),
+        (TimestampFormat::Iso8601Millis, "2024-02-29T13:05:09.042Z"),
+        (TimestampFormat::DateTime, "2024-02-29 13:05:09"),
+        (TimestampFormat::UnixSeconds, "1709211909"),
+        (TimestampFormat::UnixMillis, "1709211909042"),
+    ];
+    for (format, expected) in cases {
+        assert_eq!(format.format(millis), expected);
+        assert_eq!(format.width(), expected.len());
+    }
+}
+
+#[test]
+fn test_timestamps_increase_monotonically() -> anyhow::Result<()> {
+    let rows = generate_columns(&[FixedColumn::timestamp(timestamp_spec(TimestampFormat::Iso8601))], 20, "test_ts_monotonic.csv")?;
+
+    assert!(rows[0][1].starts_with("timestamp"));
+    assert_eq!(rows[1][1], "2024-02-28T23:59:58Z");
+    assert_eq!(rows[3][1], "2024-02-29T00:00:00Z");
+    for pair in rows[1..].windows(2) {
+        assert!(pair[0][1] < pair[1][1], "Not increasing: {} then {}", pair[0][1], pair[1][1]);
+    }
+    Ok(())
+}
+
+#[test]
+fn test_gaps_and_out_of_order_records() -> anyhow::Result<()> {
+    let mut spec = timestamp_spec(TimestampFormat::UnixMillis);
+    spec.gap_rate = 1.0;
+    spec.gap_ms = 60_000;
+    let rows = generate_columns(&[FixedColumn::timestamp(spec)], 10, "test_ts_gaps.csv")?;
+    let times: Vec<i64> = rows[1..].iter().map(|r| r[1].parse().unwrap()).collect();
+    assert!(times.windows(2).all(|w| w[1] - w[0] == 61_000));
+
+    let mut spec = timestamp_spec(TimestampFormat::UnixMillis);
+    spec.out_of_order_rate = 0.5;
+    let rows = generate_columns(&[FixedColumn::timestamp(spec)], 200, "test_ts_out_of_order.csv")?;
+    let times: Vec<i64> = rows[1..].iter().map(|r| r[1].parse().unwrap()).collect();
+    assert!(times.windows(2).any(|w| w[1] < w[0]), "Expected some out-of-order rows");
+    Ok(())
+}
+
+#[test]
+fn test_series_columns() -> anyhow::Result<()> {
+    let trend = SeriesSpec {
+        kind: "trend:start=10,slope=2,noise=0".parse()?,
+        precision: 2,
+    };
+    let seasonal = SeriesSpec {
+        kind: "seasonal:base=0,amplitude=5,period=4,noise=0".parse()?,
+        precision: 1,
+    };
+    let walk = SeriesSpec {
+        kind: SeriesKind::RandomWalk { start: 50.0, step: 1.0 },
+        precision: 2,
+    };
+    let columns = [
+        FixedColumn::timestamp(timestamp_spec(TimestampFormat::Iso8601)),
+        FixedColumn::series(1, trend, 8),
+        FixedColumn::series(2, seasonal, 6),
+        FixedColumn::series(3, walk, 8),
+    ];
+    let rows = generate_columns(&columns, 8, "test_ts_series.csv")?;
+
+    let trend: Vec<&str> = rows[1..].iter().map(|r| r[2].as_str()).collect();
+    assert_eq!(&trend[..3], ["00010.00", "00012.00", "00014.00"]);
+
+    let seasonal: Vec<&str> = rows[1..].iter().map(|r| r[3].as_str()).collect();
+    assert_eq!(&seasonal[..4], ["0000.0", "0005.0", "0000.0", "-005.0"]);
+
+    let walk: Vec<f64> = rows[1..].iter().map(|r| r[4].parse().unwrap()).collect();
+    assert_eq!(walk[0], 50.0);
+    assert!(walk.windows(2).all(|w| (w[1] - w[0]).abs() <= 1.0 + 1e-9));
+    assert!(rows[1..].iter().all(|r| r[4].len() == 8));
+    Ok(())
+}
+
+#[test]
+fn test_series_values_are_clamped_to_width() -> anyhow::Result<()> {
+    let spec = SeriesSpec {
+        kind: "trend".parse()?,
+        precision: 2,
+    };
+    assert_eq!(spec.format(123456.789, 6), "999.99");
+    assert_eq!(spec.format(-123456.789, 6), "-99.99");
+    assert_eq!(spec.format(-1.5, 6), "-01.50");
+    Ok(())
+}
+
+#[test]
+fn test_parse_durations_and_start_times() {
+    assert_eq!(dates::parse_duration_ms("500ms"), Some(500));
+    assert_eq!(dates::parse_duration_ms("1.5s"), Some(1500));
+    assert_eq!(dates::parse_duration_ms("2h"), Some(7_200_000));
+    assert_eq!(dates::parse_duration_ms("5"), None);
+
+    assert_eq!(dates::parse_timestamp_ms("1970-01-02"), Some(86_400_000));
+    assert_eq!(dates::parse_timestamp_ms("1700000000"), Some(1_700_000_000_000));
+    assert_eq!(dates::parse_timestamp_ms("2024-01-01 00:00:01"), Some(1_704_067_201_000));
+    assert_eq!(dates::parse_timestamp_ms("2024-13-01"), None);
+    assert_eq!(dates::parse_timestamp_ms("99999999999999999"), None, "Overflows as milliseconds");
+    assert_eq!(dates::parse_timestamp_ms("253402300800"), None, "Past year 9999");
+    assert_eq!(dates::parse_duration_ms("100000000000d"), None);
+    assert_eq!(dates::parse_duration_ms("3650d"), Some(315_360_000_000));
+}
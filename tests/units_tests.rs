@@ -0,0 +1,52 @@
+use csv_gen::utils::units::{format_rate, format_size, parse_byte_rate, parse_count, parse_size};
+
+#[test]
+fn test_sizes_follow_si_and_iec_prefixes() -> anyhow::Result<()> {
+    assert_eq!(parse_size("1MB")?, 1_000_000);
+    assert_eq!(parse_size("1MiB")?, 1_048_576);
+    assert_eq!(parse_size("500kb")?, 500_000);
+    assert_eq!(parse_size("2.5 GiB")?, 2_684_354_560);
+    assert_eq!(parse_size("1TB")?, 1_000_000_000_000);
+    assert_eq!(parse_size("4TiB")?, 4 << 40);
+    assert_eq!(parse_size("1_500KB")?, 1_500_000);
+    assert_eq!(parse_size("100")?, 100);
+    assert_eq!(parse_size("1.5B")?, 2, "Rounds to the nearest byte");
+    assert_eq!(parse_byte_rate("64KiB/s")?, 65_536);
+
+    for invalid in ["", "MB", "1XB", "1..5MB", "_1KB", "1__000", "1_KB", "-1MB", "1.MB"] {
+        assert!(parse_size(invalid).is_err(), "'{}' should be rejected", invalid);
+    }
+    Ok(())
+}
+
+#[test]
+fn test_counts_take_suffixes_and_must_be_whole() -> anyhow::Result<()> {
+    assert_eq!(parse_count("5000")?, 5000);
+    assert_eq!(parse_count("10M")?, 10_000_000);
+    assert_eq!(parse_count("2.5k")?, 2500);
+    assert_eq!(parse_count("1.1k")?, 1100);
+    assert_eq!(parse_count("1_000_000")?, 1_000_000);
+    assert_eq!(parse_count("3G")?, 3_000_000_000);
+
+    assert!(parse_count("2.5").is_err(), "Half a row");
+    assert!(parse_count("1.0005k").is_err());
+    assert!(parse_count("10MB").is_err(), "Bytes are not rows");
+    assert!(parse_count("ten").is_err());
+    Ok(())
+}
+
+#[test]
+fn test_formatting_reads_back() -> anyhow::Result<()> {
+    assert_eq!(format_size(512), "512 B");
+    assert_eq!(format_size(1_048_576), "1.05 MB");
+    assert_eq!(format_size(20_000), "20.0 KB");
+    assert_eq!(format_size(999_999), "1.00 MB", "Never 1000 KB");
+    assert_eq!(format_size(123_456_789_000), "123 GB");
+    assert_eq!(format_rate(2_500_000.0), "2.50 MB/s");
+
+    for bytes in [1_234u64, 56_789, 9_876_543, 4_321_000_000] {
+        let read_back = parse_size(&format_size(bytes))? as f64;
+        assert!((read_back - bytes as f64).abs() / (bytes as f64) < 0.005, "{}", bytes);
+    }
+    Ok(())
+}
@@ -0,0 +1,89 @@
+use csv_gen::data::inference::{infer_schema, DEFAULT_MAX_ENUM_VALUES};
+use csv_gen::data::planner;
+use csv_gen::data::types::SchemaSpec;
+use csv_gen::data::verify::Verifier;
+use csv_gen::generator::size_based::CsvGenerator;
+use std::fs;
+
+const SAMPLE: &str = "\
+id,email,price,joined,status,notes
+1,alice@example.com,12.50,2023-01-05,active,likes apples
+2,bob@test.org,3.99,2023-02-11,inactive,
+3,carol.w@mail.net,100.00,2022-12-30,active,prefers email
+4,dan@x.io,7.25,2021-07-04,active,called twice
+";
+
+fn spec() -> anyhow::Result<SchemaSpec> {
+    infer_schema(SAMPLE.as_bytes(), DEFAULT_MAX_ENUM_VALUES)
+}
+
+#[test]
+fn test_generated_file_passes() -> anyhow::Result<()> {
+    let output_path = "test_verify_generated.csv";
+    let plan = planner::plan_from_spec(&spec()?, Some(200_000), Some(2000))?;
+    CsvGenerator::new(plan.schema).with_seed(8).generate(output_path, plan.num_rows)?;
+
+    let report = Verifier::new(spec()?).with_rows(2000).with_size(200_000).verify_file(output_path)?;
+    assert!(report.passed(), "Unexpected violations: {:?}", report.violations);
+    assert_eq!(report.rows, 2000);
+    assert_eq!(report.bytes, fs::metadata(output_path)?.len());
+
+    let wrong = Verifier::new(spec()?).with_rows(1999).with_size(100_000).verify_file(output_path)?;
+    assert_eq!(wrong.total_violations, 2);
+
+    fs::remove_file(output_path)?;
+    Ok(())
+}
+
+#[test]
+fn test_violations_have_line_numbers() -> anyhow::Result<()> {
+    let bad = "\
+id,email,price,joined,status,note
+1,alice@example.com,12.50,2023-01-05,active,x
+1,bob@test.org,3.999,2023-02-30,gone,y
+3,not-an-email,1.00,2022-12-30,active
+,dan@x.io,7.25,2021-07-04,inactive,z
+";
+    let report = Verifier::new(spec()?).verify(bad.as_bytes())?;
+    assert!(!report.passed());
+    let messages: Vec<String> = report.violations.iter().map(ToString::to_string).collect();
+    let on_line = |line: u64, column: &str| {
+        report.violations.iter().any(|v| v.line == Some(line) && v.column.as_deref() == Some(column))
+    };
+
+    assert!(messages.contains(&"line 1: header 6 is 'note', expected 'notes'".to_string()));
+    assert!(on_line(3, "id"), "Repeated ID: {:?}", messages);
+    assert!(messages.iter().any(|m| m.contains("'1' is not above the ID '1' on line 2")));
+    assert!(on_line(3, "price") && on_line(3, "joined") && on_line(3, "status"));
+    assert!(messages.contains(&"line 4: 5 fields, expected 6".to_string()));
+    assert!(on_line(5, "id"), "Empty ID in a column without nulls");
+    assert_eq!(report.rows, 4);
+
+    let capped = Verifier::new(spec()?).with_max_violations(2).verify(bad.as_bytes())?;
+    assert_eq!(capped.violations.len(), 2);
+    assert_eq!(capped.total_violations, report.total_violations);
+    Ok(())
+}
+
+#[test]
+fn test_ids_ascend_and_other_unique_columns_on_request() -> anyhow::Result<()> {
+    let spec = infer_schema("code,tag\n1,a\n2,b\n3,c\n".as_bytes(), 1)?;
+    assert!(spec.columns.iter().all(|c| c.unique));
+
+    // Zero-padded IDs compare by value, and gaps are fine
+    let ordered = "code,tag\n0009,x\n0010,y\n0012,z\n0013,y\n";
+    let report = Verifier::new(spec.clone()).verify(ordered.as_bytes())?;
+    assert!(report.violations.iter().all(|v| v.column.as_deref() != Some("code")), "{:?}", report.violations);
+    let report = Verifier::new(spec.clone()).verify("code,tag\n2,x\n10,y\n9,z\n".as_bytes())?;
+    assert_eq!(report.violations[0].to_string(), "line 4: column code: '9' is not above the ID '10' on line 3");
+
+    // Repeated tags only count when asked for, and past the limit the check fails
+    let tags = |limit: usize| -> anyhow::Result<Vec<String>> {
+        let report = Verifier::new(spec.clone()).with_unique_limit(limit).verify(ordered.as_bytes())?;
+        Ok(report.violations.iter().filter(|v| v.column.as_deref() == Some("tag")).map(|v| v.message.clone()).collect())
+    };
+    assert!(tags(0)?.is_empty());
+    assert_eq!(tags(10)?, ["'y' repeats the value on line 3"]);
+    assert_eq!(tags(2)?, ["more than 2 distinct values to check for repeats; raise the unique limit"]);
+    Ok(())
+}
@@ -0,0 +1,64 @@
+#![cfg(feature = "xlsx")]
+
+use csv_gen::CsvGen;
+use csv_gen::data::types::{CsvSchema, DataType};
+use csv_gen::generator::xlsx::{XlsxSheet, XlsxWriter};
+use std::fs::{self, File};
+use std::io::Read;
+
+fn schema() -> anyhow::Result<CsvSchema> {
+    let csv = CsvGen::builder()
+        .rows(1000)
+        .column("id", DataType::UniqueId)
+        .column("amount", DataType::Decimal { scale: 2 })
+        .column("joined", DataType::Date)
+        .column("notes", DataType::String)
+        .build()?;
+    Ok(csv.schema().clone())
+}
+
+fn read_entry(archive: &mut zip::ZipArchive<File>, name: &str) -> anyhow::Result<String> {
+    let mut content = String::new();
+    archive.by_name(name)?.read_to_string(&mut content)?;
+    Ok(content)
+}
+
+#[test]
+fn test_typed_cells_and_sheet_rollover() -> anyhow::Result<()> {
+    let path = "test_output/workbook.xlsx";
+    fs::create_dir_all("test_output")?;
+
+    let mut writer = XlsxWriter::new().with_rows_per_sheet(400);
+    assert_eq!(writer.write_sheet(XlsxSheet::new("people", schema()?, 1000).with_seed(4))?, 1000);
+    assert_eq!(writer.write_sheet(XlsxSheet::new("orders/2024", schema()?, 10))?, 10);
+    assert_eq!(writer.sheet_names(), ["people", "people (2)", "people (3)", "orders_2024"]);
+    writer.save(path)?;
+
+    let mut archive = zip::ZipArchive::new(File::open(path)?)?;
+    let workbook = read_entry(&mut archive, "xl/workbook.xml")?;
+    assert!(workbook.contains("name=\"people (3)\""));
+
+    let rows_in = |sheet: &str| sheet.matches("<row ").count();
+    let first = read_entry(&mut archive, "xl/worksheets/sheet1.xml")?;
+    assert_eq!(rows_in(&first), 401, "Header plus 400 rows");
+    assert_eq!(rows_in(&read_entry(&mut archive, "xl/worksheets/sheet3.xml")?), 201);
+    assert_eq!(rows_in(&read_entry(&mut archive, "xl/worksheets/sheet4.xml")?), 11);
+
+    // The zero-padded ID stays text; amounts and dates are numeric cells with formats
+    assert!(first.contains("<c r=\"A2\" t=\"inlineStr\"><is><t>0001</t></is></c>"));
+    let amount = first.split("<c r=\"B2\"").nth(1).unwrap();
+    assert!(amount.starts_with(" s=\"") && !amount.split("</c>").next().unwrap().contains("inlineStr"));
+    let joined = first.split("<c r=\"C2\"").nth(1).unwrap();
+    assert!(!joined.split("</c>").next().unwrap().contains("inlineStr"), "Dates should be Excel dates");
+
+    fs::remove_file(path)?;
+    Ok(())
+}
+
+#[test]
+fn test_too_many_columns_is_an_error() -> anyhow::Result<()> {
+    let csv = CsvGen::builder().rows(2).columns(16_385).build()?;
+    let mut writer = XlsxWriter::new();
+    assert!(writer.write_sheet(XlsxSheet::new("wide", csv.schema().clone(), 2)).is_err());
+    Ok(())
+}